@@ -0,0 +1,129 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! # Embedded Catalogs
+//!
+//! [`include_messages!`] reads a `.properties` catalog of ICU patterns
+//! when the program is compiled, in the way described for
+//! [`Catalog::read_properties`], and embeds its entries in the program
+//! as a `static` table:
+//!
+//! ```ignore
+//! #[macro_use]
+//! extern crate message_format_macros;
+//!
+//! let catalog = include_messages!("../locales/en.properties");
+//! ```
+//!
+//! A missing file, an escape which can not be read and a pattern which
+//! does not parse are compile errors, which name the line and the key
+//! of the entry. Reading the catalog needs no file and no parsing of
+//! the `.properties` syntax, but each distinct pattern is still parsed
+//! once when the catalog is built, since the parts of a `Message` are
+//! boxed trait objects which can not be built in a `static`.
+//!
+//! [`include_messages!`]: macro.include_messages.html
+//! [`Catalog::read_properties`]: https://endoli.github.io/message-format.rs/message_format/struct.Catalog.html#method.read_properties
+
+#![deny(missing_docs)]
+
+extern crate message_format;
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use message_format::{icu, properties};
+use proc_macro2::TokenStream;
+use syn::LitStr;
+
+/// Embed a `.properties` catalog in the program, checking its patterns
+/// when the program is compiled, and build a `Catalog` from it.
+///
+/// The path is relative to the file which uses the macro, as with
+/// `include_str!`, or to the root of the crate if the compiler does not
+/// say which file that is.
+#[proc_macro]
+pub fn include_messages(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let path = syn::parse_macro_input!(input as LitStr);
+    let base = proc_macro::Span::call_site()
+        .local_file()
+        .and_then(|file| file.parent().map(Path::to_path_buf))
+        .or_else(|| env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from))
+        .unwrap_or_default();
+    match expand(&base.join(path.value())) {
+        Ok(tokens) => tokens.into(),
+        Err(message) => syn::Error::new(path.span(), message)
+            .to_compile_error()
+            .into(),
+    }
+}
+
+/// The code which builds the catalog in the file at `path`, or what is
+/// wrong with the file.
+fn expand(path: &Path) -> Result<TokenStream, String> {
+    // The path is made absolute for `include_bytes!`, which would
+    // otherwise take it as relative to the file using the macro.
+    let path = &path
+        .canonicalize()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut entries = vec![];
+    for entry in properties::read(BufReader::new(file)) {
+        let entry = entry.map_err(|e| format!("{}: {}", path.display(), e))?;
+        if let Err(e) = icu::parse(&entry.pattern) {
+            return Err(format!(
+                "{}:{}: {}: {}",
+                path.display(),
+                entry.line,
+                entry.key,
+                e
+            ));
+        }
+        let key = &entry.key;
+        let pattern = &entry.pattern;
+        let description = match entry.description {
+            Some(ref description) => quote!(Some(#description)),
+            None => quote!(None),
+        };
+        entries.push(quote!((#key, #pattern, #description)));
+    }
+    let path = path
+        .to_str()
+        .ok_or_else(|| format!("{}: Expected a UTF-8 path", path.display()))?;
+    Ok(quote! {{
+        // The file is included as well so that the program is rebuilt
+        // when it changes.
+        const _: &[u8] = include_bytes!(#path);
+        static ENTRIES: &[(&str, &str, Option<&str>)] = &[#(#entries),*];
+        ::message_format::Catalog::from_checked_entries(ENTRIES)
+    }})
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::expand;
+
+    #[test]
+    fn broken_patterns_are_reported() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/locales/broken.properties");
+        let error = expand(&path).unwrap_err();
+        assert!(
+            error.ends_with("broken.properties:3: farewell: Expected '}' at offset 10"),
+            "{}",
+            error
+        );
+        assert!(expand(Path::new("missing.properties")).is_err());
+    }
+}
@@ -0,0 +1,32 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[macro_use]
+extern crate message_format;
+#[macro_use]
+extern crate message_format_macros;
+
+use message_format::Context;
+
+#[test]
+fn catalogs_are_embedded() {
+    let ctx = Context::default();
+    let catalog = include_messages!("locales/en.properties");
+    assert_eq!(catalog.len(), 2);
+    assert_eq!(
+        format_message!(ctx, catalog.get("greeting").unwrap(), name => "Ann"),
+        "Hello, Ann!"
+    );
+    assert_eq!(
+        catalog
+            .metadata("title")
+            .unwrap()
+            .description
+            .as_ref()
+            .unwrap(),
+        "The title of the window."
+    );
+}
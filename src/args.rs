@@ -4,7 +4,16 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use super::Value;
+#[cfg(feature = "std")]
+use std::borrow::Borrow;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+
+use super::{Context, Value};
+use prelude::*;
 
 /// Holds the arguments being used to format a [`Message`].
 ///
@@ -25,6 +34,187 @@ pub struct Args<'a> {
     pub prev: Option<&'a Args<'a>>,
 }
 
+/// A source of named arguments for formatting a [`Message`].
+///
+/// This is implemented by [`Args`] and may be implemented by any other
+/// container of argument values.
+///
+/// ```
+/// use message_format::{arg, Arguments, Value};
+///
+/// let args = arg("count", 3);
+/// assert_eq!(args.lookup("count"), Some(Value::Number(3)));
+/// assert_eq!(args.lookup("name"), None);
+/// ```
+///
+/// [`Message`]: struct.Message.html
+/// [`Args`]: struct.Args.html
+pub trait Arguments {
+    /// Look up the value of the argument with the given `name`.
+    fn lookup(&self, name: &str) -> Option<Value<'_>>;
+
+    /// The names of the arguments, for finding any which a message does
+    /// not use.
+    ///
+    /// The default returns no names, so containers which can list their
+    /// arguments should override this.
+    fn names(&self) -> Vec<&str> {
+        vec![]
+    }
+
+    /// Write the message with the given `id` to `stream`, for a
+    /// reference to it such as `{@common.app_name}` in the message
+    /// being formatted, or return `None` if there is no such message.
+    ///
+    /// The default has no messages. The arguments that
+    /// `Catalog::format` gives a message write the other messages of
+    /// the catalog, with the same arguments.
+    fn write_reference(
+        &self,
+        ctx: &Context,
+        id: &str,
+        stream: &mut dyn fmt::Write,
+    ) -> Option<fmt::Result> {
+        let _ = (ctx, id, stream);
+        None
+    }
+}
+
+impl<'a> Arguments for Args<'a> {
+    fn lookup(&self, name: &str) -> Option<Value<'_>> {
+        let mut args = Some(self);
+        while let Some(arg) = args {
+            if arg.name == name {
+                return Some(arg.value);
+            }
+            args = arg.prev;
+        }
+        None
+    }
+
+    fn names(&self) -> Vec<&str> {
+        let mut names = vec![];
+        let mut args = Some(self);
+        while let Some(arg) = args {
+            names.push(arg.name);
+            args = arg.prev;
+        }
+        // The last argument is first in the list.
+        names.reverse();
+        names
+    }
+}
+
+/// Arguments which fall back to default values for any argument that
+/// the caller did not give.
+///
+/// ```
+/// use message_format::{icu, Context, Value, WithDefaults};
+///
+/// let ctx = Context::default();
+/// let m = icu::parse("Hello, {name}!").unwrap();
+/// let defaults = [("name", Value::Str("there"))];
+/// let args = [("name", Value::Str("Ann"))];
+/// assert_eq!(ctx.format(&m, Some(&WithDefaults::new(None, &defaults))), "Hello, there!");
+/// assert_eq!(ctx.format(&m, Some(&WithDefaults::new(Some(&args), &defaults))), "Hello, Ann!");
+/// ```
+pub struct WithDefaults<'a> {
+    args: Option<&'a dyn Arguments>,
+    defaults: &'a dyn Arguments,
+}
+
+impl<'a> WithDefaults<'a> {
+    /// Look up arguments in `args` first and then in `defaults`.
+    pub fn new(args: Option<&'a dyn Arguments>, defaults: &'a dyn Arguments) -> Self {
+        WithDefaults { args, defaults }
+    }
+}
+
+impl<'a> Arguments for WithDefaults<'a> {
+    fn lookup(&self, name: &str) -> Option<Value<'_>> {
+        self.args
+            .and_then(|args| args.lookup(name))
+            .or_else(|| self.defaults.lookup(name))
+    }
+
+    /// Only the names of the given arguments, since a message need not
+    /// use every default.
+    fn names(&self) -> Vec<&str> {
+        self.args.map_or_else(Vec::new, |args| args.names())
+    }
+
+    fn write_reference(
+        &self,
+        ctx: &Context,
+        id: &str,
+        stream: &mut dyn fmt::Write,
+    ) -> Option<fmt::Result> {
+        self.args
+            .and_then(|args| args.write_reference(ctx, id, stream))
+    }
+}
+
+/// Arguments may also be held in a slice, array or `Vec` of name and
+/// value pairs, which are searched in order:
+///
+/// ```
+/// use message_format::{icu, Context, Value};
+///
+/// let ctx = Context::default();
+/// let m = icu::parse("{name} is from {city}.").unwrap();
+/// let args = [("name", Value::Str("Hendrik")), ("city", Value::Str("Berlin"))];
+/// assert_eq!(ctx.format(&m, Some(&args)), "Hendrik is from Berlin.");
+/// ```
+impl<'a> Arguments for &[(&'a str, Value<'a>)] {
+    fn lookup(&self, name: &str) -> Option<Value<'_>> {
+        self.iter().find(|arg| arg.0 == name).map(|arg| arg.1)
+    }
+
+    fn names(&self) -> Vec<&str> {
+        self.iter().map(|arg| arg.0).collect()
+    }
+}
+
+impl<'a, const N: usize> Arguments for [(&'a str, Value<'a>); N] {
+    fn lookup(&self, name: &str) -> Option<Value<'_>> {
+        self.iter().find(|arg| arg.0 == name).map(|arg| arg.1)
+    }
+
+    fn names(&self) -> Vec<&str> {
+        self.iter().map(|arg| arg.0).collect()
+    }
+}
+
+impl<'a> Arguments for Vec<(&'a str, Value<'a>)> {
+    fn lookup(&self, name: &str) -> Option<Value<'_>> {
+        self.iter().find(|arg| arg.0 == name).map(|arg| arg.1)
+    }
+
+    fn names(&self) -> Vec<&str> {
+        self.iter().map(|arg| arg.0).collect()
+    }
+}
+
+/// Arguments may also be held in a `HashMap` with any hasher, such as
+/// the [`FastBuildHasher`] from the `fast-hash` feature. This requires
+/// the `std` feature.
+///
+/// [`FastBuildHasher`]: type.FastBuildHasher.html
+#[cfg(feature = "std")]
+impl<'a, K, S> Arguments for HashMap<K, Value<'a>, S>
+where
+    K: Borrow<str> + Eq + Hash,
+    S: BuildHasher,
+{
+    fn lookup(&self, name: &str) -> Option<Value<'_>> {
+        self.get(name).cloned()
+    }
+
+    fn names(&self) -> Vec<&str> {
+        self.keys().map(|key| key.borrow()).collect()
+    }
+}
+
 /// Create an argument holder.
 ///
 /// This isn't commonly used as arguments are usually set up via the
@@ -115,8 +305,8 @@ impl<'a> Args<'a> {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use super::super::Value;
+    use super::*;
 
     #[test]
     fn get_works() {
@@ -132,4 +322,41 @@ mod tests {
         assert_eq!(args.get("count").unwrap().value(), &Value::Number(3));
         assert_eq!(format!("{}", args.get("count").unwrap().value()), "3");
     }
+
+    #[test]
+    fn slices_work() {
+        let args = [("name", Value::Str("John")), ("count", Value::Number(3))];
+        assert_eq!(args.lookup("count"), Some(Value::Number(3)));
+        assert_eq!((&args[..1]).lookup("count"), None);
+        assert_eq!(args.to_vec().lookup("name"), Some(Value::Str("John")));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_maps_work() {
+        let mut args = ::std::collections::HashMap::new();
+        args.insert("name".to_string(), Value::Str("John"));
+        assert_eq!(args.lookup("name"), Some(Value::Str("John")));
+        assert_eq!(args.lookup("city"), None);
+    }
+
+    #[test]
+    fn defaults_are_a_fallback() {
+        let defaults = [("name", Value::Str("there")), ("count", Value::Number(1))];
+        let args = arg("count", 3);
+        let args = WithDefaults::new(Some(&args), &defaults);
+        assert_eq!(args.lookup("count"), Some(Value::Number(3)));
+        assert_eq!(args.lookup("name"), Some(Value::Str("there")));
+        assert_eq!(args.lookup("city"), None);
+    }
+
+    #[test]
+    fn names_are_listed() {
+        let args = arg("a", 1);
+        let args = args.arg("b", 2);
+        assert_eq!(args.names(), vec!["a", "b"]);
+        let args = [("c", Value::Number(3))];
+        assert_eq!(args.names(), vec!["c"]);
+        assert_eq!(WithDefaults::new(None, &args).names(), Vec::<&str>::new());
+    }
 }
@@ -0,0 +1,363 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The `message-format` command, for checking and converting catalogs
+//! of ICU patterns in `.properties` files without writing any Rust.
+//!
+//! This requires the `cli` feature.
+
+extern crate message_format;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+use std::process;
+
+use message_format::convert::{self, choice, dotnet, i18next, php, printf, python};
+use message_format::icu::{self, editor};
+use message_format::properties::{self, Entry};
+use message_format::{Catalog, Metadata, Schema};
+
+const USAGE: &str = "\
+Usage: message-format <command> [options] <files>
+
+Commands:
+  validate [--source SOURCE] FILE...
+      Check that every pattern parses, and with --source that it uses
+      only the arguments of the same message in SOURCE.
+  convert --from SYNTAX FILE
+  convert --to SYNTAX FILE
+      Convert the patterns of FILE into ICU patterns from SYNTAX, which
+      is choice, dotnet, i18next, php, printf, python or python-percent,
+      or from ICU patterns into printf, python or python-percent.
+  pseudo-localize FILE
+      Rewrite the text of every pattern with accented letters.
+  diff OLD NEW
+      List the keys which were added, removed or changed.
+  coverage [--min PERCENT] SOURCE FILE...
+      Count the messages of SOURCE which each FILE translates.
+
+Output is written to standard output. The exit status is 1 if there
+were problems or differences, and 2 if the command could not be run.
+";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    match run(&args, &mut output) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(message) => {
+            let _ = output.flush();
+            eprintln!("message-format: {}", message);
+            process::exit(2);
+        }
+    }
+}
+
+/// Run the command in `args`, returning whether it found no problems.
+fn run(args: &[String], output: &mut dyn Write) -> Result<bool, String> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (&command[..], args),
+        None => return Err(format!("expected a command\n\n{}", USAGE)),
+    };
+    match command {
+        "validate" => {
+            let (source, files) = option(args, "--source");
+            if files.is_empty() {
+                return Err("expected a file to validate".to_string());
+            }
+            let schemas = match source {
+                Some(source) => Some(schemas(&read_catalog(source)?)),
+                None => None,
+            };
+            let mut valid = true;
+            for file in files {
+                let result = read_catalog(file).and_then(|catalog| match schemas {
+                    Some(ref schemas) => catalog
+                        .validate(schemas)
+                        .map_err(|error| format!("{}: {}", file, error)),
+                    None => Ok(()),
+                });
+                if let Err(message) = result {
+                    writeln!(output, "{}", message).map_err(|e| e.to_string())?;
+                    for fix in fixes(file) {
+                        writeln!(output, "  {}", fix).map_err(|e| e.to_string())?;
+                    }
+                    valid = false;
+                }
+            }
+            Ok(valid)
+        }
+        "convert" => {
+            let (from, rest) = option(args, "--from");
+            let (to, files) = option(rest, "--to");
+            let file = match (files, from.is_some() != to.is_some()) {
+                ([file], true) => file,
+                _ => return Err("expected --from or --to and a file to convert".to_string()),
+            };
+            let mut entries = read_entries(file)?;
+            for entry in &mut entries {
+                let converted = match (from, to) {
+                    (Some(syntax), _) => to_icu(syntax, &entry.pattern)?,
+                    (_, Some(syntax)) => from_icu(syntax, &entry.pattern)?,
+                    _ => unreachable!(),
+                };
+                entry.pattern = converted.map_err(|error| {
+                    format!("{}:{}: `{}`: {}", file, entry.line, entry.key, error)
+                })?;
+            }
+            properties::write(&entries, output).map_err(|e| e.to_string())?;
+            Ok(true)
+        }
+        "pseudo-localize" => {
+            let file = match args {
+                [file] => file,
+                _ => return Err("expected a file to pseudo-localize".to_string()),
+            };
+            let mut entries = read_entries(file)?;
+            for entry in &mut entries {
+                entry.pattern = icu::pseudo_localize(&entry.pattern).map_err(|error| {
+                    format!("{}:{}: `{}`: {}", file, entry.line, entry.key, error)
+                })?;
+            }
+            properties::write(&entries, output).map_err(|e| e.to_string())?;
+            Ok(true)
+        }
+        "diff" => {
+            let (old, new) = match args {
+                [old, new] => (read_entries(old)?, read_entries(new)?),
+                _ => return Err("expected the old and the new file".to_string()),
+            };
+            let changes = diff(&old, &new);
+            for change in &changes {
+                writeln!(output, "{}", change).map_err(|e| e.to_string())?;
+            }
+            Ok(changes.is_empty())
+        }
+        "coverage" => {
+            let (min, files) = option(args, "--min");
+            let min = match min {
+                Some(min) => min
+                    .parse::<f64>()
+                    .map_err(|_| format!("expected a percentage, not `{}`", min))?,
+                None => 0.0,
+            };
+            let (source, files) = match files.split_first() {
+                Some((source, files)) if !files.is_empty() => (read_entries(source)?, files),
+                _ => return Err("expected the source file and a translation".to_string()),
+            };
+            let mut covered = true;
+            for file in files {
+                let coverage = Coverage::new(&source, &read_entries(file)?);
+                writeln!(output, "{}: {}", file, coverage).map_err(|e| e.to_string())?;
+                covered &= coverage.percent() >= min;
+            }
+            Ok(covered)
+        }
+        "help" | "--help" | "-h" => {
+            output
+                .write_all(USAGE.as_bytes())
+                .map_err(|e| e.to_string())?;
+            Ok(true)
+        }
+        _ => Err(format!("unknown command `{}`\n\n{}", command, USAGE)),
+    }
+}
+
+/// Take the value of the option `name` out of `args`, if it is first.
+fn option<'a>(args: &'a [String], name: &str) -> (Option<&'a str>, &'a [String]) {
+    match args {
+        [first, value, rest @ ..] if first == name => (Some(value), rest),
+        _ => (None, args),
+    }
+}
+
+fn read_entries(path: &str) -> Result<Vec<Entry>, String> {
+    let file = File::open(path).map_err(|error| format!("{}: {}", path, error))?;
+    properties::read(BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| format!("{}: {}", path, error))
+}
+
+/// The fixes for the problems in the patterns of a file, each with the
+/// line and key of its entry.
+fn fixes(path: &str) -> Vec<String> {
+    let mut fixes = vec![];
+    for entry in read_entries(path).unwrap_or_default() {
+        for diagnostic in editor::diagnostics(&entry.pattern, &[]) {
+            for fix in diagnostic.fixes {
+                fixes.push(format!(
+                    "line {}: `{}`: {}: {}",
+                    entry.line, entry.key, diagnostic.message, fix.title
+                ));
+            }
+        }
+    }
+    fixes
+}
+
+fn read_catalog(path: &str) -> Result<Catalog, String> {
+    let file = File::open(path).map_err(|error| format!("{}: {}", path, error))?;
+    Catalog::read_properties(BufReader::new(file)).map_err(|error| format!("{}: {}", path, error))
+}
+
+/// A catalog whose metadata has a schema for each message of `source`,
+/// made from the arguments that the message uses.
+fn schemas(source: &Catalog) -> Catalog {
+    let mut schemas = Catalog::new();
+    for (key, message) in source.iter() {
+        let mut schema = Schema::new();
+        for (name, ty) in message.arguments() {
+            schema = schema.argument(&name, ty);
+        }
+        let metadata = Metadata {
+            schema: Some(schema),
+            ..Metadata::default()
+        };
+        schemas.set_metadata(key, metadata);
+    }
+    schemas
+}
+
+type Converted = Result<String, convert::ConvertError>;
+
+fn to_icu(syntax: &str, pattern: &str) -> Result<Converted, String> {
+    Ok(match syntax {
+        "choice" => choice::to_icu(pattern).map(|conversion| conversion.pattern),
+        "dotnet" => dotnet::to_icu(pattern),
+        "i18next" => i18next::to_icu(pattern),
+        "php" => php::to_icu(pattern).map(|conversion| conversion.pattern),
+        "printf" => printf::to_icu(pattern),
+        "python" => python::to_icu(pattern, python::Style::Format),
+        "python-percent" => python::to_icu(pattern, python::Style::Percent),
+        _ => return Err(format!("can not convert from `{}`", syntax)),
+    })
+}
+
+fn from_icu(syntax: &str, pattern: &str) -> Result<Converted, String> {
+    Ok(match syntax {
+        "printf" => printf::from_icu(pattern),
+        "python" => python::from_icu(pattern, python::Style::Format),
+        "python-percent" => python::from_icu(pattern, python::Style::Percent),
+        _ => return Err(format!("can not convert to `{}`", syntax)),
+    })
+}
+
+/// The keys which were added, removed or changed between two files,
+/// each marked with `+`, `-` or `~`, in the order of the keys.
+fn diff(old: &[Entry], new: &[Entry]) -> Vec<String> {
+    let patterns = |entries: &[Entry]| -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|entry| (entry.key.clone(), entry.pattern.clone()))
+            .collect()
+    };
+    let (old, new) = (patterns(old), patterns(new));
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| match (old.get(key), new.get(key)) {
+            (None, Some(_)) => Some(format!("+ {}", key)),
+            (Some(_), None) => Some(format!("- {}", key)),
+            (Some(old), Some(new)) if old != new => Some(format!("~ {}", key)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// How many of the messages of a source file a translation has.
+struct Coverage {
+    total: usize,
+    missing: Vec<String>,
+    unknown: Vec<String>,
+}
+
+impl Coverage {
+    fn new(source: &[Entry], translation: &[Entry]) -> Self {
+        let keys = |entries: &[Entry]| -> BTreeSet<String> {
+            entries.iter().map(|entry| entry.key.clone()).collect()
+        };
+        let (source, translation) = (keys(source), keys(translation));
+        Coverage {
+            total: source.len(),
+            missing: source.difference(&translation).cloned().collect(),
+            unknown: translation.difference(&source).cloned().collect(),
+        }
+    }
+
+    fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            (self.total - self.missing.len()) as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+impl std::fmt::Display for Coverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{} ({:.1}%)",
+            self.total - self.missing.len(),
+            self.total,
+            self.percent()
+        )?;
+        for key in &self.missing {
+            write!(f, "\n  missing {}", key)?;
+        }
+        for key in &self.unknown {
+            write!(f, "\n  unknown {}", key)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, option, run, Coverage};
+    use message_format::properties;
+
+    fn entries(source: &str) -> Vec<properties::Entry> {
+        properties::read(source.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn changes_are_listed() {
+        let old = entries("a=A\nb=B\nc=C\n");
+        let new = entries("c=C\nb=Bee\nd=D\n");
+        assert_eq!(diff(&old, &new), ["- a", "~ b", "+ d"]);
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn coverage_is_counted() {
+        let source = entries("a=A\nb=B\nc=C\nd=D\n");
+        let coverage = Coverage::new(&source, &entries("a=X\nc=Y\nz=Z\n"));
+        assert_eq!(
+            coverage.to_string(),
+            "2/4 (50.0%)\n  missing b\n  missing d\n  unknown z"
+        );
+    }
+
+    #[test]
+    fn commands_are_checked() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let coverage = args(&["--min", "90", "a"]);
+        let (value, rest) = option(&coverage, "--min");
+        assert_eq!((value, rest.len()), (Some("90"), 1));
+
+        let mut output = vec![];
+        assert!(run(&args(&[]), &mut output).is_err());
+        assert!(run(&args(&["frobnicate"]), &mut output).is_err());
+        assert!(run(&args(&["convert", "--from", "cobol", "x"]), &mut output).is_err());
+        assert_eq!(run(&args(&["help"]), &mut output), Ok(true));
+    }
+}
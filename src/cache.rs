@@ -0,0 +1,599 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::hash_map::{Entry, RandomState};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::Duration;
+
+use language_tags::LanguageTag;
+
+use {
+    icu, Arguments, Context, Decimal, Error, Inflections, Message, NonFiniteNumbers, NumberBackend,
+    Value,
+};
+
+/// A cache of formatted messages, for applications which format the
+/// same messages with the same arguments over and over again.
+///
+/// Results are keyed by a message id chosen by the caller, the
+/// [`Context`] and the arguments. Once the cache holds `capacity`
+/// results, the least recently used result is dropped to make room for
+/// a new one.
+///
+/// The caller must use a different id for each message. A message
+/// which fails to format is cached in the same form as
+/// [`Context::format`] returns it.
+///
+/// This requires the `std` feature.
+///
+/// ```
+/// use message_format::{icu, Context, FormatCache, Value};
+///
+/// let ctx = Context::default();
+/// let message = icu::parse("Hello, {name}!").unwrap();
+/// let mut cache = FormatCache::new(100);
+///
+/// let args = [("name", Value::Str("Ann"))];
+/// assert_eq!(cache.format(&ctx, "greeting", &message, &args), "Hello, Ann!");
+/// assert_eq!(cache.len(), 1);
+/// ```
+///
+/// [`Context`]: struct.Context.html
+/// [`Context::format`]: struct.Context.html#method.format
+#[derive(Debug)]
+pub struct FormatCache {
+    capacity: usize,
+    hasher: RandomState,
+    /// Cached results, keyed by the hash of what they were formatted
+    /// from.
+    results: HashMap<u64, CachedResult>,
+    /// Incremented on every lookup to order the results by use.
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct CachedResult {
+    id: String,
+    language_tag: LanguageTag,
+    placeholder_value: Option<i64>,
+    coerce_arguments: bool,
+    non_finite_numbers: NonFiniteNumbers,
+    inflections: Option<Arc<Inflections>>,
+    number_backend: Option<Arc<dyn NumberBackend>>,
+    args: Vec<(String, CachedValue)>,
+    output: String,
+    last_used: u64,
+}
+
+#[derive(Debug, PartialEq)]
+enum CachedValue {
+    Number(i64),
+    Str(String),
+    Duration(Duration),
+    I128(i128),
+    U128(u128),
+    Decimal(Decimal),
+    /// The bits of a float, so that NaN matches itself.
+    Float(u64),
+}
+
+impl<'a> From<Value<'a>> for CachedValue {
+    fn from(value: Value<'a>) -> Self {
+        match value {
+            Value::Number(n) => CachedValue::Number(n),
+            Value::Str(s) => CachedValue::Str(s.to_string()),
+            Value::Duration(d) => CachedValue::Duration(d),
+            Value::Float(f) => CachedValue::Float(f.to_bits()),
+            Value::I128(n) => CachedValue::I128(n),
+            Value::U128(n) => CachedValue::U128(n),
+            Value::Decimal(d) => CachedValue::Decimal(d),
+        }
+    }
+}
+
+impl<'a> PartialEq<Value<'a>> for CachedValue {
+    fn eq(&self, value: &Value<'a>) -> bool {
+        match (self, *value) {
+            (CachedValue::Number(a), Value::Number(b)) => *a == b,
+            (CachedValue::Str(a), Value::Str(b)) => a == b,
+            (CachedValue::Duration(a), Value::Duration(b)) => *a == b,
+            (CachedValue::Float(a), Value::Float(b)) => *a == b.to_bits(),
+            (CachedValue::I128(a), Value::I128(b)) => *a == b,
+            (CachedValue::U128(a), Value::U128(b)) => *a == b,
+            (CachedValue::Decimal(a), Value::Decimal(b)) => *a == b,
+            _ => false,
+        }
+    }
+}
+
+impl CachedResult {
+    fn new(ctx: &Context, id: &str, message: &Message, args: &[(&str, Value)]) -> Self {
+        CachedResult {
+            id: id.to_string(),
+            language_tag: ctx.language_tag.clone(),
+            placeholder_value: ctx.placeholder_value,
+            coerce_arguments: ctx.coerce_arguments,
+            non_finite_numbers: ctx.non_finite_numbers,
+            inflections: ctx.inflections.clone(),
+            number_backend: ctx.number_backend.clone(),
+            args: args
+                .iter()
+                .map(|arg| (arg.0.to_string(), CachedValue::from(arg.1)))
+                .collect(),
+            output: ctx.format(message, Some(&args)),
+            last_used: 0,
+        }
+    }
+
+    fn matches(&self, ctx: &Context, id: &str, args: &[(&str, Value)]) -> bool {
+        self.id == id
+            && self.language_tag == ctx.language_tag
+            && self.placeholder_value == ctx.placeholder_value
+            && self.coerce_arguments == ctx.coerce_arguments
+            && self.non_finite_numbers == ctx.non_finite_numbers
+            && same_shared(&self.inflections, &ctx.inflections)
+            && same_shared(&self.number_backend, &ctx.number_backend)
+            && self.args.len() == args.len()
+            && self
+                .args
+                .iter()
+                .zip(args)
+                .all(|(cached, arg)| cached.0 == arg.0 && cached.1 == arg.1)
+    }
+}
+
+/// Whether two contexts share the same table of inflections or number
+/// backend. These are compared by identity, since comparing their
+/// contents each time would cost more than formatting.
+fn same_shared<T: ?Sized>(a: &Option<Arc<T>>, b: &Option<Arc<T>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Feeds formatted text into a hasher, so that a `LanguageTag` can be
+/// hashed without allocating.
+struct HashWriter<'a, H: 'a>(&'a mut H);
+
+impl<'a, H: Hasher> Write for HashWriter<'a, H> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+impl FormatCache {
+    /// Create a cache which holds up to `capacity` results.
+    pub fn new(capacity: usize) -> Self {
+        FormatCache {
+            capacity,
+            hasher: RandomState::new(),
+            results: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Format a message, or return the result of formatting it earlier
+    /// with the same id, context and arguments.
+    pub fn format(
+        &mut self,
+        ctx: &Context,
+        id: &str,
+        message: &Message,
+        args: &[(&str, Value)],
+    ) -> &str {
+        self.clock += 1;
+        let key = self.key(ctx, id, args);
+        let full = self.results.len() >= self.capacity;
+        if full && !self.results.contains_key(&key) {
+            self.evict();
+        }
+        let clock = self.clock;
+        let result = match self.results.entry(key) {
+            Entry::Occupied(entry) => {
+                let result = entry.into_mut();
+                if result.matches(ctx, id, args) {
+                    if let Some(ref metrics) = ctx.metrics {
+                        metrics.cache_hit();
+                    }
+                } else {
+                    if let Some(ref metrics) = ctx.metrics {
+                        metrics.cache_miss();
+                    }
+                    *result = CachedResult::new(ctx, id, message, args);
+                }
+                result
+            }
+            Entry::Vacant(entry) => {
+                if let Some(ref metrics) = ctx.metrics {
+                    metrics.cache_miss();
+                }
+                entry.insert(CachedResult::new(ctx, id, message, args))
+            }
+        };
+        result.last_used = clock;
+        &result.output
+    }
+
+    /// The number of results held in the cache.
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Whether the cache holds no results.
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Drop all of the cached results, such as after the messages have
+    /// been reloaded.
+    pub fn clear(&mut self) {
+        self.results.clear();
+    }
+
+    fn key(&self, ctx: &Context, id: &str, args: &[(&str, Value)]) -> u64 {
+        let mut hasher = self.hasher.build_hasher();
+        id.hash(&mut hasher);
+        let _ = write!(HashWriter(&mut hasher), "{}", ctx.language_tag);
+        ctx.placeholder_value.hash(&mut hasher);
+        ctx.coerce_arguments.hash(&mut hasher);
+        ctx.non_finite_numbers.hash(&mut hasher);
+        ctx.inflections
+            .as_ref()
+            .map(|inflections| Arc::as_ptr(inflections) as usize)
+            .hash(&mut hasher);
+        ctx.number_backend
+            .as_ref()
+            .map(|backend| Arc::as_ptr(backend) as *const () as usize)
+            .hash(&mut hasher);
+        args.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Drop the least recently used result. This is a linear search,
+    /// but only happens when a new result is added to a full cache.
+    fn evict(&mut self) {
+        let oldest = self
+            .results
+            .iter()
+            .min_by_key(|entry| entry.1.last_used)
+            .map(|entry| *entry.0);
+        if let Some(key) = oldest {
+            self.results.remove(&key);
+        }
+    }
+}
+
+/// A cache of parsed messages, keyed by a hash of their patterns, for
+/// programs which parse the same patterns over and over again, such as
+/// a default message compiled into many crates.
+///
+/// Each pattern is parsed once, after which parsing it again returns
+/// the same shared message. Patterns which fail to parse are not
+/// cached. A cache made with `new` holds every pattern given to it
+/// until it is cleared, so it should only be given patterns which the
+/// program itself provides, not ones from users. A cache made with
+/// `with_capacity` drops the least recently used message instead.
+///
+/// This requires the `std` feature.
+///
+/// ```
+/// use std::sync::Arc;
+/// use message_format::ParseCache;
+///
+/// let cache = ParseCache::new();
+/// let first = cache.parse("Hello, {name}!").unwrap();
+/// let second = cache.parse("Hello, {name}!").unwrap();
+/// assert!(Arc::ptr_eq(&first, &second));
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct ParseCache {
+    hasher: RandomState,
+    capacity: Option<usize>,
+    messages: Mutex<ParsedMessages>,
+}
+
+/// The patterns and messages of a `ParseCache`.
+#[derive(Debug, Default)]
+struct ParsedMessages {
+    /// The messages by the hash of their patterns.
+    by_hash: HashMap<u64, Vec<ParsedMessage>>,
+    len: usize,
+    /// Counts each lookup, to tell which message was used least
+    /// recently.
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct ParsedMessage {
+    pattern: Box<str>,
+    message: Arc<Message>,
+    last_used: u64,
+}
+
+impl ParseCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        ParseCache::default()
+    }
+
+    /// Create an empty cache which holds at most `capacity` messages,
+    /// dropping the least recently used one to make room for another.
+    pub fn with_capacity(capacity: usize) -> Self {
+        ParseCache {
+            capacity: Some(capacity),
+            ..ParseCache::default()
+        }
+    }
+
+    /// The cache shared by the whole program, such as by libraries
+    /// which each parse the same default messages.
+    pub fn global() -> &'static ParseCache {
+        static GLOBAL: OnceLock<ParseCache> = OnceLock::new();
+        GLOBAL.get_or_init(ParseCache::new)
+    }
+
+    /// Parse an ICU pattern, or return the message parsed from it
+    /// earlier.
+    ///
+    /// The cache is not locked while parsing, so threads which parse
+    /// the same new pattern at once may each parse it, but only one of
+    /// the messages is kept and returned to them all.
+    pub fn parse(&self, pattern: &str) -> Result<Arc<Message>, Error> {
+        let hash = self.hasher.hash_one(pattern);
+        if let Some(message) = self.lock().find(hash, pattern) {
+            return Ok(message);
+        }
+        let message = Arc::new(icu::parse(pattern)?);
+        let mut messages = self.lock();
+        if let Some(message) = messages.find(hash, pattern) {
+            return Ok(message);
+        }
+        let capacity = self.capacity.unwrap_or(usize::MAX);
+        if capacity == 0 {
+            return Ok(message);
+        }
+        if messages.len >= capacity {
+            messages.evict();
+        }
+        let last_used = messages.clock;
+        messages.len += 1;
+        messages
+            .by_hash
+            .entry(hash)
+            .or_default()
+            .push(ParsedMessage {
+                pattern: pattern.into(),
+                message: message.clone(),
+                last_used,
+            });
+        Ok(message)
+    }
+
+    /// The number of messages held in the cache.
+    pub fn len(&self) -> usize {
+        self.lock().len
+    }
+
+    /// Whether the cache holds no messages.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all of the cached messages. Messages which were returned
+    /// earlier are still valid.
+    pub fn clear(&self) {
+        let mut messages = self.lock();
+        messages.by_hash.clear();
+        messages.len = 0;
+    }
+
+    /// Lock the messages. A thread which panicked while holding the
+    /// lock can not have left them half changed, so the poisoning is
+    /// ignored.
+    fn lock(&self) -> MutexGuard<'_, ParsedMessages> {
+        self.messages.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl ParsedMessages {
+    /// The message parsed from `pattern`, if there is one, marking it
+    /// as used.
+    fn find(&mut self, hash: u64, pattern: &str) -> Option<Arc<Message>> {
+        self.clock += 1;
+        let clock = self.clock;
+        let entry = self
+            .by_hash
+            .get_mut(&hash)?
+            .iter_mut()
+            .find(|entry| &*entry.pattern == pattern)?;
+        entry.last_used = clock;
+        Some(entry.message.clone())
+    }
+
+    /// Drop the least recently used message. This is a linear search,
+    /// but only happens when a new message is added to a full cache.
+    fn evict(&mut self) {
+        let oldest = self
+            .by_hash
+            .iter()
+            .flat_map(|(&hash, entries)| {
+                entries
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, entry)| (entry.last_used, hash, index))
+            })
+            .min();
+        if let Some((_, hash, index)) = oldest {
+            if let Entry::Occupied(mut entries) = self.by_hash.entry(hash) {
+                entries.get_mut().swap_remove(index);
+                if entries.get().is_empty() {
+                    entries.remove();
+                }
+                self.len -= 1;
+            }
+        }
+    }
+}
+
+/// The number of patterns whose messages `format` keeps.
+const FORMAT_PATTERNS: usize = 256;
+
+/// Parse an ICU `pattern` and format it for `locale`, such as `"de-AT"`,
+/// in one call, as with `MessageFormat.format` in other languages.
+///
+/// This is meant for scripts and small tools. The messages of the
+/// patterns used most recently are kept, so formatting the same
+/// pattern again does not parse it again. Larger programs should keep
+/// their messages in a [`Catalog`] and their [`Context`] for each
+/// language instead, which this makes for each call.
+///
+/// This fails with `Error::Locale` if `locale` is not a language tag,
+/// and otherwise as `icu::parse` and `Context::try_format` do.
+///
+/// This requires the `std` feature.
+///
+/// ```
+/// use message_format::{format, Value};
+///
+/// let args = [("count", Value::Number(1234))];
+/// let output = format("{count, number} Dateien", "de", &args).unwrap();
+/// assert_eq!(output, "1.234 Dateien");
+/// ```
+///
+/// [`Catalog`]: struct.Catalog.html
+/// [`Context`]: struct.Context.html
+pub fn format(pattern: &str, locale: &str, args: &dyn Arguments) -> Result<String, Error> {
+    static MESSAGES: OnceLock<ParseCache> = OnceLock::new();
+    let language_tag = locale.parse().map_err(|_| Error::Locale {
+        tag: locale.to_string(),
+    })?;
+    let message = MESSAGES
+        .get_or_init(|| ParseCache::with_capacity(FORMAT_PATTERNS))
+        .parse(pattern)?;
+    let ctx = Context {
+        language_tag,
+        ..Context::default()
+    };
+    ctx.try_format(&message, Some(args))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::{format, FormatCache, ParseCache};
+    use icu::parse;
+    use {Context, Value};
+
+    #[test]
+    fn results_are_reused() {
+        let ctx = Context::default();
+        let hello = parse("Hello, {name}!").unwrap();
+        let bye = parse("Bye, {name}!").unwrap();
+        let mut cache = FormatCache::new(10);
+
+        let ann = [("name", Value::Str("Ann"))];
+        let bob = [("name", Value::Str("Bob"))];
+        assert_eq!(cache.format(&ctx, "hello", &hello, &ann), "Hello, Ann!");
+        assert_eq!(cache.format(&ctx, "hello", &hello, &bob), "Hello, Bob!");
+        assert_eq!(cache.format(&ctx, "bye", &bye, &ann), "Bye, Ann!");
+        assert_eq!(cache.len(), 3);
+
+        // A cached result is returned even if the message is different.
+        assert_eq!(cache.format(&ctx, "hello", &bye, &ann), "Hello, Ann!");
+        assert_eq!(cache.len(), 3);
+
+        let ctx = Context::for_language("de");
+        assert_eq!(cache.format(&ctx, "hello", &bye, &ann), "Bye, Ann!");
+        assert_eq!(cache.len(), 4);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn least_recently_used_is_dropped() {
+        let ctx = Context::default();
+        let message = parse("{n}").unwrap();
+        let other = parse("other").unwrap();
+        let mut cache = FormatCache::new(2);
+
+        cache.format(&ctx, "n", &message, &[("n", Value::Number(1))]);
+        cache.format(&ctx, "n", &message, &[("n", Value::Number(2))]);
+        cache.format(&ctx, "n", &message, &[("n", Value::Number(1))]);
+        cache.format(&ctx, "n", &message, &[("n", Value::Number(3))]);
+        assert_eq!(cache.len(), 2);
+
+        assert_eq!(
+            cache.format(&ctx, "n", &other, &[("n", Value::Number(1))]),
+            "1"
+        );
+        assert_eq!(
+            cache.format(&ctx, "n", &other, &[("n", Value::Number(2))]),
+            "other"
+        );
+    }
+
+    #[test]
+    fn patterns_are_parsed_once() {
+        let cache = ParseCache::new();
+        let threads: Vec<_> = (0..4)
+            .map(|_| thread::spawn(|| ParseCache::global().parse("{n} items").unwrap()))
+            .collect();
+        let messages: Vec<_> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+        assert!(messages.iter().all(|m| Arc::ptr_eq(m, &messages[0])));
+
+        assert!(cache.parse("{unclosed").is_err());
+        assert!(cache.is_empty());
+        let first = cache.parse("a").unwrap();
+        cache.parse("b").unwrap();
+        assert_eq!(cache.len(), 2);
+        cache.clear();
+        assert!(!Arc::ptr_eq(&first, &cache.parse("a").unwrap()));
+    }
+
+    #[test]
+    fn least_recently_parsed_is_dropped() {
+        let cache = ParseCache::with_capacity(2);
+        let a = cache.parse("a").unwrap();
+        cache.parse("b").unwrap();
+        cache.parse("a").unwrap();
+        cache.parse("c").unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(&a, &cache.parse("a").unwrap()));
+        assert_eq!(cache.len(), 2);
+
+        let none = ParseCache::with_capacity(0);
+        none.parse("a").unwrap();
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn patterns_are_formatted_in_one_call() {
+        let args = [("n", Value::Number(1500))];
+        assert_eq!(format("{n, number}", "fr", &args).unwrap(), "1\u{202f}500");
+        assert_eq!(format("{n, number}", "en", &args).unwrap(), "1,500");
+        assert_eq!(format("{n", "en", &args).unwrap_err().code(), "parse");
+        assert_eq!(
+            format("{n}", "not a tag", &args).unwrap_err().code(),
+            "locale"
+        );
+        assert_eq!(
+            format("{n}", "en", &[("m", Value::Number(1))])
+                .unwrap_err()
+                .code(),
+            "missing-argument"
+        );
+    }
+}
@@ -0,0 +1,1364 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::BufRead;
+use std::sync::Arc;
+
+use icu;
+use properties::{self, Entry};
+use {Arguments, Context, Error, Message, Schema, Value, WithDefaults};
+
+/// Caps on the resources which one call to [`Catalog::format`] may
+/// use, for catalogs from sources which are not trusted.
+///
+/// A message which goes over a limit fails with an error saying which,
+/// rather than using however much memory or time its references lead
+/// to. By default only the depth of references is limited.
+///
+/// ```
+/// use message_format::{icu, Catalog, Context, Limits};
+///
+/// let mut catalog = Catalog::new();
+/// catalog.insert("ha", icu::parse("ha").unwrap());
+/// catalog.insert("laugh", icu::parse("{@ha}{@ha}{@ha}{@ha}").unwrap());
+/// catalog.set_limits(Limits {
+///     output_length: 6,
+///     ..Limits::default()
+/// });
+///
+/// let ctx = Context::default();
+/// let error = catalog.format(&ctx, "laugh", None).unwrap_err();
+/// assert_eq!(error.code(), "output-length");
+/// ```
+///
+/// [`Catalog::format`]: struct.Catalog.html#method.format
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Limits {
+    /// The most bytes of output.
+    pub output_length: usize,
+    /// How deeply messages may refer to other messages, so that a long
+    /// chain of references can not overflow the stack. This is 16 by
+    /// default.
+    pub reference_depth: usize,
+    /// How many messages may be formatted, counting the message that
+    /// was asked for and each reference from it separately, so that
+    /// messages which each refer to others several times can not take
+    /// time which grows exponentially with their depth.
+    pub work: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            output_length: usize::MAX,
+            reference_depth: 16,
+            work: usize::MAX,
+        }
+    }
+}
+
+/// How many of the messages of a [`Catalog`] are shared between keys,
+/// from [`Catalog::dedup_stats`].
+///
+/// [`Catalog`]: struct.Catalog.html
+/// [`Catalog::dedup_stats`]: struct.Catalog.html#method.dedup_stats
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DedupStats {
+    /// The number of keys, with or without a context.
+    pub messages: usize,
+    /// The number of distinct messages held for those keys.
+    pub distinct: usize,
+    /// The memory, in bytes, which the keys sharing messages save over
+    /// each holding its own, as counted by `Message::memory_footprint`.
+    pub saved_bytes: usize,
+}
+
+/// Information about a message for translators and tools.
+///
+/// Nothing here changes how a message is formatted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    /// A description of where and how the message is used.
+    pub description: Option<String>,
+    /// The meaning of the message, telling apart messages with the same
+    /// text which may need different translations.
+    pub meaning: Option<String>,
+    /// Example values of the arguments of the message, by name.
+    pub examples: Vec<(String, String)>,
+    /// Values for arguments of the message which callers may leave
+    /// out, by name.
+    pub defaults: Vec<(String, String)>,
+    /// The arguments that every translation of the message may use.
+    pub schema: Option<Schema>,
+}
+
+impl Metadata {
+    /// Whether nothing is known about the message.
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none()
+            && self.meaning.is_none()
+            && self.examples.is_empty()
+            && self.defaults.is_empty()
+            && self.schema.is_none()
+    }
+}
+
+/// [`Catalog::format`] falls back to the defaults of a message for any
+/// argument which the caller leaves out, and they can be used as
+/// arguments elsewhere through [`WithDefaults`]:
+///
+/// ```
+/// use message_format::{icu, Catalog, Context, Metadata, Value, WithDefaults};
+///
+/// let mut catalog = Catalog::new();
+/// catalog.insert("greeting", icu::parse("Hello, {name}!").unwrap());
+/// catalog.set_metadata("greeting", Metadata {
+///     defaults: vec![("name".to_string(), "there".to_string())],
+///     ..Metadata::default()
+/// });
+///
+/// let ctx = Context::default();
+/// let output = catalog.format(&ctx, "greeting", None).unwrap();
+/// assert_eq!(output, "Hello, there!");
+///
+/// let args = [("name", Value::Str("Ann"))];
+/// let defaults = catalog.metadata("greeting").unwrap();
+/// let message = icu::parse("Goodbye, {name}!").unwrap();
+/// let args = WithDefaults::new(Some(&args), defaults);
+/// assert_eq!(ctx.format(&message, Some(&args)), "Goodbye, Ann!");
+/// ```
+///
+/// [`Catalog::format`]: struct.Catalog.html#method.format
+/// [`WithDefaults`]: struct.WithDefaults.html
+impl Arguments for Metadata {
+    fn lookup(&self, name: &str) -> Option<Value<'_>> {
+        self.defaults
+            .iter()
+            .find(|default| default.0 == name)
+            .map(|default| Value::Str(&default.1))
+    }
+}
+
+/// A set of messages looked up by key.
+///
+/// This requires the `std` feature.
+#[derive(Debug, Default)]
+pub struct Catalog {
+    messages: HashMap<String, Arc<Message>>,
+    metadata: HashMap<String, Metadata>,
+    contexts: HashMap<String, HashMap<String, Arc<Message>>>,
+    limits: Limits,
+    sandboxed: bool,
+}
+
+impl Catalog {
+    /// Create an empty `Catalog`.
+    pub fn new() -> Self {
+        Catalog::default()
+    }
+
+    /// Add a message, replacing any message with the same key.
+    pub fn insert(&mut self, key: &str, message: Message) {
+        self.insert_shared(key, Arc::new(message));
+    }
+
+    /// Add a message which may be shared with other keys or catalogs,
+    /// replacing any message with the same key.
+    pub fn insert_shared(&mut self, key: &str, message: Arc<Message>) {
+        self.messages.insert(key.to_string(), message);
+    }
+
+    /// Look up the message with the given `key`.
+    pub fn get(&self, key: &str) -> Option<&Message> {
+        self.messages.get(key).map(|message| &**message)
+    }
+
+    /// Look up the message with the given `key`, for sharing it with
+    /// other keys or catalogs.
+    pub fn get_shared(&self, key: &str) -> Option<&Arc<Message>> {
+        self.messages.get(key)
+    }
+
+    /// Share a single copy of each message among all of the keys, with
+    /// or without a context, whose messages are the same, such as after
+    /// merging catalogs in which many keys have the same pattern.
+    ///
+    /// Messages are the same if they have the same outline from
+    /// [`Message::dump`]. Catalogs read with `read_properties` already
+    /// share the messages of identical patterns.
+    ///
+    /// ```
+    /// use message_format::{icu, Catalog};
+    ///
+    /// let mut catalog = Catalog::new();
+    /// for brand in &["acme", "globex", "initech"] {
+    ///     catalog.insert(&format!("{}.cancel", brand), icu::parse("Cancel").unwrap());
+    /// }
+    /// catalog.dedup();
+    ///
+    /// let stats = catalog.dedup_stats();
+    /// assert_eq!((stats.messages, stats.distinct), (3, 1));
+    /// assert!(stats.saved_bytes > 0);
+    /// ```
+    ///
+    /// [`Message::dump`]: struct.Message.html#method.dump
+    pub fn dedup(&mut self) {
+        let mut distinct: HashMap<String, Arc<Message>> = HashMap::new();
+        let messages = self
+            .messages
+            .values_mut()
+            .chain(self.contexts.values_mut().flat_map(HashMap::values_mut));
+        for message in messages {
+            let shared = distinct
+                .entry(message.dump())
+                .or_insert_with(|| message.clone());
+            *message = shared.clone();
+        }
+    }
+
+    /// How many of the messages of this catalog are shared between
+    /// keys, and how much memory that saves.
+    pub fn dedup_stats(&self) -> DedupStats {
+        let mut stats = DedupStats::default();
+        let mut seen = HashSet::new();
+        let messages = self
+            .messages
+            .values()
+            .chain(self.contexts.values().flat_map(HashMap::values));
+        for message in messages {
+            stats.messages += 1;
+            if seen.insert(Arc::as_ptr(message)) {
+                stats.distinct += 1;
+            } else {
+                stats.saved_bytes += message.memory_footprint();
+            }
+        }
+        stats
+    }
+
+    /// Format the message with the given `key`, returning an error like
+    /// `Context::try_format` if it could not be formatted.
+    ///
+    /// A message may refer to another message of the catalog by its
+    /// key, as in `{@app.name}`, which is formatted with the same
+    /// arguments. A message which refers back to itself fails with
+    /// `Error::ReferenceCycle`, and one which refers to a message which
+    /// is not in the catalog fails with `Error::MissingMessage`.
+    ///
+    /// Any argument which is not given falls back to the defaults in
+    /// the metadata of the message.
+    ///
+    /// ```
+    /// use message_format::{icu, Catalog, Context, Value};
+    ///
+    /// let mut catalog = Catalog::new();
+    /// catalog.insert("app.name", icu::parse("Notes").unwrap());
+    /// catalog.insert("welcome", icu::parse("Welcome to {@app.name}, {name}!").unwrap());
+    ///
+    /// let ctx = Context::default();
+    /// let args = [("name", Value::Str("Ann"))];
+    /// let output = catalog.format(&ctx, "welcome", Some(&args)).unwrap();
+    /// assert_eq!(output, "Welcome to Notes, Ann!");
+    /// ```
+    pub fn format(
+        &self,
+        ctx: &Context,
+        key: &str,
+        args: Option<&dyn Arguments>,
+    ) -> Result<String, Error> {
+        self.format_message(ctx, key, self.messages.get(key), args)
+    }
+
+    /// Format the message with the given `key` in the given `context`,
+    /// failing like `format`.
+    ///
+    /// The references of the message are to messages without a
+    /// context, and the same limits apply as to `format`. Errors name
+    /// the message by its context and key joined by `\u{4}`, as in the
+    /// `.mo` files of gettext.
+    ///
+    /// ```
+    /// use message_format::{icu, Catalog, Context, Error};
+    ///
+    /// let mut catalog = Catalog::new();
+    /// catalog.insert("Open", icu::parse("Öffnen").unwrap());
+    /// catalog.insert_in_context("door state", "Open", icu::parse("Offen").unwrap());
+    ///
+    /// let ctx = Context::default();
+    /// assert_eq!(catalog.format_in_context(&ctx, "door state", "Open", None).unwrap(), "Offen");
+    /// match catalog.format_in_context(&ctx, "menu", "Open", None) {
+    ///     Err(Error::MissingMessage { id }) => assert_eq!(id, "menu\u{4}Open"),
+    ///     other => panic!("unexpected result: {:?}", other),
+    /// }
+    /// ```
+    pub fn format_in_context(
+        &self,
+        ctx: &Context,
+        context: &str,
+        key: &str,
+        args: Option<&dyn Arguments>,
+    ) -> Result<String, Error> {
+        let id = format!("{}\u{4}{}", context, key);
+        let message = self
+            .contexts
+            .get(context)
+            .and_then(|messages| messages.get(key));
+        self.format_message(ctx, &id, message, args)
+    }
+
+    /// Format `message`, which is the message with the given `id` if
+    /// there is one, for `format` and `format_in_context`.
+    fn format_message(
+        &self,
+        ctx: &Context,
+        id: &str,
+        message: Option<&Arc<Message>>,
+        args: Option<&dyn Arguments>,
+    ) -> Result<String, Error> {
+        let message = match message {
+            Some(message) => message,
+            None => {
+                let error = Err(Error::MissingMessage { id: id.to_string() });
+                ctx.record(&error);
+                return error;
+            }
+        };
+        if self.sandboxed && !message.is_pure() {
+            let error = Err(Error::Sandboxed { id: id.to_string() });
+            ctx.record(&error);
+            return error;
+        }
+        let defaults = self
+            .metadata
+            .get(id)
+            .filter(|metadata| !metadata.defaults.is_empty());
+        let with_defaults;
+        let args = match defaults {
+            Some(defaults) => {
+                with_defaults = WithDefaults::new(args, defaults);
+                Some(&with_defaults as &dyn Arguments)
+            }
+            None => args,
+        };
+        let error = RefCell::new(None);
+        let work = Cell::new(1);
+        let mut output = LimitedOutput {
+            text: String::new(),
+            limit: self.limits.output_length,
+            exceeded: false,
+        };
+        let result = {
+            let references = References {
+                catalog: self,
+                args,
+                ids: vec![id],
+                error: &error,
+                work: &work,
+            };
+            ctx.write_checked(message, &mut output, Some(&references))
+        };
+        let result = match error.into_inner() {
+            _ if output.exceeded => Err(Error::OutputLength {
+                limit: self.limits.output_length,
+            }),
+            Some(error) => Err(error),
+            None => result.map(|()| output.text),
+        };
+        ctx.record(&result);
+        result
+    }
+
+    /// Add a message for use in the given `context`, replacing any
+    /// message with the same key in that context.
+    ///
+    /// Like `msgctxt` in gettext, a context lets the same key have a
+    /// different translation where it is used differently, such as
+    /// "Open" as a verb on a button and as the state of a door. The
+    /// messages of each context are kept apart from each other and from
+    /// those without a context.
+    ///
+    /// ```
+    /// use message_format::{icu, Catalog, Context};
+    ///
+    /// let mut catalog = Catalog::new();
+    /// catalog.insert("Open", icu::parse("Öffnen").unwrap());
+    /// catalog.insert_in_context("door state", "Open", icu::parse("Offen").unwrap());
+    ///
+    /// let ctx = Context::default();
+    /// let message = catalog.get_in_context("door state", "Open").unwrap();
+    /// assert_eq!(ctx.format(message, None), "Offen");
+    /// assert!(catalog.get_in_context("menu", "Open").is_none());
+    /// ```
+    pub fn insert_in_context(&mut self, context: &str, key: &str, message: Message) {
+        self.contexts
+            .entry(context.to_string())
+            .or_default()
+            .insert(key.to_string(), Arc::new(message));
+    }
+
+    /// Look up the message with the given `key` in the given `context`.
+    pub fn get_in_context(&self, context: &str, key: &str) -> Option<&Message> {
+        self.contexts
+            .get(context)?
+            .get(key)
+            .map(|message| &**message)
+    }
+
+    /// Set the metadata of the message with the given `key`, replacing
+    /// any which it had. Empty metadata is not stored.
+    pub fn set_metadata(&mut self, key: &str, metadata: Metadata) {
+        if metadata.is_empty() {
+            self.metadata.remove(key);
+        } else {
+            self.metadata.insert(key.to_string(), metadata);
+        }
+    }
+
+    /// Set the limits on the resources used by each call to `format`.
+    ///
+    /// [`Limits`] describes what is limited.
+    ///
+    /// [`Limits`]: struct.Limits.html
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// The limits on the resources used by each call to `format`.
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Set whether this catalog is sandboxed, for rendering messages
+    /// written by users who are not trusted, such as the customers of
+    /// a hosted service.
+    ///
+    /// A sandboxed catalog only formats messages which are pure, as
+    /// told by [`Message::is_pure`]. Any other message fails with
+    /// `Error::Sandboxed`, since references could reach messages which
+    /// the user should not see and parts of kinds other than those
+    /// built in could do anything.
+    ///
+    /// ```
+    /// use message_format::{icu, Catalog, Context};
+    ///
+    /// let mut catalog = Catalog::new();
+    /// catalog.insert("secret", icu::parse("hunter2").unwrap());
+    /// catalog.insert("template", icu::parse("Hi {name}, {@secret}").unwrap());
+    /// catalog.set_sandboxed(true);
+    ///
+    /// let ctx = Context::default();
+    /// let error = catalog.format(&ctx, "template", None).unwrap_err();
+    /// assert_eq!(error.code(), "sandboxed");
+    /// ```
+    ///
+    /// [`Message::is_pure`]: struct.Message.html#method.is_pure
+    pub fn set_sandboxed(&mut self, sandboxed: bool) {
+        self.sandboxed = sandboxed;
+    }
+
+    /// Whether this catalog is sandboxed.
+    pub fn is_sandboxed(&self) -> bool {
+        self.sandboxed
+    }
+
+    /// Look up the metadata of the message with the given `key`.
+    ///
+    /// ```
+    /// use message_format::Catalog;
+    ///
+    /// let source = "# Shown on the home page.\ngreeting = Hello, {name}!\n";
+    /// let catalog = Catalog::read_properties(source.as_bytes()).unwrap();
+    ///
+    /// let metadata = catalog.metadata("greeting").unwrap();
+    /// assert_eq!(metadata.description.as_ref().unwrap(), "Shown on the home page.");
+    /// ```
+    pub fn metadata(&self, key: &str) -> Option<&Metadata> {
+        self.metadata.get(key)
+    }
+
+    /// Check every message of this catalog against the [`Schema`] in the
+    /// metadata of the message with the same key in `schemas`, which is
+    /// usually the catalog of the source language or this catalog
+    /// itself. Messages without a schema are not checked.
+    ///
+    /// This is meant to be called when translations are loaded, as
+    /// [`read_properties_with_schemas`] does, so that a translation
+    /// which uses an argument that the code does not give fails then
+    /// rather than when it is formatted.
+    ///
+    /// ```
+    /// use message_format::{ArgumentType, Catalog, Metadata, Schema};
+    ///
+    /// let mut source = Catalog::read_properties("greeting=Hello, {name}!".as_bytes()).unwrap();
+    /// source.set_metadata("greeting", Metadata {
+    ///     schema: Some(Schema::new().argument("name", ArgumentType::Str)),
+    ///     ..Metadata::default()
+    /// });
+    ///
+    /// let german = Catalog::read_properties("greeting=Hallo, {nmae}!".as_bytes()).unwrap();
+    /// assert_eq!(german.validate(&source).unwrap_err().code(), "schema");
+    /// ```
+    ///
+    /// [`read_properties_with_schemas`]: #method.read_properties_with_schemas
+    /// [`Schema`]: struct.Schema.html
+    pub fn validate(&self, schemas: &Catalog) -> Result<(), Error> {
+        for (key, message) in self.iter() {
+            let schema = schemas
+                .metadata(key)
+                .and_then(|metadata| metadata.schema.as_ref());
+            if let Some(schema) = schema {
+                schema.check(message).map_err(|error| Error::Schema {
+                    key: key.to_string(),
+                    error: Box::new(error),
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Iterate over the keys and messages of the catalog which have no
+    /// context, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Message)> {
+        self.messages
+            .iter()
+            .map(|(key, message)| (&key[..], &**message))
+    }
+
+    /// Iterate over the contexts, keys and messages of the catalog
+    /// which have a context, in no particular order.
+    pub fn iter_contexts(&self) -> impl Iterator<Item = (&str, &str, &Message)> {
+        self.contexts.iter().flat_map(|(context, messages)| {
+            messages
+                .iter()
+                .map(move |(key, message)| (&context[..], &key[..], &**message))
+        })
+    }
+
+    /// A view of the messages whose keys are in the namespace `name`,
+    /// looked up by the rest of their keys.
+    ///
+    /// Keys are split into namespaces at dots, so the namespace
+    /// `settings.network` holds `settings.network.proxy` but not
+    /// `settings.networking`. The empty namespace holds every message.
+    ///
+    /// ```
+    /// use message_format::{icu, Catalog};
+    ///
+    /// let mut catalog = Catalog::new();
+    /// catalog.insert("settings.network.proxy", icu::parse("Proxy").unwrap());
+    /// catalog.insert("settings.network.dns", icu::parse("DNS").unwrap());
+    /// catalog.insert("settings.title", icu::parse("Settings").unwrap());
+    ///
+    /// let network = catalog.namespace("settings.network");
+    /// assert_eq!(network.len(), 2);
+    /// assert!(network.get("proxy").is_some());
+    /// assert!(network.get("title").is_none());
+    /// ```
+    pub fn namespace(&self, name: &str) -> Namespace<'_> {
+        Namespace {
+            catalog: self,
+            prefix: namespace_prefix(name),
+        }
+    }
+
+    /// Move the messages of `other`, with their metadata and contexts,
+    /// into the namespace `name`, replacing any with the same keys.
+    ///
+    /// This lets each feature of an application keep a catalog of its
+    /// own, with short keys, which is then merged into the catalog of
+    /// the application.
+    pub fn merge_namespace(&mut self, name: &str, other: Catalog) {
+        let prefix = namespace_prefix(name);
+        for (key, message) in other.messages {
+            self.messages.insert(format!("{}{}", prefix, key), message);
+        }
+        for (key, metadata) in other.metadata {
+            self.metadata.insert(format!("{}{}", prefix, key), metadata);
+        }
+        for (context, messages) in other.contexts {
+            let entries = self.contexts.entry(context).or_default();
+            for (key, message) in messages {
+                entries.insert(format!("{}{}", prefix, key), message);
+            }
+        }
+    }
+
+    /// The number of messages in the catalog, with or without a context.
+    pub fn len(&self) -> usize {
+        self.messages.len() + self.contexts.values().map(HashMap::len).sum::<usize>()
+    }
+
+    /// Whether the catalog holds no messages.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read a catalog of ICU patterns from a Java `.properties` file.
+    ///
+    /// The file is read a line at a time, so only one entry is held in
+    /// memory at once and the reader may decompress its input as it
+    /// goes. Keys with identical patterns share a single parsed
+    /// message. Keys are separated from their patterns by `=`, `:` or
+    /// whitespace. Lines starting with `#` or `!` are comments, a
+    /// trailing `\` continues an entry onto the next line, and the
+    /// escapes `\t`, `\n`, `\r`, `\f` and `\uXXXX` are understood.
+    /// Comment lines directly before an entry become the description in
+    /// its [`Metadata`]. The file must be UTF-8, with or without a byte
+    /// order mark. Files in other encodings can be read after converting
+    /// them with [`encoding::decode`]. The entries can also be read
+    /// without parsing their patterns, with [`properties::read`].
+    ///
+    /// ```
+    /// use message_format::{Catalog, Context, Value};
+    ///
+    /// let source = "# Greetings\ngreeting = Hello, {name}!\nfarewell: Bye\n";
+    /// let catalog = Catalog::read_properties(source.as_bytes()).unwrap();
+    ///
+    /// let ctx = Context::default();
+    /// let message = catalog.get("greeting").unwrap();
+    /// let args = [("name", Value::Str("Ann"))];
+    /// assert_eq!(ctx.format(message, Some(&args)), "Hello, Ann!");
+    /// ```
+    ///
+    /// [`encoding::decode`]: encoding/fn.decode.html
+    /// [`Metadata`]: struct.Metadata.html
+    /// [`properties::read`]: properties/fn.read.html
+    pub fn read_properties<R: BufRead>(reader: R) -> Result<Self, Error> {
+        let mut catalog = Catalog::new();
+        let mut parsed = HashMap::new();
+        for entry in properties::read(reader) {
+            catalog.insert_entry(&mut parsed, entry?)?;
+        }
+        Ok(catalog)
+    }
+
+    /// Read a catalog as [`read_properties`] does and check it against
+    /// the schemas in `schemas` as [`validate`] does, so that a
+    /// translation which does not match them fails to load.
+    ///
+    /// ```
+    /// use message_format::{ArgumentType, Catalog, Metadata, Schema};
+    ///
+    /// let mut source = Catalog::read_properties("greeting=Hello, {name}!".as_bytes()).unwrap();
+    /// source.set_metadata("greeting", Metadata {
+    ///     schema: Some(Schema::new().argument("name", ArgumentType::Str)),
+    ///     ..Metadata::default()
+    /// });
+    ///
+    /// let german = "greeting=Hallo, {nmae}!".as_bytes();
+    /// let error = Catalog::read_properties_with_schemas(german, &source).unwrap_err();
+    /// assert_eq!(error.code(), "schema");
+    /// ```
+    ///
+    /// [`read_properties`]: #method.read_properties
+    /// [`validate`]: #method.validate
+    pub fn read_properties_with_schemas<R: BufRead>(
+        reader: R,
+        schemas: &Catalog,
+    ) -> Result<Self, Error> {
+        let catalog = Catalog::read_properties(reader)?;
+        catalog.validate(schemas)?;
+        Ok(catalog)
+    }
+
+    /// Build a catalog from the key, pattern and description of each
+    /// entry of a `.properties` file, for `include_messages!` from the
+    /// `message-format-macros` crate, which has already checked that
+    /// the patterns parse.
+    #[doc(hidden)]
+    pub fn from_checked_entries(entries: &[(&str, &str, Option<&str>)]) -> Self {
+        let mut catalog = Catalog::new();
+        let mut parsed = HashMap::new();
+        for &(key, pattern, description) in entries {
+            let entry = Entry {
+                key: key.to_string(),
+                pattern: pattern.to_string(),
+                description: description.map(str::to_string),
+                line: 0,
+            };
+            catalog
+                .insert_entry(&mut parsed, entry)
+                .expect("include_messages! checks the patterns");
+        }
+        catalog
+    }
+
+    /// Add an entry of a `.properties` file, sharing the message of
+    /// each pattern in `parsed` with later entries.
+    fn insert_entry(
+        &mut self,
+        parsed: &mut HashMap<String, Arc<Message>>,
+        entry: Entry,
+    ) -> Result<(), Error> {
+        let message = match parsed.get(&entry.pattern) {
+            Some(message) => message.clone(),
+            None => {
+                let message = icu::parse(&entry.pattern).map_err(|e| Error::Entry {
+                    key: entry.key.clone(),
+                    line: entry.line,
+                    error: Box::new(e),
+                })?;
+                let message = Arc::new(message);
+                parsed.insert(entry.pattern, message.clone());
+                message
+            }
+        };
+        if let Some(description) = entry.description {
+            let metadata = Metadata {
+                description: Some(description),
+                ..Metadata::default()
+            };
+            self.metadata.insert(entry.key.clone(), metadata);
+        }
+        self.messages.insert(entry.key, message);
+        Ok(())
+    }
+}
+
+/// The start of the keys in the namespace `name`.
+fn namespace_prefix(name: &str) -> String {
+    if name.is_empty() {
+        String::new()
+    } else {
+        format!("{}.", name)
+    }
+}
+
+/// The messages of a [`Catalog`] in one namespace, from
+/// [`Catalog::namespace`].
+///
+/// [`Catalog`]: struct.Catalog.html
+/// [`Catalog::namespace`]: struct.Catalog.html#method.namespace
+#[derive(Clone, Debug)]
+pub struct Namespace<'a> {
+    catalog: &'a Catalog,
+    prefix: String,
+}
+
+impl<'a> Namespace<'a> {
+    /// Look up the message with the given `key` in this namespace.
+    pub fn get(&self, key: &str) -> Option<&'a Message> {
+        self.catalog.get(&format!("{}{}", self.prefix, key))
+    }
+
+    /// Format the message with the given `key` in this namespace, like
+    /// `Catalog::format`. References in the message are to the full
+    /// keys of other messages.
+    pub fn format(
+        &self,
+        ctx: &Context,
+        key: &str,
+        args: Option<&dyn Arguments>,
+    ) -> Result<String, Error> {
+        self.catalog
+            .format(ctx, &format!("{}{}", self.prefix, key), args)
+    }
+
+    /// A view of the namespace `name` within this one.
+    pub fn namespace(&self, name: &str) -> Namespace<'a> {
+        Namespace {
+            catalog: self.catalog,
+            prefix: format!("{}{}", self.prefix, namespace_prefix(name)),
+        }
+    }
+
+    /// Iterate over the keys in this namespace, without the namespace,
+    /// and their messages, in no particular order. Messages with a
+    /// context are left out, as by `Catalog::iter`.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a Message)> + '_ {
+        self.catalog
+            .iter()
+            .filter_map(move |(key, message)| Some((key.strip_prefix(&self.prefix[..])?, message)))
+    }
+
+    /// The number of messages in this namespace without a context.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Whether this namespace holds no messages without a context.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+}
+
+/// The arguments of a message being formatted by `Catalog::format`,
+/// which also write the messages it refers to.
+struct References<'a> {
+    catalog: &'a Catalog,
+    args: Option<&'a dyn Arguments>,
+    /// The keys of the messages being formatted, from the outermost.
+    ids: Vec<&'a str>,
+    /// The first error found in a message which was referred to.
+    error: &'a RefCell<Option<Error>>,
+    /// How many messages have been formatted so far.
+    work: &'a Cell<usize>,
+}
+
+impl<'a> References<'a> {
+    fn fail(&self, error: Error) -> Option<fmt::Result> {
+        self.error.borrow_mut().get_or_insert(error);
+        Some(Err(fmt::Error {}))
+    }
+}
+
+impl<'a> Arguments for References<'a> {
+    fn lookup(&self, name: &str) -> Option<Value<'_>> {
+        self.args.and_then(|args| args.lookup(name))
+    }
+
+    fn names(&self) -> Vec<&str> {
+        self.args.map_or_else(Vec::new, |args| args.names())
+    }
+
+    fn write_reference(
+        &self,
+        ctx: &Context,
+        id: &str,
+        stream: &mut dyn fmt::Write,
+    ) -> Option<fmt::Result> {
+        if self.ids.contains(&id) {
+            let mut ids: Vec<String> = self.ids.iter().map(|id| id.to_string()).collect();
+            ids.push(id.to_string());
+            return self.fail(Error::ReferenceCycle { ids });
+        }
+        let limits = self.catalog.limits;
+        if self.ids.len() > limits.reference_depth {
+            return self.fail(Error::ReferenceDepth { id: id.to_string() });
+        }
+        if self.work.get() >= limits.work {
+            return self.fail(Error::Work { limit: limits.work });
+        }
+        self.work.set(self.work.get() + 1);
+        let (key, message) = match self.catalog.messages.get_key_value(id) {
+            Some(entry) => entry,
+            None => return self.fail(Error::MissingMessage { id: id.to_string() }),
+        };
+        let mut ids = self.ids.clone();
+        ids.push(key);
+        let references = References { ids, ..*self };
+        let result = message.write_message(ctx, stream, Some(&references));
+        // Only the innermost failure is looked into, since each message
+        // around it fails as well.
+        if result.is_err() && self.error.borrow().is_none() {
+            if let Err(error) = message.check_arguments(ctx, Some(&references)) {
+                return self.fail(error);
+            }
+        }
+        Some(result)
+    }
+}
+
+/// The output of `Catalog::format`, which fails once it would grow
+/// past its limit.
+struct LimitedOutput {
+    text: String,
+    limit: usize,
+    exceeded: bool,
+}
+
+impl fmt::Write for LimitedOutput {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.text.len() + s.len() > self.limit {
+            self.exceeded = true;
+            return Err(fmt::Error {});
+        }
+        self.text.push_str(s);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::io::{self, BufReader, Read};
+    use std::sync::Arc;
+
+    use super::{Catalog, Limits, Metadata};
+    use {convert, icu};
+    use {ArgumentType, Arguments, Context, Error, Message, MessagePart, Schema, Value};
+
+    #[test]
+    fn properties_work() {
+        let ctx = Context::default();
+        let source = "\
+# A comment
+! Another comment
+
+greeting=Hello, {name}!
+farewell : Bye, \\
+    {name}.
+tab\\ key\tSee\\tyou
+unicode=Gr\\u00fc\\u00dfe
+";
+        let catalog = Catalog::read_properties(source.as_bytes()).unwrap();
+        assert_eq!(catalog.len(), 4);
+        assert_eq!(
+            format_message!(ctx, catalog.get("greeting").unwrap(), name => "Ann"),
+            "Hello, Ann!"
+        );
+        assert_eq!(
+            format_message!(ctx, catalog.get("farewell").unwrap(), name => "Ann"),
+            "Bye, Ann."
+        );
+        assert_eq!(
+            format_message!(ctx, catalog.get("tab key").unwrap()),
+            "See\tyou"
+        );
+        assert_eq!(
+            format_message!(ctx, catalog.get("unicode").unwrap()),
+            "Grüße"
+        );
+    }
+
+    #[test]
+    fn comments_become_descriptions() {
+        let source = "\
+# Not about any entry.
+
+# The title of the window.
+! Keep it short.
+title=Settings
+plain=No comment
+";
+        let mut catalog = Catalog::read_properties(source.as_bytes()).unwrap();
+        assert_eq!(
+            catalog.metadata("title"),
+            Some(&Metadata {
+                description: Some("The title of the window.\nKeep it short.".to_string()),
+                ..Metadata::default()
+            })
+        );
+        assert_eq!(catalog.metadata("plain"), None);
+
+        let metadata = Metadata {
+            meaning: Some("noun".to_string()),
+            examples: vec![("name".to_string(), "Ann".to_string())],
+            ..Metadata::default()
+        };
+        catalog.set_metadata("plain", metadata.clone());
+        assert_eq!(catalog.metadata("plain"), Some(&metadata));
+        catalog.set_metadata("plain", Metadata::default());
+        assert_eq!(catalog.metadata("plain"), None);
+    }
+
+    #[test]
+    fn defaults_are_used_for_missing_arguments() {
+        let ctx = Context::default();
+        let mut catalog = Catalog::new();
+        catalog.insert("greeting", icu::parse("Hello, {name}!").unwrap());
+        catalog.set_metadata(
+            "greeting",
+            Metadata {
+                defaults: vec![("name".to_string(), "there".to_string())],
+                ..Metadata::default()
+            },
+        );
+        assert_eq!(
+            catalog.format(&ctx, "greeting", None).unwrap(),
+            "Hello, there!"
+        );
+        let args = [("name", Value::Str("Ann"))];
+        assert_eq!(
+            catalog.format(&ctx, "greeting", Some(&args)).unwrap(),
+            "Hello, Ann!"
+        );
+
+        let strict = Context {
+            strict_arguments: true,
+            ..Context::default()
+        };
+        assert_eq!(
+            catalog.format(&strict, "greeting", None).unwrap(),
+            "Hello, there!"
+        );
+    }
+
+    #[test]
+    fn references_are_resolved() {
+        let ctx = Context::default();
+        let mut catalog = Catalog::new();
+        for &(key, pattern) in &[
+            ("app", "Notes"),
+            ("title", "{@app} for {name}"),
+            ("welcome", "Welcome to {@title}!"),
+            ("broken", "See {@nothing}"),
+            ("unnamed", "Hi {@title}"),
+            ("a", "a {@b}"),
+            ("b", "b {@a}"),
+        ] {
+            catalog.insert(key, icu::parse(pattern).unwrap());
+        }
+        let args = [("name", Value::Str("Ann"))];
+        assert_eq!(
+            catalog.format(&ctx, "welcome", Some(&args)).unwrap(),
+            "Welcome to Notes for Ann!"
+        );
+        let nested = convert::i18next::to_icu("{{name}} uses $t(app)").unwrap();
+        catalog.insert("nested", icu::parse(&nested).unwrap());
+        assert_eq!(
+            catalog.format(&ctx, "nested", Some(&args)).unwrap(),
+            "Ann uses Notes"
+        );
+        match catalog.format(&ctx, "broken", None) {
+            Err(Error::MissingMessage { ref id }) => assert_eq!(id, "nothing"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match catalog.format(&ctx, "unnamed", None) {
+            Err(Error::MissingArgument { ref name }) => assert_eq!(name, "name"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match catalog.format(&ctx, "a", None) {
+            Err(Error::ReferenceCycle { ref ids }) => assert_eq!(ids, &["a", "b", "a"]),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            catalog.format(&ctx, "missing", None).unwrap_err().code(),
+            "missing-message"
+        );
+
+        for depth in 0..20 {
+            let pattern = format!("{{@level{}}}", depth + 1);
+            catalog.insert(&format!("level{}", depth), icu::parse(&pattern).unwrap());
+        }
+        assert_eq!(
+            catalog.format(&ctx, "level0", None).unwrap_err().code(),
+            "reference-depth"
+        );
+    }
+
+    #[test]
+    fn sandboxes_format_only_pure_messages() {
+        #[derive(Debug)]
+        struct Clock;
+
+        impl MessagePart for Clock {
+            fn apply_format(
+                &self,
+                _ctx: &Context,
+                stream: &mut dyn fmt::Write,
+                _args: Option<&dyn Arguments>,
+            ) -> fmt::Result {
+                stream.write_str("12:00")
+            }
+        }
+
+        let ctx = Context::default();
+        let mut catalog = Catalog::new();
+        catalog.insert("time", Message::new(vec![Box::new(Clock)]));
+        catalog.insert("app", icu::parse("Notes").unwrap());
+        catalog.insert("title", icu::parse("{@app}").unwrap());
+        catalog.insert("count", icu::parse("{n, number} notes").unwrap());
+        assert_eq!(catalog.format(&ctx, "time", None).unwrap(), "12:00");
+        assert_eq!(catalog.format(&ctx, "title", None).unwrap(), "Notes");
+
+        catalog.set_sandboxed(true);
+        for &key in &["time", "title"] {
+            match catalog.format(&ctx, key, None) {
+                Err(Error::Sandboxed { ref id }) => assert_eq!(id, key),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+        let args = [("n", Value::Number(3))];
+        assert_eq!(
+            catalog.format(&ctx, "count", Some(&args)).unwrap(),
+            "3 notes"
+        );
+    }
+
+    #[test]
+    fn identical_patterns_are_shared() {
+        let source = "a=Cancel\nb=Save\nc=Cancel\n";
+        let mut catalog = Catalog::read_properties(source.as_bytes()).unwrap();
+        assert!(Arc::ptr_eq(
+            catalog.get_shared("a").unwrap(),
+            catalog.get_shared("c").unwrap()
+        ));
+        catalog.insert("d", icu::parse("Save").unwrap());
+        catalog.insert_in_context("verb", "e", icu::parse("Save").unwrap());
+        let stats = catalog.dedup_stats();
+        assert_eq!((stats.messages, stats.distinct), (5, 4));
+
+        catalog.dedup();
+        let stats = catalog.dedup_stats();
+        assert_eq!((stats.messages, stats.distinct), (5, 2));
+        let save = catalog.get("b").unwrap();
+        assert_eq!(
+            stats.saved_bytes,
+            2 * save.memory_footprint() + catalog.get("a").unwrap().memory_footprint()
+        );
+        assert!(Arc::ptr_eq(
+            catalog.get_shared("b").unwrap(),
+            catalog.get_shared("d").unwrap()
+        ));
+        assert_eq!(
+            Context::default().format(catalog.get_in_context("verb", "e").unwrap(), None),
+            "Save"
+        );
+    }
+
+    #[test]
+    fn messages_with_other_skeletons_are_not_shared() {
+        let ctx = Context::default();
+        let mut catalog = Catalog::new();
+        for &(key, pattern) in &[
+            ("plain", "{n, number}"),
+            ("fixed", "{n, number, ::.00}"),
+            ("whole", "{n, number, integer}"),
+        ] {
+            catalog.insert(key, icu::parse(pattern).unwrap());
+        }
+        catalog.dedup();
+        assert_eq!(catalog.dedup_stats().distinct, 3);
+        let args = [("n", Value::Float(1.5))];
+        for &(key, expected) in &[("plain", "1.5"), ("fixed", "1.50"), ("whole", "2")] {
+            assert_eq!(catalog.format(&ctx, key, Some(&args)).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn limits_are_enforced() {
+        let ctx = Context::default();
+        let mut catalog = Catalog::new();
+        catalog.insert("word", icu::parse("ha").unwrap());
+        for depth in 0..8 {
+            let pattern = format!("{{@level{0}}}{{@level{0}}}", depth + 1);
+            catalog.insert(&format!("level{}", depth), icu::parse(&pattern).unwrap());
+        }
+        catalog.insert("level8", icu::parse("{@word}").unwrap());
+        assert_eq!(catalog.format(&ctx, "level0", None).unwrap().len(), 512);
+
+        catalog.set_limits(Limits {
+            work: 100,
+            ..Limits::default()
+        });
+        match catalog.format(&ctx, "level0", None) {
+            Err(Error::Work { limit }) => assert_eq!(limit, 100),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        catalog.set_limits(Limits {
+            output_length: 511,
+            ..Limits::default()
+        });
+        match catalog.format(&ctx, "level0", None) {
+            Err(Error::OutputLength { limit }) => assert_eq!(limit, 511),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        catalog.set_limits(Limits {
+            reference_depth: 4,
+            ..Limits::default()
+        });
+        assert_eq!(
+            catalog.format(&ctx, "level0", None).unwrap_err().code(),
+            "reference-depth"
+        );
+        assert_eq!(catalog.format(&ctx, "level5", None).unwrap().len(), 16);
+    }
+
+    #[test]
+    fn namespaces_work() {
+        let mut catalog = Catalog::new();
+        catalog.insert("settings.title", icu::parse("Settings").unwrap());
+        catalog.insert("settings.networking", icu::parse("Networking").unwrap());
+
+        let mut network = Catalog::new();
+        network.insert("proxy", icu::parse("Proxy").unwrap());
+        network.insert("dns.server", icu::parse("{n} servers").unwrap());
+        network.insert_in_context("verb", "proxy", icu::parse("Use a proxy").unwrap());
+        network.set_metadata(
+            "proxy",
+            Metadata {
+                meaning: Some("noun".to_string()),
+                ..Metadata::default()
+            },
+        );
+        catalog.merge_namespace("settings.network", network);
+        assert_eq!(catalog.len(), 5);
+        assert!(catalog.metadata("settings.network.proxy").is_some());
+        assert!(catalog
+            .get_in_context("verb", "settings.network.proxy")
+            .is_some());
+
+        let settings = catalog.namespace("settings");
+        assert_eq!(settings.len(), 4);
+        let network = settings.namespace("network");
+        let mut keys: Vec<_> = network.iter().map(|(key, _)| key).collect();
+        keys.sort();
+        assert_eq!(keys, ["dns.server", "proxy"]);
+        assert!(network.namespace("dns").get("server").is_some());
+        assert!(network.namespace("nothing").is_empty());
+        assert_eq!(catalog.namespace("").len(), 4);
+
+        let ctx = Context::default();
+        let args = [("n", Value::Number(2))];
+        assert_eq!(
+            network.format(&ctx, "dns.server", Some(&args)).unwrap(),
+            "2 servers"
+        );
+    }
+
+    #[test]
+    fn messages_are_formatted_in_contexts() {
+        let ctx = Context::default();
+        let mut catalog = Catalog::new();
+        catalog.insert("Open", icu::parse("open").unwrap());
+        catalog.insert_in_context("state", "Open", icu::parse("Is {@Open}").unwrap());
+        catalog.insert_in_context("button", "Open", icu::parse("{n, ordinal}").unwrap());
+        assert_eq!(
+            catalog
+                .format_in_context(&ctx, "state", "Open", None)
+                .unwrap(),
+            "Is open"
+        );
+        match catalog.format_in_context(&ctx, "button", "Open", None) {
+            Err(Error::MissingArgument { name }) => assert_eq!(name, "n"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match catalog.format_in_context(&ctx, "Open", "state", None) {
+            Err(Error::MissingMessage { id }) => assert_eq!(id, "Open\u{4}state"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        catalog.set_limits(Limits {
+            output_length: 4,
+            ..Limits::default()
+        });
+        match catalog.format_in_context(&ctx, "state", "Open", None) {
+            Err(Error::OutputLength { limit }) => assert_eq!(limit, 4),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        catalog.set_limits(Limits::default());
+        catalog.set_sandboxed(true);
+        match catalog.format_in_context(&ctx, "state", "Open", None) {
+            Err(Error::Sandboxed { id }) => assert_eq!(id, "state\u{4}Open"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn contexts_are_separate() {
+        let ctx = Context::default();
+        let mut catalog = Catalog::new();
+        catalog.insert("Open", icu::parse("Open").unwrap());
+        catalog.insert_in_context("verb", "Open", icu::parse("Open it").unwrap());
+        catalog.insert_in_context("state", "Open", icu::parse("Is open").unwrap());
+        catalog.insert_in_context("state", "Open", icu::parse("Opened").unwrap());
+        assert_eq!(catalog.len(), 3);
+        assert_eq!(catalog.iter().count(), 1);
+
+        let message = catalog.get_in_context("state", "Open").unwrap();
+        assert_eq!(format_message!(ctx, message), "Opened");
+        assert!(catalog.get_in_context("Open", "verb").is_none());
+        assert!(catalog.get("verb").is_none());
+
+        let mut entries: Vec<_> = catalog
+            .iter_contexts()
+            .map(|(context, key, message)| (context, key, ctx.format(message, None)))
+            .collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            vec![
+                ("state", "Open", "Opened".to_string()),
+                ("verb", "Open", "Open it".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn translations_are_validated() {
+        let mut source = Catalog::read_properties("a=A {x}\nb=B\n".as_bytes()).unwrap();
+        let schema = Schema::new().argument("x", ArgumentType::Number);
+        let metadata = Metadata {
+            schema: Some(schema),
+            ..Metadata::default()
+        };
+        source.set_metadata("a", metadata);
+        assert!(source.validate(&source).is_ok());
+
+        let translation = Catalog::read_properties("a=Ä\nb=B {y}\n".as_bytes()).unwrap();
+        assert!(translation.validate(&source).is_ok());
+
+        let translation = Catalog::read_properties("a=Ä {y}\n".as_bytes()).unwrap();
+        match translation.validate(&source) {
+            Err(Error::Schema { ref key, ref error }) => {
+                assert_eq!(key, "a");
+                assert_eq!(error.code(), "undeclared-argument");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let loaded = Catalog::read_properties_with_schemas("a=Ä {x}\n".as_bytes(), &source);
+        assert!(loaded.is_ok());
+        let loaded = Catalog::read_properties_with_schemas("a=Ä {y}\n".as_bytes(), &source);
+        assert_eq!(loaded.unwrap_err().code(), "schema");
+    }
+
+    /// A reader which hands out one byte at a time, to check that
+    /// entries are not expected to arrive in one read.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((&byte, rest)) if !buf.is_empty() => {
+                    buf[0] = byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn reads_incrementally() {
+        let source = "a=One\nb=Two {n}\nc=Three\n";
+        let reader = BufReader::with_capacity(1, Trickle(source.as_bytes()));
+        let catalog = Catalog::read_properties(reader).unwrap();
+        assert_eq!(catalog.len(), 3);
+        assert!(catalog.get("b").is_some());
+    }
+
+    #[test]
+    fn byte_order_mark_is_skipped() {
+        let catalog = Catalog::read_properties("\u{feff}a=One\n".as_bytes()).unwrap();
+        assert!(catalog.get("a").is_some());
+    }
+
+    #[test]
+    fn errors_report_lines() {
+        match Catalog::read_properties("a=One\n\nb=Two {n\n".as_bytes()) {
+            Err(Error::Entry {
+                line,
+                ref key,
+                ref error,
+            }) => {
+                assert_eq!(line, 3);
+                assert_eq!(key, "b");
+                assert_eq!(error.code(), "parse");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match Catalog::read_properties("a=\\u00\n".as_bytes()) {
+            Err(Error::Entry {
+                line, ref error, ..
+            }) => {
+                assert_eq!(line, 1);
+                match **error {
+                    Error::Parse { position, .. } => assert_eq!(position, 0),
+                    ref other => panic!("unexpected error: {:?}", other),
+                }
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let error = Catalog::read_properties(&[b'a', b'=', 0xff][..]).unwrap_err();
+        assert_eq!(error.code(), "io");
+    }
+}
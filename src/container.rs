@@ -0,0 +1,409 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Catalogs which record the version of their layout and a revision of
+//! each message, so that they can be migrated as an application
+//! changes.
+//!
+//! A container is a `.properties` file which starts with a comment
+//! giving its version, such as `# % version 2`, and in which the
+//! comment before each entry gives its revision, such as
+//! `# % revision 3`. A message which is used in a [context] has a
+//! `# % context door state` line as well. Any other tool which reads
+//! `.properties` files can still read it, taking these lines as
+//! comments.
+//!
+//! The version belongs to the application. When it changes the layout
+//! of its catalog, such as by renaming keys, it raises the version and
+//! adds a [`Migration`] from the old version, so that containers
+//! written before the change are brought up to date as they are read.
+//! The revision of a message is raised each time its pattern is
+//! changed with [`Container::set`], so that translators and caches can
+//! tell which messages changed.
+//!
+//! ```
+//! use message_format::container::{self, Container, Migration};
+//! use message_format::Error;
+//!
+//! // Version 2 moved the messages of the settings window under `settings.`.
+//! fn add_namespace(container: &mut Container) -> Result<(), Error> {
+//!     for record in &mut container.records {
+//!         record.entry.key = format!("settings.{}", record.entry.key);
+//!     }
+//!     Ok(())
+//! }
+//! const MIGRATIONS: &[Migration] = &[Migration { from: 1, apply: add_namespace }];
+//!
+//! let source = "# % version 1\n\n# % revision 4\ntitle=Settings\n";
+//! let mut container = container::read(source.as_bytes()).unwrap();
+//! container.migrate(2, MIGRATIONS).unwrap();
+//! assert_eq!(container.version, 2);
+//!
+//! let record = container.get("settings.title").unwrap();
+//! assert_eq!((&record.entry.pattern[..], record.revision), ("Settings", 4));
+//! ```
+//!
+//! This requires the `std` feature.
+//!
+//! [`Migration`]: struct.Migration.html
+//! [`Container::set`]: struct.Container.html#method.set
+//! [context]: ../struct.Catalog.html#method.insert_in_context
+
+use std::io::{self, BufRead};
+
+use properties::{self, Entry};
+use {icu, Catalog, Error, Metadata};
+
+/// An entry of a container along with its revision.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Record {
+    /// The entry, whose description does not include the revision.
+    pub entry: Entry,
+    /// How many times the pattern of the entry has been set, starting
+    /// from 1.
+    pub revision: u32,
+    /// The context in which the message is used, if it has one.
+    pub context: Option<String>,
+}
+
+/// The entries of a catalog along with the version of its layout.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Container {
+    /// The version of the layout of the catalog, as chosen by the
+    /// application.
+    pub version: u32,
+    /// The entries, in the order in which they are written.
+    pub records: Vec<Record>,
+}
+
+/// A step which brings a container from one version to the next, for
+/// [`Container::migrate`].
+///
+/// [`Container::migrate`]: struct.Container.html#method.migrate
+#[derive(Clone, Copy)]
+pub struct Migration {
+    /// The version which this migration applies to.
+    pub from: u32,
+    /// Change the records of a container of version `from` into the
+    /// layout of the next version. The version is raised afterwards.
+    pub apply: fn(&mut Container) -> Result<(), Error>,
+}
+
+impl Container {
+    /// Create an empty container of the given version.
+    pub fn new(version: u32) -> Self {
+        Container {
+            version,
+            records: vec![],
+        }
+    }
+
+    /// Look up the record with the given `key` and no context.
+    pub fn get(&self, key: &str) -> Option<&Record> {
+        self.records.iter().find(|record| record.is(key))
+    }
+
+    /// Set the pattern of the entry with the given `key` and no
+    /// context, raising its revision if the pattern is different from
+    /// before, or else add it at revision 1.
+    pub fn set(&mut self, key: &str, pattern: &str) {
+        match self.records.iter_mut().find(|record| record.is(key)) {
+            Some(ref record) if record.entry.pattern == pattern => {}
+            Some(record) => {
+                record.entry.pattern = pattern.to_string();
+                record.revision += 1;
+            }
+            None => self.records.push(Record {
+                entry: Entry {
+                    key: key.to_string(),
+                    pattern: pattern.to_string(),
+                    description: None,
+                    line: 0,
+                },
+                revision: 1,
+                context: None,
+            }),
+        }
+    }
+
+    /// Remove the record with the given `key` and no context,
+    /// returning it if there was one.
+    pub fn remove(&mut self, key: &str) -> Option<Record> {
+        let index = self.records.iter().position(|record| record.is(key))?;
+        Some(self.records.remove(index))
+    }
+
+    /// Bring this container up to version `to`, applying the migration
+    /// from each version in turn.
+    ///
+    /// This fails with `Error::Version` if there is no migration from
+    /// one of the versions on the way, or if the container is already
+    /// newer than `to`, in which case the container is left at the last
+    /// version which it reached.
+    pub fn migrate(&mut self, to: u32, migrations: &[Migration]) -> Result<(), Error> {
+        if self.version > to {
+            return Err(Error::Version {
+                found: self.version,
+                wanted: to,
+            });
+        }
+        while self.version < to {
+            let migration = migrations
+                .iter()
+                .find(|migration| migration.from == self.version)
+                .ok_or(Error::Version {
+                    found: self.version,
+                    wanted: to,
+                })?;
+            (migration.apply)(self)?;
+            self.version += 1;
+        }
+        Ok(())
+    }
+
+    /// Parse the patterns of this container into a [`Catalog`], with
+    /// the description of each entry without a context in its
+    /// [`Metadata`]. The messages of entries with a context are added
+    /// in that context.
+    ///
+    /// [`Catalog`]: ../struct.Catalog.html
+    /// [`Metadata`]: ../struct.Metadata.html
+    pub fn catalog(&self) -> Result<Catalog, Error> {
+        let mut catalog = Catalog::new();
+        for record in &self.records {
+            let entry = &record.entry;
+            let message = icu::parse(&entry.pattern).map_err(|e| Error::Entry {
+                key: entry.key.clone(),
+                line: entry.line,
+                error: Box::new(e),
+            })?;
+            if let Some(ref context) = record.context {
+                catalog.insert_in_context(context, &entry.key, message);
+                continue;
+            }
+            catalog.insert(&entry.key, message);
+            if let Some(ref description) = entry.description {
+                let metadata = Metadata {
+                    description: Some(description.clone()),
+                    ..Metadata::default()
+                };
+                catalog.set_metadata(&entry.key, metadata);
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// Write this container as a `.properties` file.
+    pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), Error> {
+        writeln!(writer, "# % version {}", self.version)?;
+        writeln!(writer)?;
+        let entries: Vec<Entry> = self
+            .records
+            .iter()
+            .map(|record| {
+                let mut description = format!("% revision {}", record.revision);
+                if let Some(ref context) = record.context {
+                    description.push_str("\n% context ");
+                    description.push_str(context);
+                }
+                if let Some(ref text) = record.entry.description {
+                    description.push('\n');
+                    description.push_str(text);
+                }
+                Entry {
+                    description: Some(description),
+                    ..record.entry.clone()
+                }
+            })
+            .collect();
+        properties::write(&entries, writer)
+    }
+}
+
+impl Record {
+    /// Whether this is the record of `key` without a context.
+    fn is(&self, key: &str) -> bool {
+        self.entry.key == key && self.context.is_none()
+    }
+}
+
+/// The value of a `% name value` line, if `line` is one.
+fn directive<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    line.strip_prefix('%')?
+        .trim_start()
+        .strip_prefix(name)
+        .map(str::trim)
+}
+
+/// Read a container from a `.properties` file, a line at a time.
+///
+/// The first line must give the version. An entry with no revision in
+/// its comment, such as one added by hand, is at revision 1. Other
+/// comment lines starting with `%` are left out of the descriptions,
+/// so that later versions of this format can add to them.
+pub fn read<R: BufRead>(mut reader: R) -> Result<Container, Error> {
+    let mut first = String::new();
+    reader.read_line(&mut first)?;
+    let header = first.trim_start_matches('\u{feff}').trim();
+    let version = header
+        .strip_prefix(&['#', '!'][..])
+        .and_then(|comment| directive(comment.trim_start(), "version"))
+        .and_then(|version| version.parse().ok())
+        .ok_or_else(|| Error::parse(0, "Expected a `# % version` line"))?;
+    let mut container = Container::new(version);
+    for entry in properties::read(reader) {
+        let mut entry = entry.map_err(|error| match error {
+            Error::Entry { key, line, error } => Error::Entry {
+                key,
+                line: line + 1,
+                error,
+            },
+            error => error,
+        })?;
+        entry.line += 1;
+        let mut revision = 1;
+        let mut context = None;
+        let description = entry.description.take().unwrap_or_default();
+        let mut lines = vec![];
+        for line in description.lines() {
+            match (line.starts_with('%'), directive(line, "revision")) {
+                (_, Some(value)) => {
+                    revision = value.parse().map_err(|_| Error::Entry {
+                        key: entry.key.clone(),
+                        line: entry.line,
+                        error: Box::new(Error::parse(0, "Expected a revision number")),
+                    })?;
+                }
+                (true, None) => {
+                    if let Some(value) = directive(line, "context") {
+                        context = Some(value.to_string());
+                    }
+                }
+                (false, None) => lines.push(line),
+            }
+        }
+        if !lines.is_empty() {
+            entry.description = Some(lines.join("\n"));
+        }
+        container.records.push(Record {
+            entry,
+            revision,
+            context,
+        });
+    }
+    Ok(container)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read, Container, Migration};
+    use {Context, Error};
+
+    #[test]
+    fn containers_are_written_back() {
+        let mut container = Container::new(3);
+        container.set("title", "Settings");
+        container.set("greeting", "Hello, {name}!");
+        container.set("title", "Preferences");
+        container.set("greeting", "Hello, {name}!");
+        container.records[1].entry.description = Some("On the start page".to_string());
+
+        let mut output = vec![];
+        container.write(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output.clone()).unwrap(),
+            "# % version 3\n\n\
+             # % revision 2\ntitle=Preferences\n\
+             # % revision 1\n# On the start page\ngreeting=Hello, {name}!\n"
+        );
+
+        let read = read(&output[..]).unwrap();
+        assert_eq!(read.version, 3);
+        assert_eq!(read.records[0].revision, 2);
+        assert_eq!(read.records[1].entry.line, 7);
+        assert_eq!(
+            read.records[1].entry.description,
+            Some("On the start page".to_string())
+        );
+        assert!(read.catalog().unwrap().get("greeting").is_some());
+    }
+
+    #[test]
+    fn contexts_are_kept() {
+        let source = "\
+# % version 1
+
+# % revision 2
+# % context door state
+# Whether the door can be walked through.
+Open=Offen
+Open=Öffnen
+";
+        let container = read(source.as_bytes()).unwrap();
+        let record = &container.records[0];
+        assert_eq!(record.context, Some("door state".to_string()));
+        assert_eq!(
+            record.entry.description,
+            Some("Whether the door can be walked through.".to_string())
+        );
+        assert_eq!(container.get("Open").unwrap().entry.pattern, "Öffnen");
+
+        let catalog = container.catalog().unwrap();
+        let ctx = Context::default();
+        assert_eq!(
+            catalog
+                .format_in_context(&ctx, "door state", "Open", None)
+                .unwrap(),
+            "Offen"
+        );
+        assert_eq!(catalog.format(&ctx, "Open", None).unwrap(), "Öffnen");
+
+        let mut output = vec![];
+        container.write(&mut output).unwrap();
+        let written = read(&output[..]).unwrap();
+        assert_eq!(written.records[0].context, record.context);
+        assert_eq!(written.records[1].context, None);
+    }
+
+    #[test]
+    fn migrations_are_applied_in_turn() {
+        fn rename(container: &mut Container) -> Result<(), Error> {
+            let mut record = container.remove("old").unwrap();
+            record.entry.key = "new".to_string();
+            container.records.push(record);
+            Ok(())
+        }
+        fn reword(container: &mut Container) -> Result<(), Error> {
+            container.set("new", "Reworded");
+            Ok(())
+        }
+        let migrations = [
+            Migration {
+                from: 2,
+                apply: reword,
+            },
+            Migration {
+                from: 1,
+                apply: rename,
+            },
+        ];
+
+        let mut container = read("# % version 1\nold=Text\n".as_bytes()).unwrap();
+        container.migrate(3, &migrations).unwrap();
+        assert_eq!(container.version, 3);
+        assert_eq!(container.get("new").unwrap().revision, 2);
+
+        match container.migrate(4, &migrations) {
+            Err(Error::Version { found, wanted }) => assert_eq!((found, wanted), (3, 4)),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match container.migrate(2, &migrations) {
+            Err(Error::Version { found, wanted }) => assert_eq!((found, wanted), (3, 2)),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(read("old=Text\n".as_bytes()).unwrap_err().code(), "parse");
+    }
+}
@@ -4,52 +4,315 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#[cfg(feature = "std")]
 use language_tags::LanguageTag;
 use std::fmt;
+#[cfg(feature = "std")]
+use std::sync::Arc;
 
-use {Args, Message};
+use prelude::*;
+use {
+    ArgumentType, Arguments, Error, FormatOptions, MappedOutput, Message, NonFiniteNumbers, Value,
+    WithDefaults,
+};
+#[cfg(feature = "std")]
+use {Inflections, Metrics, NumberBackend};
 
 /// Contextual configuration data.
 #[derive(Clone, Debug)]
 pub struct Context {
     /// The language being localized for.
+    ///
+    /// This requires the `std` feature.
+    #[cfg(feature = "std")]
     pub language_tag: LanguageTag,
     /// The value to use in a `PlaceholderFormat`.
     pub placeholder_value: Option<i64>,
+    /// Whether an argument of the wrong type is converted to the type
+    /// that the message needs when it can be, rather than failing.
+    ///
+    /// A `plural` then accepts a string which holds a whole number,
+    /// such as `"3"`, and a `select` accepts a number, which it matches
+    /// by its decimal digits. This is off by default, so that passing
+    /// the wrong argument is reported by `try_format`.
+    pub coerce_arguments: bool,
+    /// Whether `try_format` fails with `Error::UnusedArguments` when it
+    /// is given arguments which the message does not use, such as after
+    /// a placeholder was renamed in the message but not by its callers.
+    ///
+    /// This is off by default. It is meant for development and tests,
+    /// since formatting then has to look at every argument.
+    pub strict_arguments: bool,
+    /// How a `number` argument which is infinite or not a number is
+    /// written. By default, with the symbols of the language.
+    pub non_finite_numbers: NonFiniteNumbers,
+    /// The inflected forms of words for an `inflect` argument, such as
+    /// the grammatical cases of the names of cities.
+    ///
+    /// This requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub inflections: Option<Arc<Inflections>>,
+    /// Counters which are told about each message formatted with this
+    /// context, such as for a dashboard of localization errors.
+    ///
+    /// This requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub metrics: Option<Arc<dyn Metrics>>,
+    /// The engine which writes the digits of numbers, instead of the
+    /// grouping and separators of this crate.
+    ///
+    /// This requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub number_backend: Option<Arc<dyn NumberBackend>>,
 }
 
 impl Context {
     /// Create a new instance of `Context`.
+    ///
+    /// This requires the `std` feature. Without it, start from
+    /// `Context::default()` instead.
+    #[cfg(feature = "std")]
     pub fn new(language: LanguageTag, placeholder_value: Option<i64>) -> Self {
         Context {
             language_tag: language,
             placeholder_value: placeholder_value,
+            coerce_arguments: false,
+            strict_arguments: false,
+            non_finite_numbers: NonFiniteNumbers::Symbols,
+            inflections: None,
+            metrics: None,
+            number_backend: None,
+        }
+    }
+
+    /// A default context for the language tag `language`, for tests.
+    #[cfg(all(test, feature = "std"))]
+    pub(crate) fn for_language(language: &str) -> Self {
+        Context {
+            language_tag: language.parse().unwrap(),
+            ..Context::default()
+        }
+    }
+
+    /// Tell the metrics of this context, if any, that a message was
+    /// formatted with the given result.
+    pub(crate) fn record<T>(&self, result: &Result<T, Error>) {
+        #[cfg(feature = "std")]
+        {
+            if let Some(ref metrics) = self.metrics {
+                metrics.formatted();
+                if let Err(ref error) = *result {
+                    metrics.failed(error.code());
+                }
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = result;
+        }
+    }
+
+    /// Tell the metrics of this context, if any, that a message was
+    /// written with the given result, looking into why it failed only
+    /// if there are metrics to tell.
+    fn record_write(&self, message: &Message, args: Option<&dyn Arguments>, result: fmt::Result) {
+        #[cfg(feature = "std")]
+        {
+            if self.metrics.is_some() {
+                self.record(&result.map_err(|_| {
+                    message
+                        .check_arguments(self, args)
+                        .err()
+                        .unwrap_or(Error::Format)
+                }));
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = (message, args, result);
+        }
+    }
+
+    /// Whether the language of this context has the primary language
+    /// subtag `language`, such as `"de"` for `de-AT`.
+    ///
+    /// Without the `std` feature, there is no language, so this is
+    /// always false.
+    pub(crate) fn is_language(&self, language: &str) -> bool {
+        #[cfg(feature = "std")]
+        {
+            self.language_tag
+                .language
+                .as_ref()
+                .is_some_and(|tag| tag.eq_ignore_ascii_case(language))
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            let _ = language;
+            false
         }
     }
 
     /// Format a message, returning a string.
-    pub fn format<'f>(&self, message: &Message, args: Option<&Args<'f>>) -> String {
+    pub fn format(&self, message: &Message, args: Option<&dyn Arguments>) -> String {
         let mut output = String::new();
-        let _ = message.write_message(self, &mut output, args);
+        let result = message.write_message(self, &mut output, args);
+        self.record_write(message, args, result);
         output
     }
 
+    /// Format a message, returning an error if it could not be
+    /// formatted rather than the partial output that `format` returns.
+    ///
+    /// ```
+    /// use message_format::{icu, Context, Error};
+    ///
+    /// let ctx = Context::default();
+    /// let m = icu::parse("Hello, {name}!").unwrap();
+    /// match ctx.try_format(&m, None) {
+    ///     Err(Error::MissingArgument { name }) => assert_eq!(name, "name"),
+    ///     other => panic!("unexpected result: {:?}", other),
+    /// }
+    /// ```
+    ///
+    /// A `plural` or `select` given a value of the wrong type fails with
+    /// `Error::UnsupportedType`, unless `coerce_arguments` is set and the
+    /// value can be converted.
+    pub fn try_format(
+        &self,
+        message: &Message,
+        args: Option<&dyn Arguments>,
+    ) -> Result<String, Error> {
+        let mut output = String::new();
+        let result = self.write_checked(message, &mut output, args);
+        self.record(&result);
+        result.map(|()| output)
+    }
+
+    /// Write a message, failing like `try_format`, without telling the
+    /// metrics of this context, for callers which tell them themselves.
+    pub(crate) fn write_checked(
+        &self,
+        message: &Message,
+        output: &mut dyn fmt::Write,
+        args: Option<&dyn Arguments>,
+    ) -> Result<(), Error> {
+        match message.write_message(self, output, args) {
+            Ok(()) => self.check_unused(message, args),
+            Err(_) => Err(message
+                .check_arguments(self, args)
+                .err()
+                .unwrap_or(Error::Format)),
+        }
+    }
+
+    /// Fail with `Error::UnusedArguments` if `strict_arguments` is set
+    /// and `message` does not use all of `args`.
+    fn check_unused(&self, message: &Message, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        if !self.strict_arguments {
+            return Ok(());
+        }
+        let names = args.map_or_else(Vec::new, |args| message.unused_arguments(args));
+        if names.is_empty() {
+            Ok(())
+        } else {
+            let names = names.into_iter().map(|name| name.to_string()).collect();
+            Err(Error::UnusedArguments { names })
+        }
+    }
+
+    /// Format a message, recording which part of the message produced
+    /// each span of the output, such as to highlight the argument under
+    /// the cursor in a preview of a translation.
+    pub fn format_mapped(&self, message: &Message, args: Option<&dyn Arguments>) -> MappedOutput {
+        let mut output = MappedOutput::default();
+        let result = message.write_mapped(self, &mut output, args);
+        self.record_write(message, args, result);
+        output
+    }
+
+    /// Format a message with [`FormatOptions`] for this call, returning
+    /// an error like `try_format` if it could not be formatted.
+    ///
+    /// The spans of the output are only kept if `options.trace` is set.
+    ///
+    /// [`FormatOptions`]: struct.FormatOptions.html
+    pub fn format_with(
+        &self,
+        message: &Message,
+        args: Option<&dyn Arguments>,
+        options: &FormatOptions,
+    ) -> Result<MappedOutput, Error> {
+        let ctx = options.context(self);
+        let arguments = message.arguments();
+        let fallbacks: Vec<(&str, String)> = arguments
+            .iter()
+            .filter(|argument| argument.1 == ArgumentType::Any)
+            .filter(|argument| args.and_then(|args| args.lookup(&argument.0)).is_none())
+            .filter_map(|argument| {
+                options
+                    .fallback(&argument.0)
+                    .map(|fallback| (&argument.0[..], fallback))
+            })
+            .collect();
+        #[cfg(feature = "std")]
+        {
+            if let Some(ref metrics) = self.metrics {
+                for fallback in &fallbacks {
+                    metrics.fallback(fallback.0);
+                }
+            }
+        }
+        let defaults: Vec<(&str, Value)> = fallbacks
+            .iter()
+            .map(|fallback| (fallback.0, Value::Str(&fallback.1)))
+            .collect();
+        let with_defaults = WithDefaults::new(args, &defaults);
+
+        let mut output = MappedOutput::default();
+        let result = match message.write_mapped(&ctx, &mut output, Some(&with_defaults)) {
+            // The fallbacks are used arguments, so only those given are
+            // checked.
+            Ok(()) => ctx
+                .check_unused(message, args)
+                .map(|()| options.finish(output)),
+            Err(_) => Err(message
+                .check_arguments(&ctx, Some(&with_defaults))
+                .err()
+                .unwrap_or(Error::Format)),
+        };
+        self.record(&result);
+        result
+    }
+
     /// Write a message to a stream.
-    pub fn write<'f>(
+    pub fn write(
         &self,
         message: &Message,
         stream: &mut fmt::Write,
-        args: Option<&Args<'f>>,
+        args: Option<&dyn Arguments>,
     ) -> fmt::Result {
-        message.write_message(self, stream, args)
+        let result = message.write_message(self, stream, args);
+        self.record_write(message, args, result);
+        result
     }
 }
 
 impl Default for Context {
     fn default() -> Self {
         Context {
+            #[cfg(feature = "std")]
             language_tag: Default::default(),
             placeholder_value: None,
+            coerce_arguments: false,
+            strict_arguments: false,
+            non_finite_numbers: NonFiniteNumbers::Symbols,
+            #[cfg(feature = "std")]
+            inflections: None,
+            #[cfg(feature = "std")]
+            metrics: None,
+            #[cfg(feature = "std")]
+            number_backend: None,
         }
     }
 }
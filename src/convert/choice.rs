@@ -0,0 +1,364 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Java `ChoiceFormat` Migration
+//!
+//! A `ChoiceFormat` selects text by comparing a number against a list
+//! of limits. With `0#no files|1#one file|1<{0} files`, values below `1`
+//! use the first text, values from `1` up to the next limit use the
+//! second and values greater than `1` use the last. ICU deprecates
+//! `choice` arguments in favor of `plural` and `select`.
+//!
+//! [`to_icu`] rewrites each `choice` argument in a pattern:
+//!
+//! * When the choices only distinguish `0`, `1` and all other values,
+//!   a `plural` argument with `=0` and `=1` exact matches is produced.
+//! * Otherwise, a `select` argument keyed by the integer values is
+//!   produced. ICU only matches `select` keys against strings, so the
+//!   value must then be passed as a string.
+//!
+//! The last choice becomes the `other` branch. A range which covers a
+//! few integers becomes one key per integer. Anything that cannot be
+//! mapped exactly, such as a range covering many integers, a
+//! fractional limit or a first limit other than `0`, below which
+//! values would use the first choice, is reported as a [`Warning`].
+//!
+//! [`to_icu`]: fn.to_icu.html
+//! [`Warning`]: ../struct.Warning.html
+
+use super::{
+    parse_icu, push_literal, push_plural_literal, Conversion, ConvertError, Piece, Warning,
+};
+
+/// The largest number of integers in a range that is expanded into one
+/// key per integer.
+const MAX_EXPANDED_KEYS: f64 = 10.0;
+
+/// Rewrite the `choice` arguments in an ICU or Java `MessageFormat`
+/// pattern into `plural` or `select` arguments.
+///
+/// ```
+/// use message_format::convert::choice;
+///
+/// let conversion = choice::to_icu("{0,choice,0#no files|1#one file|1<{0} files}").unwrap();
+/// assert_eq!(conversion.pattern, "{0, plural, =0 {no files} =1 {one file} other {{0} files}}");
+/// assert!(conversion.warnings.is_empty());
+/// ```
+pub fn to_icu(pattern: &str) -> Result<Conversion, ConvertError> {
+    let mut warnings = vec![];
+    let pattern = convert_message(pattern, 0, false, &mut warnings)?;
+    Ok(Conversion { pattern, warnings })
+}
+
+/// A single `limit#text` or `limit<text` choice.
+struct Choice<'a> {
+    /// The byte offset of the choice within the pattern being converted.
+    position: usize,
+    limit: f64,
+    /// Whether the limit itself is excluded, as with `<`.
+    exclusive: bool,
+    text: &'a str,
+    text_position: usize,
+}
+
+/// Convert a message whose text starts at byte offset `base` of the
+/// pattern being converted.
+fn convert_message(
+    text: &str,
+    base: usize,
+    in_plural: bool,
+    warnings: &mut Vec<Warning>,
+) -> Result<String, ConvertError> {
+    let mut output = String::new();
+    for piece in parse_icu(text).map_err(|e| e.offset(base))? {
+        match piece {
+            Piece::Text(ref text) if in_plural => push_plural_literal(&mut output, text),
+            Piece::Text(ref text) => push_literal(&mut output, text),
+            Piece::Argument {
+                position,
+                name,
+                kind: Some("choice"),
+                style,
+            } => {
+                let choices = match style {
+                    Some(choices) => choices,
+                    None => return Err(ConvertError::syntax(base + position, "Expected choices")),
+                };
+                let choices_position = base + (choices.as_ptr() as usize - text.as_ptr() as usize);
+                let choices = parse_choices(choices, choices_position)?;
+                output.push_str(&convert_choices(name, &choices, warnings)?);
+            }
+            Piece::Argument {
+                name, kind, style, ..
+            } => {
+                output.push('{');
+                output.push_str(name);
+                for part in kind.iter().chain(style.iter()) {
+                    output.push_str(", ");
+                    output.push_str(part);
+                }
+                output.push('}');
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Split the choices of a `choice` argument which starts at byte offset
+/// `base` of the pattern being converted.
+fn parse_choices(choices: &str, base: usize) -> Result<Vec<Choice<'_>>, ConvertError> {
+    let mut segments = vec![];
+    let mut start = 0;
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut chars = choices.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, c)| c);
+        match c {
+            '\'' if next == Some('\'') => {
+                chars.next();
+            }
+            '\'' if quoted => quoted = false,
+            '\'' => quoted = next.is_some_and(|c| "{}#|".contains(c)),
+            _ if quoted => {}
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '|' if depth == 0 => {
+                segments.push((start, &choices[start..position]));
+                start = position + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push((start, &choices[start..]));
+
+    let mut parsed = vec![];
+    for (offset, segment) in segments {
+        let position = base + offset;
+        let separator = match segment.find(['#', '<', '\u{2264}']) {
+            Some(separator) => separator,
+            None => return Err(ConvertError::syntax(position, "Expected '#' or '<'")),
+        };
+        let limit = match segment[..separator].trim() {
+            "\u{221e}" => f64::INFINITY,
+            "-\u{221e}" => f64::NEG_INFINITY,
+            limit => match limit.parse() {
+                Ok(limit) => limit,
+                Err(_) => return Err(ConvertError::syntax(position, "Expected a number")),
+            },
+        };
+        let separator_len = segment[separator..]
+            .chars()
+            .next()
+            .map_or(1, char::len_utf8);
+        parsed.push(Choice {
+            position,
+            limit,
+            exclusive: segment[separator..].starts_with('<'),
+            text: &segment[separator + separator_len..],
+            text_position: position + separator + separator_len,
+        });
+    }
+    Ok(parsed)
+}
+
+/// Produce the `plural` or `select` argument for a list of choices.
+fn convert_choices(
+    name: &str,
+    choices: &[Choice],
+    warnings: &mut Vec<Warning>,
+) -> Result<String, ConvertError> {
+    let mut keys: Vec<(i64, &Choice)> = vec![];
+
+    for (index, choice) in choices.iter().enumerate() {
+        if choice.limit.is_finite() && choice.limit.fract() != 0.0 {
+            warnings.push(Warning {
+                position: choice.position,
+                message: format!(
+                    "The limit {} is not an integer, so fractional values may \
+                     select a different branch",
+                    choice.limit
+                ),
+            });
+        }
+        let next = match choices.get(index + 1) {
+            Some(next) => next,
+            None => break,
+        };
+        let low = if choice.exclusive {
+            choice.limit.floor() + 1.0
+        } else {
+            choice.limit.ceil()
+        };
+        let high = if next.exclusive {
+            next.limit.floor()
+        } else {
+            next.limit.ceil() - 1.0
+        };
+        if index == 0 && low != 0.0 && low.is_finite() {
+            warnings.push(Warning {
+                position: choice.position,
+                message: format!(
+                    "Values below {} will use the `other` branch rather than this one",
+                    low
+                ),
+            });
+        }
+        let count = high - low + 1.0;
+        if count < 1.0 {
+            warnings.push(Warning {
+                position: choice.position,
+                message: "This choice can not be selected by an integer value".to_string(),
+            });
+            continue;
+        }
+        let mut values = vec![];
+        if count <= MAX_EXPANDED_KEYS {
+            values.extend(low as i64..=high as i64);
+        } else {
+            let value = if low.is_finite() { low } else { high };
+            values.push(value as i64);
+            warnings.push(Warning {
+                position: choice.position,
+                message: format!(
+                    "Only {} is matched exactly, other values in this range will \
+                     use the `other` branch",
+                    value
+                ),
+            });
+        }
+        for value in values {
+            if !keys.iter().any(|&(key, _)| key == value) {
+                keys.push((value, choice));
+            }
+        }
+    }
+
+    let is_plural = keys.iter().all(|&(key, _)| key == 0 || key == 1);
+    if !is_plural {
+        warnings.push(Warning {
+            position: choices[0].position,
+            message: format!("`select` requires `{}` to be passed as a string", name),
+        });
+    }
+
+    let mut output = format!(
+        "{{{}, {},",
+        name,
+        if is_plural { "plural" } else { "select" }
+    );
+    let last = &choices[choices.len() - 1];
+    let branches = keys
+        .iter()
+        .map(|&(key, choice)| {
+            let prefix = if is_plural { "=" } else { "" };
+            (format!("{}{}", prefix, key), choice)
+        })
+        .chain(Some(("other".to_string(), last)));
+    for (key, choice) in branches {
+        let text = convert_message(choice.text, choice.text_position, is_plural, warnings)?;
+        output.push_str(&format!(" {} {{{}}}", key, text));
+    }
+    output.push('}');
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_icu;
+    use {arg, icu, Context};
+
+    #[test]
+    fn plural_choices_work() {
+        let conversion =
+            to_icu("There {0,choice,0#are no files|1#is one file|1<are {0,number,integer} files}.")
+                .unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "There {0, plural, =0 {are no files} =1 {is one file} \
+             other {are {0, number, integer} files}}."
+        );
+        assert!(conversion.warnings.is_empty());
+
+        let conversion = to_icu("{n,choice,0#no #tags|1<# tags}").unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "{n, plural, =0 {no '#'tags} =1 {no '#'tags} other {'#' tags}}"
+        );
+    }
+
+    #[test]
+    fn lookup_tables_use_select() {
+        let conversion = to_icu("{n,choice,0#none|1#one|2#a few|5#many}").unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "{n, select, 0 {none} 1 {one} 2 {a few} 3 {a few} 4 {a few} other {many}}"
+        );
+        assert_eq!(conversion.warnings.len(), 1);
+    }
+
+    #[test]
+    fn lossy_choices_warn() {
+        let conversion = to_icu("{n,choice,0#none|1#some|100#lots}").unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "{n, plural, =0 {none} =1 {some} other {lots}}"
+        );
+        assert_eq!(conversion.warnings.len(), 1);
+        assert_eq!(conversion.warnings[0].position, 17);
+
+        let conversion = to_icu("{n,choice,1#one|1<many}").unwrap();
+        assert_eq!(conversion.pattern, "{n, plural, =1 {one} other {many}}");
+        assert_eq!(conversion.warnings.len(), 1);
+
+        let conversion = to_icu("{n,choice,0#none|0.5#half|1#one}").unwrap();
+        assert_eq!(conversion.warnings.len(), 2);
+
+        let conversion = to_icu("{n,choice,-1#neg|0#none|1#one|2<many}").unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "{n, select, -1 {neg} 0 {none} 1 {one} 2 {one} other {many}}"
+        );
+        assert_eq!(conversion.warnings[0].position, 10);
+        assert_eq!(
+            conversion.warnings[0].message,
+            "Values below -1 will use the `other` branch rather than this one"
+        );
+    }
+
+    #[test]
+    fn output_is_formatted() {
+        let ctx = Context::default();
+        let conversion =
+            to_icu("There {0,choice,0#are no files|1#is one file|1<are {0,number,integer} files}.")
+                .unwrap();
+        let message = icu::parse(&conversion.pattern).unwrap();
+        for &(count, expected) in &[
+            (0, "There are no files."),
+            (1, "There is one file."),
+            (1200, "There are 1,200 files."),
+        ] {
+            assert_eq!(ctx.format(&message, Some(&arg("0", count))), expected);
+        }
+
+        let conversion = to_icu("{n,choice,0#no #tags|1<# tags}").unwrap();
+        let message = icu::parse(&conversion.pattern).unwrap();
+        assert_eq!(ctx.format(&message, Some(&arg("n", 3))), "# tags");
+        assert_eq!(ctx.format(&message, Some(&arg("n", 1))), "no #tags");
+
+        let conversion = to_icu("{n,choice,0#none|1#one|2#a few|5#many}").unwrap();
+        let message = icu::parse(&conversion.pattern).unwrap();
+        assert_eq!(ctx.format(&message, Some(&arg("n", "3"))), "a few");
+        assert_eq!(ctx.format(&message, Some(&arg("n", "9"))), "many");
+    }
+
+    #[test]
+    fn invalid_choices_fail() {
+        assert!(to_icu("{n,choice}").is_err());
+        assert!(to_icu("{n,choice,zero#none}").is_err());
+        assert!(to_icu("{n,choice,0 none}").is_err());
+    }
+}
@@ -0,0 +1,428 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! .NET Composite Format Strings
+//!
+//! This converts strings written for `String.Format`, such as
+//! `"Total: {0:N2}"`, into ICU patterns, such as
+//! `"Total: {0, number, ::.00}"`.
+//!
+//! Standard numeric format specifiers (`D`, `F`, `G`, `N`, `P` and
+//! `R`) and standard date and time format specifiers are mapped onto
+//! ICU number skeletons and date styles. Custom numeric formats which
+//! only group digits and fix the digits after the separator, such as
+//! `#,##0.0`, are mapped onto skeletons too, and custom date and time
+//! formats are translated into ICU date patterns. [`icu::parse`] does
+//! not read `date` and `time` arguments yet, so patterns with them are
+//! for catalogs which are also used by other ICU implementations.
+//!
+//! Argument alignment (`{0,10}`), hexadecimal formatting, currencies,
+//! whose code is not given, scientific notation and zero padding have
+//! no equivalent and are reported as errors.
+//!
+//! The specifiers `d`, `D`, `f`, `F`, `g` and `G` mean different things
+//! for numbers and for dates. [`to_icu`] treats them as numeric, while
+//! [`to_icu_with`] can be told which arguments are dates.
+//!
+//! [`to_icu`]: fn.to_icu.html
+//! [`to_icu_with`]: fn.to_icu_with.html
+//! [`icu::parse`]: ../../icu/fn.parse.html
+
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use super::{number_style, push_literal, ConvertError, NumberStyle};
+
+/// The kind of value that is passed for an argument.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArgumentKind {
+    /// A numeric value.
+    Number,
+    /// A `DateTime` or `DateTimeOffset` value.
+    DateTime,
+}
+
+/// Convert a .NET composite format string into an ICU pattern.
+///
+/// Format specifiers which could apply to either numbers or dates are
+/// treated as numeric.
+///
+/// ```
+/// use message_format::convert::dotnet;
+///
+/// assert_eq!(dotnet::to_icu("{0} paid {1:N2}.").unwrap(),
+///            "{0} paid {1, number, ::.00}.");
+/// assert_eq!(dotnet::to_icu("Due {0:t}").unwrap(),
+///            "Due {0, time, short}");
+/// ```
+pub fn to_icu(source: &str) -> Result<String, ConvertError> {
+    to_icu_with(source, |_| None)
+}
+
+/// Convert a .NET composite format string into an ICU pattern, using
+/// `kind_of` to look up the kind of value passed for each argument index.
+///
+/// Arguments for which `kind_of` returns `None` have their kind inferred
+/// from the format specifier.
+///
+/// ```
+/// use message_format::convert::dotnet::{self, ArgumentKind};
+///
+/// let kind_of = |index| if index == 0 { Some(ArgumentKind::DateTime) } else { None };
+/// assert_eq!(dotnet::to_icu_with("Ordered {0:d}: {1:d}", kind_of).unwrap(),
+///            "Ordered {0, date, short}: {1, number, ::precision-integer group-off}");
+/// ```
+pub fn to_icu_with<F>(source: &str, kind_of: F) -> Result<String, ConvertError>
+where
+    F: Fn(usize) -> Option<ArgumentKind>,
+{
+    let mut output = String::new();
+    let mut literal = String::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        match c {
+            '{' if next_is(&mut chars, '{') => literal.push('{'),
+            '}' if next_is(&mut chars, '}') => literal.push('}'),
+            '{' => {
+                push_literal(&mut output, &literal);
+                literal.clear();
+                let item = parse_item(source, position, &mut chars)?;
+                let kind = kind_of(item.index);
+                push_item(&mut output, &item, kind)?;
+            }
+            '}' => return Err(ConvertError::syntax(position, "Unmatched '}'")),
+            _ => literal.push(c),
+        }
+    }
+    push_literal(&mut output, &literal);
+    Ok(output)
+}
+
+/// A parsed `{index[,alignment][:format]}` item.
+struct FormatItem<'a> {
+    position: usize,
+    index: usize,
+    format: Option<&'a str>,
+}
+
+/// Consume the next character if it is `expected`.
+fn next_is(chars: &mut Peekable<CharIndices>, expected: char) -> bool {
+    if chars.peek().map(|&(_, c)| c) == Some(expected) {
+        chars.next();
+        true
+    } else {
+        false
+    }
+}
+
+fn skip_spaces(chars: &mut Peekable<CharIndices>) {
+    while chars.peek().map(|&(_, c)| c) == Some(' ') {
+        chars.next();
+    }
+}
+
+/// Parse a format item whose opening brace is at `start`.
+fn parse_item<'a>(
+    source: &'a str,
+    start: usize,
+    chars: &mut Peekable<CharIndices>,
+) -> Result<FormatItem<'a>, ConvertError> {
+    let mut index = None;
+    while let Some(&(_, c)) = chars.peek() {
+        match c.to_digit(10) {
+            Some(digit) => {
+                index = Some(index.unwrap_or(0) * 10 + digit as usize);
+                chars.next();
+            }
+            None => break,
+        }
+    }
+    let index = match index {
+        Some(index) => index,
+        None => return Err(ConvertError::syntax(start, "Expected an argument index")),
+    };
+    skip_spaces(chars);
+
+    if let Some(&(position, ',')) = chars.peek() {
+        let end = source[position..]
+            .find([':', '}'])
+            .map_or(source.len(), |offset| position + offset);
+        return Err(ConvertError::unsupported(position, &source[position..end]));
+    }
+
+    let mut format = None;
+    if next_is(chars, ':') {
+        let format_start = chars.peek().map_or(source.len(), |&(position, _)| position);
+        loop {
+            match chars.next() {
+                Some((position, '}')) => {
+                    format = Some(&source[format_start..position]);
+                    break;
+                }
+                Some((position, '{')) => {
+                    return Err(ConvertError::syntax(position, "Unexpected '{' in format"))
+                }
+                Some(_) => {}
+                None => return Err(ConvertError::syntax(source.len(), "Expected '}'")),
+            }
+        }
+    } else {
+        match chars.next() {
+            Some((_, '}')) => {}
+            Some((position, _)) => return Err(ConvertError::syntax(position, "Expected '}'")),
+            None => return Err(ConvertError::syntax(source.len(), "Expected '}'")),
+        }
+    }
+
+    Ok(FormatItem {
+        position: start,
+        index,
+        format,
+    })
+}
+
+/// Append the ICU form of `item` to `output`.
+fn push_item(
+    output: &mut String,
+    item: &FormatItem,
+    kind: Option<ArgumentKind>,
+) -> Result<(), ConvertError> {
+    let format = match item.format {
+        Some(format) if !format.is_empty() => format,
+        _ => {
+            output.push_str(&format!("{{{}}}", item.index));
+            return Ok(());
+        }
+    };
+
+    let mut standard = format.chars();
+    let specifier = standard.next().unwrap_or(' ');
+    let precision = standard.as_str();
+    let is_standard = specifier.is_ascii_alphabetic()
+        && precision.len() <= 2
+        && precision.chars().all(|c| c.is_ascii_digit());
+
+    let styles = if is_standard {
+        let precision = precision.parse().ok();
+        match kind.unwrap_or_else(|| standard_kind(specifier)) {
+            ArgumentKind::Number => numeric_style(specifier, precision).map(|style| vec![style]),
+            ArgumentKind::DateTime => date_styles(specifier),
+        }
+    } else {
+        let custom = if format.starts_with('%') && format.len() > 1 {
+            &format[1..]
+        } else {
+            format
+        };
+        let is_numeric = custom.chars().all(|c| "0#,.%\u{2030};E+- ".contains(c));
+        if kind == Some(ArgumentKind::DateTime) || (kind.is_none() && !is_numeric) {
+            date_pattern(custom).map(|pattern| vec![("date", pattern)])
+        } else if is_numeric {
+            number_style(Some(custom)).map(|style| vec![("number", style.to_icu())])
+        } else {
+            None
+        }
+    };
+
+    match styles {
+        Some(styles) => {
+            for (i, &(kind, ref style)) in styles.iter().enumerate() {
+                if i > 0 {
+                    output.push(' ');
+                }
+                if style.is_empty() {
+                    output.push_str(&format!("{{{}, {}}}", item.index, kind));
+                } else {
+                    output.push_str(&format!("{{{}, {}, {}}}", item.index, kind, style));
+                }
+            }
+            Ok(())
+        }
+        None => Err(ConvertError::unsupported(item.position, format)),
+    }
+}
+
+/// The kind implied by a standard format specifier.
+fn standard_kind(specifier: char) -> ArgumentKind {
+    match specifier {
+        't' | 'T' | 'M' | 'm' | 'Y' | 'y' | 's' | 'O' | 'o' | 'u' | 'U' => ArgumentKind::DateTime,
+        _ => ArgumentKind::Number,
+    }
+}
+
+/// Map a standard numeric format specifier onto an ICU number style.
+fn numeric_style(specifier: char, precision: Option<usize>) -> Option<(&'static str, String)> {
+    let (grouping, fraction_digits, percent) = match specifier {
+        'D' | 'd' if precision.unwrap_or(1) <= 1 => (false, Some(0), false),
+        'F' | 'f' => (false, Some(precision.unwrap_or(2)), false),
+        'G' | 'g' | 'R' | 'r' => (true, None, false),
+        'N' | 'n' => (true, Some(precision.unwrap_or(2)), false),
+        'P' | 'p' => (true, Some(precision.unwrap_or(2)), true),
+        _ => return None,
+    };
+    let style = NumberStyle {
+        grouping,
+        fraction_digits,
+        percent,
+    };
+    Some(("number", style.to_icu()))
+}
+
+/// Map a standard date and time format specifier onto ICU date and
+/// time styles.
+fn date_styles(specifier: char) -> Option<Vec<(&'static str, String)>> {
+    let styles: &[(&'static str, &str)] = match specifier {
+        'd' => &[("date", "short")],
+        'D' => &[("date", "full")],
+        't' => &[("time", "short")],
+        'T' => &[("time", "medium")],
+        'f' => &[("date", "full"), ("time", "short")],
+        'F' => &[("date", "full"), ("time", "medium")],
+        'g' => &[("date", "short"), ("time", "short")],
+        'G' => &[("date", "short"), ("time", "medium")],
+        'M' | 'm' => &[("date", "MMMM d")],
+        'Y' | 'y' => &[("date", "MMMM y")],
+        's' => &[("date", "yyyy-MM-dd'T'HH:mm:ss")],
+        'O' | 'o' => &[("date", "yyyy-MM-dd'T'HH:mm:ss.SSSSSSSxxx")],
+        _ => return None,
+    };
+    Some(
+        styles
+            .iter()
+            .map(|&(kind, style)| (kind, style.to_string()))
+            .collect(),
+    )
+}
+
+/// Translate a custom .NET date and time format into an ICU date pattern.
+fn date_pattern(format: &str) -> Option<String> {
+    let mut pattern = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let mut count = 1;
+        while c.is_ascii_alphabetic() && chars.peek() == Some(&c) {
+            chars.next();
+            count += 1;
+        }
+        let field = match c {
+            'd' if count <= 2 => "d".repeat(count),
+            'd' if count == 3 => "EEE".to_string(),
+            'd' => "EEEE".to_string(),
+            'M' | 'y' | 'h' | 'H' | 'm' | 's' => c.to_string().repeat(count),
+            'f' | 'F' => "S".repeat(count),
+            't' => "a".to_string(),
+            'z' if count <= 2 => "x".to_string(),
+            'z' => "xxx".to_string(),
+            'g' => "G".to_string(),
+            '\'' | '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some(q) => text.push(q),
+                        None => return None,
+                    }
+                }
+                quote_date_literal(&text)
+            }
+            '\\' => quote_date_literal(&chars.next()?.to_string()),
+            _ if c.is_ascii_alphabetic() => return None,
+            _ => quote_date_literal(&c.to_string()),
+        };
+        pattern.push_str(&field);
+    }
+    Some(pattern)
+}
+
+/// Quote literal text for use within an ICU date pattern.
+fn quote_date_literal(text: &str) -> String {
+    if text == "'" {
+        "''".to_string()
+    } else if text
+        .chars()
+        .any(|c| c.is_ascii_alphabetic() || "'{}".contains(c))
+    {
+        format!("'{}'", text.replace('\'', "''"))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {arg, icu, Context};
+
+    #[test]
+    fn plain_arguments_work() {
+        assert_eq!(to_icu("{0} is from {1}.").unwrap(), "{0} is from {1}.");
+        assert_eq!(to_icu("{{literal}} {0}").unwrap(), "'{'literal'}' {0}");
+    }
+
+    #[test]
+    fn numeric_specifiers_work() {
+        assert_eq!(to_icu("{0:N2}").unwrap(), "{0, number, ::.00}");
+        assert_eq!(to_icu("{0:N0}").unwrap(), "{0, number, integer}");
+        assert_eq!(to_icu("{0:P0}").unwrap(), "{0, number, percent}");
+        assert_eq!(
+            to_icu("{0:P1}").unwrap(),
+            "{0, number, ::percent scale/100 .0}"
+        );
+        assert_eq!(to_icu("{0:F}").unwrap(), "{0, number, ::.00 group-off}");
+        assert_eq!(to_icu("{0:G}").unwrap(), "{0, number}");
+        assert_eq!(to_icu("{0:#,##0.0}").unwrap(), "{0, number, ::.0}");
+        assert_eq!(to_icu("{0:C}"), Err(ConvertError::unsupported(0, "C")));
+        assert_eq!(to_icu("{0:D4}"), Err(ConvertError::unsupported(0, "D4")));
+        assert_eq!(to_icu("{0:E2}"), Err(ConvertError::unsupported(0, "E2")));
+    }
+
+    #[test]
+    fn output_is_formatted() {
+        let ctx = Context::default();
+        for &(source, expected) in &[
+            ("{0:N2} and {0:F1}", "1,234.50 and 1234.5"),
+            ("{0:N0}, {1:P1} and {1:P0}", "1,234, 12.5% and 12%"),
+            ("{2:D} of {0:G}! It's {{{2}}}", "7 of 1,234.5! It's {7}"),
+            ("{0:0.000}", "1234.500"),
+        ] {
+            let pattern = to_icu(source).unwrap();
+            let message = icu::parse(&pattern).unwrap();
+            let first = arg("0", 1234.5);
+            let second = first.arg("1", 0.125);
+            let args = second.arg("2", 7);
+            assert_eq!(ctx.format(&message, Some(&args)), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn date_specifiers_work() {
+        assert_eq!(to_icu("{0:T}").unwrap(), "{0, time, medium}");
+        assert_eq!(to_icu("{0:yyyy-MM-dd}").unwrap(), "{0, date, yyyy-MM-dd}");
+        assert_eq!(
+            to_icu("{0:dddd 'at' HH:mm}").unwrap(),
+            "{0, date, EEEE 'at' HH:mm}"
+        );
+        let kind_of = |_| Some(ArgumentKind::DateTime);
+        assert_eq!(
+            to_icu_with("{0:g}", kind_of).unwrap(),
+            "{0, date, short} {0, time, short}"
+        );
+    }
+
+    #[test]
+    fn unsupported_constructs_fail() {
+        assert_eq!(to_icu("Id: {0,8}"), Err(ConvertError::unsupported(6, ",8")));
+        assert_eq!(to_icu("{0:X}"), Err(ConvertError::unsupported(0, "X")));
+        assert_eq!(
+            to_icu("{name}"),
+            Err(ConvertError::syntax(0, "Expected an argument index"))
+        );
+        assert!(to_icu("{0").is_err());
+        assert!(to_icu("0}").is_err());
+    }
+}
@@ -0,0 +1,556 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! gettext Plural Forms Migration
+//!
+//! A gettext PO file gives each translation with plural forms as a list
+//! of `msgstr[n]` entries. Which entry is used for a number is decided
+//! by the C expression in the `Plural-Forms` header of the file:
+//!
+//! ```text
+//! "Plural-Forms: nplurals=2; plural=(n != 1);\n"
+//! ```
+//!
+//! ICU `plural` arguments use the CLDR plural categories instead. The
+//! same gettext expression can correspond to different categories in
+//! different languages, so [`to_icu_plural`] compares the expression
+//! against a plural classifier for the language, such as
+//! [`english_cardinal_classifier`], to decide which entry belongs to
+//! which category.
+//!
+//! Values for which the expression and the classifier disagree are
+//! given exact `=n` branches. When there are too many of these to list,
+//! a [`Warning`] is reported instead.
+//!
+//! The text of each entry is copied as is, including any printf-style
+//! directives such as `%d`.
+//!
+//! [`to_icu_plural`]: fn.to_icu_plural.html
+//! [`english_cardinal_classifier`]: ../../fn.english_cardinal_classifier.html
+//! [`Warning`]: ../struct.Warning.html
+
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use super::{push_plural_literal, Conversion, ConvertError, Warning};
+use PluralCategory;
+
+/// The largest number of values within a category that are given their
+/// own exact branch before a warning is reported instead.
+const MAX_EXACT_VALUES: usize = 3;
+
+/// The categories in the order their branches are written.
+const CATEGORIES: [PluralCategory; 6] = [
+    PluralCategory::Zero,
+    PluralCategory::One,
+    PluralCategory::Two,
+    PluralCategory::Few,
+    PluralCategory::Many,
+    PluralCategory::Other,
+];
+
+/// The parsed value of a `Plural-Forms` header.
+#[derive(Debug)]
+pub struct PluralForms {
+    nplurals: usize,
+    plural: Expression,
+}
+
+impl PluralForms {
+    /// Parse the value of a `Plural-Forms` header, with or without the
+    /// leading `Plural-Forms:`.
+    ///
+    /// ```
+    /// use message_format::convert::gettext::PluralForms;
+    ///
+    /// let forms = PluralForms::parse("nplurals=2; plural=(n != 1);").unwrap();
+    /// assert_eq!(forms.nplurals(), 2);
+    /// assert_eq!(forms.index(1), 0);
+    /// assert_eq!(forms.index(5), 1);
+    /// ```
+    pub fn parse(header: &str) -> Result<Self, ConvertError> {
+        let mut nplurals = None;
+        let mut plural = None;
+        let start = if header
+            .trim_start()
+            .to_ascii_lowercase()
+            .starts_with("plural-forms")
+        {
+            header.find(':').map_or(0, |colon| colon + 1)
+        } else {
+            0
+        };
+        let mut offset = start;
+
+        for field in header[start..].split(';') {
+            let field_offset = offset;
+            offset += field.len() + 1;
+            if field.trim().is_empty() {
+                continue;
+            }
+            let equals = match field.find('=') {
+                Some(equals) => equals,
+                None => return Err(ConvertError::syntax(field_offset, "Expected '='")),
+            };
+            let value = &field[equals + 1..];
+            let value_offset = field_offset + equals + 1;
+            match field[..equals].trim() {
+                "nplurals" => match value.trim().parse() {
+                    Ok(count) if count > 0 => nplurals = Some(count),
+                    _ => return Err(ConvertError::syntax(value_offset, "Expected a count")),
+                },
+                "plural" => {
+                    let mut parser = ExpressionParser {
+                        source: value,
+                        chars: value.char_indices().peekable(),
+                    };
+                    let expression = parser.parse().map_err(|error| error.offset(value_offset))?;
+                    plural = Some(expression);
+                }
+                _ => return Err(ConvertError::syntax(field_offset, "Unknown field")),
+            }
+        }
+
+        match (nplurals, plural) {
+            (Some(nplurals), Some(plural)) => Ok(PluralForms { nplurals, plural }),
+            (None, _) => Err(ConvertError::syntax(header.len(), "Expected nplurals")),
+            (_, None) => Err(ConvertError::syntax(header.len(), "Expected plural")),
+        }
+    }
+
+    /// The number of plural forms each translation provides.
+    pub fn nplurals(&self) -> usize {
+        self.nplurals
+    }
+
+    /// The index of the `msgstr` entry that gettext uses for `n`.
+    pub fn index(&self, n: u64) -> usize {
+        self.plural.evaluate(n) as usize
+    }
+}
+
+/// Convert the `msgstr[n]` entries of a translation into an ICU `plural`
+/// argument on the variable `variable_name`.
+///
+/// ```
+/// use message_format::convert::gettext::{self, PluralForms};
+/// use message_format::english_cardinal_classifier;
+///
+/// let forms = PluralForms::parse("nplurals=2; plural=n>1;").unwrap();
+/// let conversion = gettext::to_icu_plural(&forms, english_cardinal_classifier,
+///                                         "count", &["%d file", "%d files"]).unwrap();
+/// assert_eq!(conversion.pattern,
+///            "{count, plural, =0 {%d file} one {%d file} other {%d files}}");
+/// ```
+pub fn to_icu_plural(
+    forms: &PluralForms,
+    classifier: fn(i64) -> PluralCategory,
+    variable_name: &str,
+    msgstrs: &[&str],
+) -> Result<Conversion, ConvertError> {
+    if msgstrs.len() != forms.nplurals {
+        return Err(ConvertError::syntax(
+            0,
+            &format!("Expected {} plural forms", forms.nplurals),
+        ));
+    }
+
+    // For each category, the index used by each sampled value.
+    let mut samples: Vec<Vec<(u64, usize)>> = vec![vec![]; CATEGORIES.len()];
+    for n in (0..=1000).chain(vec![10_000, 100_000, 1_000_000]) {
+        let index = forms.index(n);
+        if index >= msgstrs.len() {
+            return Err(ConvertError::syntax(
+                0,
+                &format!("The plural expression selects form {} for {}", index, n),
+            ));
+        }
+        samples[category_index(classifier(n as i64))].push((n, index));
+    }
+
+    let mut warnings = vec![];
+    let mut exact = vec![];
+    let mut branches = vec![];
+    let mut used = vec![false; msgstrs.len()];
+    for (category, samples) in CATEGORIES.iter().zip(&samples) {
+        let index = match most_common_index(samples, msgstrs.len()) {
+            Some(index) => index,
+            None if *category == PluralCategory::Other => msgstrs.len() - 1,
+            None => continue,
+        };
+        used[index] = true;
+        branches.push((category_keyword(*category).to_string(), index));
+
+        let exceptions: Vec<_> = samples.iter().filter(|s| s.1 != index).collect();
+        if exceptions.len() <= MAX_EXACT_VALUES {
+            for &&(n, index) in &exceptions {
+                used[index] = true;
+                exact.push((n, index));
+            }
+        } else {
+            warnings.push(Warning {
+                position: 0,
+                message: format!(
+                    "Some values in the `{}` category, such as {}, use form {} rather than {}",
+                    category_keyword(*category),
+                    exceptions[0].0,
+                    exceptions[0].1,
+                    index
+                ),
+            });
+        }
+    }
+    for (index, used) in used.iter().enumerate() {
+        if !used {
+            warnings.push(Warning {
+                position: 0,
+                message: format!("Form {} is not used by any category", index),
+            });
+        }
+    }
+
+    exact.sort();
+    let mut pattern = format!("{{{}, plural,", variable_name);
+    let exact = exact
+        .into_iter()
+        .map(|(n, index)| (format!("={}", n), index));
+    for (key, index) in exact.chain(branches) {
+        pattern.push_str(&format!(" {} {{", key));
+        push_plural_literal(&mut pattern, msgstrs[index]);
+        pattern.push('}');
+    }
+    pattern.push('}');
+    Ok(Conversion { pattern, warnings })
+}
+
+fn category_index(category: PluralCategory) -> usize {
+    CATEGORIES
+        .iter()
+        .position(|&c| c == category)
+        .unwrap_or(CATEGORIES.len() - 1)
+}
+
+fn category_keyword(category: PluralCategory) -> &'static str {
+    match category {
+        PluralCategory::Zero => "zero",
+        PluralCategory::One => "one",
+        PluralCategory::Two => "two",
+        PluralCategory::Few => "few",
+        PluralCategory::Many => "many",
+        PluralCategory::Other => "other",
+    }
+}
+
+/// The index used by the most sampled values, preferring lower indices.
+fn most_common_index(samples: &[(u64, usize)], forms: usize) -> Option<usize> {
+    let mut counts = vec![0; forms];
+    for &(_, index) in samples {
+        counts[index] += 1;
+    }
+    let max = *counts.iter().max().unwrap_or(&0);
+    if max == 0 {
+        None
+    } else {
+        counts.iter().position(|&count| count == max)
+    }
+}
+
+/// An operator in a plural expression.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+/// A plural expression, which follows the syntax and unsigned integer
+/// semantics of C.
+#[derive(Debug)]
+enum Expression {
+    N,
+    Number(u64),
+    Not(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Conditional(Box<Expression>, Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    fn evaluate(&self, n: u64) -> u64 {
+        match *self {
+            Expression::N => n,
+            Expression::Number(value) => value,
+            Expression::Not(ref operand) => (operand.evaluate(n) == 0) as u64,
+            Expression::Conditional(ref condition, ref then, ref otherwise) => {
+                if condition.evaluate(n) != 0 {
+                    then.evaluate(n)
+                } else {
+                    otherwise.evaluate(n)
+                }
+            }
+            Expression::Binary(operator, ref left, ref right) => {
+                let left = left.evaluate(n);
+                let right = right.evaluate(n);
+                match operator {
+                    Operator::Or => (left != 0 || right != 0) as u64,
+                    Operator::And => (left != 0 && right != 0) as u64,
+                    Operator::Equal => (left == right) as u64,
+                    Operator::NotEqual => (left != right) as u64,
+                    Operator::Less => (left < right) as u64,
+                    Operator::LessOrEqual => (left <= right) as u64,
+                    Operator::Greater => (left > right) as u64,
+                    Operator::GreaterOrEqual => (left >= right) as u64,
+                    Operator::Add => left.wrapping_add(right),
+                    Operator::Subtract => left.wrapping_sub(right),
+                    Operator::Multiply => left.wrapping_mul(right),
+                    Operator::Divide => left.checked_div(right).unwrap_or(0),
+                    Operator::Remainder => left.checked_rem(right).unwrap_or(0),
+                }
+            }
+        }
+    }
+}
+
+/// A recursive descent parser for plural expressions.
+struct ExpressionParser<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> ExpressionParser<'a> {
+    fn parse(&mut self) -> Result<Expression, ConvertError> {
+        let expression = self.conditional()?;
+        self.skip_whitespace();
+        match self.chars.peek() {
+            None => Ok(expression),
+            Some(&(position, _)) => Err(ConvertError::syntax(position, "Unexpected character")),
+        }
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map_or(self.source.len(), |&(position, _)| position)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|&(_, c)| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    /// Consume `token` if it is next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let position = self.position();
+        if self.source[position..].starts_with(token) {
+            for _ in token.chars() {
+                self.chars.next();
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume the first of `operators` that is next.
+    fn operator(&mut self, operators: &[(&str, Operator)]) -> Option<Operator> {
+        operators
+            .iter()
+            .find(|&&(token, _)| self.eat(token))
+            .map(|&(_, operator)| operator)
+    }
+
+    fn conditional(&mut self) -> Result<Expression, ConvertError> {
+        let condition = self.binary(0)?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        let then = self.conditional()?;
+        if !self.eat(":") {
+            return Err(ConvertError::syntax(self.position(), "Expected ':'"));
+        }
+        let otherwise = self.conditional()?;
+        Ok(Expression::Conditional(
+            Box::new(condition),
+            Box::new(then),
+            Box::new(otherwise),
+        ))
+    }
+
+    /// Parse the binary operators with the given precedence `level` or
+    /// higher.
+    fn binary(&mut self, level: usize) -> Result<Expression, ConvertError> {
+        const LEVELS: [&[(&str, Operator)]; 6] = [
+            &[("||", Operator::Or)],
+            &[("&&", Operator::And)],
+            &[("==", Operator::Equal), ("!=", Operator::NotEqual)],
+            &[
+                ("<=", Operator::LessOrEqual),
+                (">=", Operator::GreaterOrEqual),
+                ("<", Operator::Less),
+                (">", Operator::Greater),
+            ],
+            &[("+", Operator::Add), ("-", Operator::Subtract)],
+            &[
+                ("*", Operator::Multiply),
+                ("/", Operator::Divide),
+                ("%", Operator::Remainder),
+            ],
+        ];
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(operator) = self.operator(LEVELS[level]) {
+            let right = self.binary(level + 1)?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expression, ConvertError> {
+        if self.eat("!") {
+            return Ok(Expression::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let expression = self.conditional()?;
+            if !self.eat(")") {
+                return Err(ConvertError::syntax(self.position(), "Expected ')'"));
+            }
+            return Ok(expression);
+        }
+        if self.eat("n") {
+            return Ok(Expression::N);
+        }
+        let start = self.position();
+        while self.chars.peek().is_some_and(|&(_, c)| c.is_ascii_digit()) {
+            self.chars.next();
+        }
+        match self.source[start..self.position()].parse() {
+            Ok(value) => Ok(Expression::Number(value)),
+            Err(_) => Err(ConvertError::syntax(start, "Expected an operand")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {arg, english_cardinal_classifier, icu, Context};
+
+    const SLAVIC: &str = "Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : \
+                          n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);";
+
+    fn russian_classifier(n: i64) -> PluralCategory {
+        match (n % 10, n % 100) {
+            (1, r) if r != 11 => PluralCategory::One,
+            (2..=4, r) if !(12..=14).contains(&r) => PluralCategory::Few,
+            _ => PluralCategory::Many,
+        }
+    }
+
+    #[test]
+    fn expressions_work() {
+        let forms = PluralForms::parse(SLAVIC).unwrap();
+        assert_eq!(forms.nplurals(), 3);
+        let indices: Vec<_> = [1, 2, 5, 11, 12, 21, 22, 25, 111]
+            .iter()
+            .map(|&n| forms.index(n))
+            .collect();
+        assert_eq!(indices, vec![0, 1, 2, 2, 2, 0, 1, 2, 2]);
+
+        let forms = PluralForms::parse("nplurals=2; plural=n == 1 ? 0 : 1").unwrap();
+        assert_eq!((forms.index(1), forms.index(2)), (0, 1));
+        let forms = PluralForms::parse("nplurals=1; plural=0").unwrap();
+        assert_eq!(forms.index(7), 0);
+        let forms = PluralForms::parse("nplurals=2; plural=!(n/2 - 1 * 0)").unwrap();
+        assert_eq!((forms.index(1), forms.index(2)), (1, 0));
+    }
+
+    #[test]
+    fn invalid_headers_fail() {
+        assert!(PluralForms::parse("nplurals=2;").is_err());
+        assert!(PluralForms::parse("plural=n != 1;").is_err());
+        assert!(PluralForms::parse("nplurals=2; plural=(n != 1;").is_err());
+        assert!(PluralForms::parse("nplurals=2; plural=n ? 1;").is_err());
+        assert!(PluralForms::parse("nplurals=2; plural=x;").is_err());
+    }
+
+    #[test]
+    fn matching_categories_work() {
+        let forms = PluralForms::parse(SLAVIC).unwrap();
+        let conversion = to_icu_plural(
+            &forms,
+            russian_classifier,
+            "n",
+            &["# файл", "{n} файла", "%d файлов"],
+        )
+        .unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "{n, plural, one {'#' файл} few {'{'n'}' файла} many {%d файлов} \
+             other {%d файлов}}"
+        );
+        assert!(conversion.warnings.is_empty());
+    }
+
+    #[test]
+    fn output_is_formatted() {
+        let forms = PluralForms::parse(SLAVIC).unwrap();
+        let conversion = to_icu_plural(
+            &forms,
+            russian_classifier,
+            "n",
+            &["# файл", "{n} файла", "%d файлов"],
+        )
+        .unwrap();
+        assert!(icu::parse(&conversion.pattern).is_ok());
+
+        let forms = PluralForms::parse("nplurals=2; plural=n != 1;").unwrap();
+        let conversion = to_icu_plural(
+            &forms,
+            english_cardinal_classifier,
+            "n",
+            &["One file in {dir}", "%d files in {dir}"],
+        )
+        .unwrap();
+        let message = icu::parse(&conversion.pattern).unwrap();
+        let ctx = Context::default();
+        let args = arg("n", 1);
+        assert_eq!(
+            ctx.format(&message, Some(&args.arg("dir", "tmp"))),
+            "One file in {dir}"
+        );
+        let args = arg("n", 2);
+        assert_eq!(
+            ctx.format(&message, Some(&args.arg("dir", "tmp"))),
+            "%d files in {dir}"
+        );
+    }
+
+    #[test]
+    fn mismatched_categories_warn() {
+        let forms = PluralForms::parse(SLAVIC).unwrap();
+        let conversion =
+            to_icu_plural(&forms, english_cardinal_classifier, "n", &["a", "b", "c"]).unwrap();
+        assert_eq!(conversion.pattern, "{n, plural, one {a} other {c}}");
+        assert_eq!(conversion.warnings.len(), 2);
+
+        assert!(to_icu_plural(&forms, english_cardinal_classifier, "n", &["a", "b"]).is_err());
+        let forms = PluralForms::parse("nplurals=2; plural=n;").unwrap();
+        assert!(to_icu_plural(&forms, english_cardinal_classifier, "n", &["a", "b"]).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! i18next Interpolation
+//!
+//! This converts i18next translation strings into ICU patterns:
+//!
+//! * `{{name}}` and the unescaped `{{- name}}` become `{name}`. Nested
+//!   property paths such as `{{user.name}}` keep their dotted names.
+//! * `{{value, number}}` becomes `{value, number}`, with the
+//!   `minimumFractionDigits` and `maximumFractionDigits` options mapped
+//!   onto a number skeleton. `{{price, currency(USD)}}` becomes
+//!   `{price, number, ::currency/USD}` and `{{when, datetime}}` becomes
+//!   `{when, date}`. [`icu::parse`] does not read `date` arguments yet,
+//!   so patterns with them are for catalogs which are also used by
+//!   other ICU implementations.
+//! * Nesting with `$t(key)` becomes the message reference `{@msg key}`,
+//!   which [`Catalog::format`] resolves. Message references are not
+//!   part of standard ICU syntax.
+//!
+//! Other formats and nesting with options are reported as errors. The
+//! HTML escaping which i18next applies to `{{name}}` is dropped. Plurals
+//! and contexts are selected by key suffixes in i18next rather than
+//! within a string and so are not handled here.
+//!
+//! [`Catalog::format`]: ../../struct.Catalog.html#method.format
+//! [`icu::parse`]: ../../icu/fn.parse.html
+
+use super::{push_literal, ConvertError};
+
+/// Convert an i18next translation string into an ICU pattern.
+///
+/// ```
+/// use message_format::convert::i18next;
+///
+/// assert_eq!(i18next::to_icu("Hello {{name}}, you owe {{total, currency(EUR)}}.").unwrap(),
+///            "Hello {name}, you owe {total, number, ::currency/EUR}.");
+/// assert_eq!(i18next::to_icu("Welcome to $t(app.name)!").unwrap(),
+///            "Welcome to {@msg app.name}!");
+/// ```
+pub fn to_icu(source: &str) -> Result<String, ConvertError> {
+    let mut output = String::new();
+    let mut literal_start = 0;
+    let mut position = 0;
+
+    while position < source.len() {
+        let rest = &source[position..];
+        let converted = if rest.starts_with("{{") {
+            let end = match rest.find("}}") {
+                Some(end) => end,
+                None => return Err(ConvertError::syntax(source.len(), "Expected '}}'")),
+            };
+            Some((interpolation(&rest[2..end], position)?, end + 2))
+        } else if rest.starts_with("$t(") {
+            let end = match rest.find(')') {
+                Some(end) => end,
+                None => return Err(ConvertError::syntax(source.len(), "Expected ')'")),
+            };
+            let key = rest[3..end].trim();
+            if key.is_empty() || key.contains(',') || key.contains('{') {
+                return Err(ConvertError::unsupported(position, &rest[..end + 1]));
+            }
+            Some((format!("{{@msg {}}}", key), end + 1))
+        } else {
+            None
+        };
+
+        match converted {
+            Some((argument, length)) => {
+                push_literal(&mut output, &source[literal_start..position]);
+                output.push_str(&argument);
+                position += length;
+                literal_start = position;
+            }
+            None => position += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    push_literal(&mut output, &source[literal_start..]);
+    Ok(output)
+}
+
+/// Convert the contents of a `{{...}}` interpolation found at `position`.
+fn interpolation(contents: &str, position: usize) -> Result<String, ConvertError> {
+    let contents = contents.trim();
+    let contents = contents.strip_prefix('-').unwrap_or(contents).trim();
+    let (name, format) = match contents.find(',') {
+        Some(comma) => (contents[..comma].trim(), Some(contents[comma + 1..].trim())),
+        None => (contents, None),
+    };
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || "{}".contains(c)) {
+        return Err(ConvertError::syntax(position, "Expected a variable name"));
+    }
+
+    let format = match format {
+        Some(format) => format,
+        None => return Ok(format!("{{{}}}", name)),
+    };
+    let (format_name, options) = match format.find('(') {
+        Some(open) if format.ends_with(')') => {
+            let options = format[open + 1..format.len() - 1]
+                .split(',')
+                .map(str::trim)
+                .filter(|option| !option.is_empty())
+                .collect();
+            (format[..open].trim(), options)
+        }
+        Some(_) => return Err(ConvertError::syntax(position, "Expected ')'")),
+        None => (format, vec![]),
+    };
+
+    let style = match (format_name, options.as_slice()) {
+        ("number", options) => match fraction_skeleton(options) {
+            Some(skeleton) => skeleton,
+            None => return Err(ConvertError::unsupported(position, format)),
+        },
+        ("currency", [code]) if code.len() == 3 && code.chars().all(|c| c.is_ascii_uppercase()) => {
+            format!(", ::currency/{}", code)
+        }
+        ("datetime", []) => return Ok(format!("{{{}, date}}", name)),
+        _ => return Err(ConvertError::unsupported(position, format)),
+    };
+    Ok(format!("{{{}, number{}}}", name, style))
+}
+
+/// Map the fraction digit options of the i18next `number` format onto
+/// an ICU number skeleton, including the leading separator.
+fn fraction_skeleton(options: &[&str]) -> Option<String> {
+    let mut minimum = None;
+    let mut maximum = None;
+    for option in options {
+        let colon = option.find(':')?;
+        let value = option[colon + 1..].trim().parse::<usize>().ok()?;
+        match option[..colon].trim() {
+            "minimumFractionDigits" => minimum = Some(value),
+            "maximumFractionDigits" => maximum = Some(value),
+            _ => return None,
+        }
+    }
+    if minimum.is_none() && maximum.is_none() {
+        return Some(String::new());
+    }
+    let minimum = minimum.unwrap_or(0);
+    let maximum = maximum.unwrap_or(minimum.max(3));
+    if maximum < minimum {
+        return None;
+    }
+    if maximum == 0 {
+        return Some(", ::precision-integer".to_string());
+    }
+    Some(format!(
+        ", ::.{}{}",
+        "0".repeat(minimum),
+        "#".repeat(maximum - minimum)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_icu;
+    use {arg, icu, Context};
+
+    #[test]
+    fn interpolation_works() {
+        assert_eq!(
+            to_icu("{{name}} and {{- html}}").unwrap(),
+            "{name} and {html}"
+        );
+        assert_eq!(to_icu("{{ user.name }}").unwrap(), "{user.name}");
+        assert_eq!(
+            to_icu("{literal} 'quoted'").unwrap(),
+            "'{'literal'}' ''quoted''"
+        );
+        assert!(to_icu("{{name").is_err());
+        assert!(to_icu("{{}}").is_err());
+    }
+
+    #[test]
+    fn formats_work() {
+        assert_eq!(to_icu("{{n, number}}").unwrap(), "{n, number}");
+        assert_eq!(
+            to_icu("{{n, number(minimumFractionDigits: 2)}}").unwrap(),
+            "{n, number, ::.00#}"
+        );
+        assert_eq!(
+            to_icu("{{n, number(maximumFractionDigits: 0)}}").unwrap(),
+            "{n, number, ::precision-integer}"
+        );
+        assert_eq!(to_icu("{{d, datetime}}").unwrap(), "{d, date}");
+        assert!(to_icu("{{n, number(style: percent)}}").is_err());
+        assert!(to_icu("{{n, currency}}").is_err());
+        assert!(to_icu("{{list, list}}").is_err());
+    }
+
+    #[test]
+    fn output_is_formatted() {
+        let ctx = Context::default();
+        for &(source, value, expected) in &[
+            ("{{n, number}}", 1234.5, "1,234.5"),
+            ("{{n, number(minimumFractionDigits: 2)}}", 1.5, "1.50"),
+            ("{{n, number(minimumFractionDigits: 2)}}", 1.23456, "1.235"),
+            ("{{n, number(maximumFractionDigits: 1)}}", 2.0, "2"),
+            ("{{n, number(maximumFractionDigits: 0)}}", 2.5, "2"),
+            ("{{n, currency(USD)}}", 3.0, "$3"),
+        ] {
+            let message = icu::parse(&to_icu(source).unwrap()).unwrap();
+            assert_eq!(
+                ctx.format(&message, Some(&arg("n", value))),
+                expected,
+                "{}",
+                source
+            );
+        }
+        let message = icu::parse(&to_icu("{{name}}'s {html}").unwrap()).unwrap();
+        assert_eq!(
+            ctx.format(&message, Some(&arg("name", "Ann"))),
+            "Ann's {html}"
+        );
+    }
+
+    #[test]
+    fn nesting_works() {
+        assert_eq!(
+            to_icu("$t(common:greeting), {{name}}").unwrap(),
+            "{@msg common:greeting}, {name}"
+        );
+        assert!(to_icu("$t(items, {\"count\": 2})").is_err());
+        assert!(to_icu("$t(items").is_err());
+    }
+}
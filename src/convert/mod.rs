@@ -0,0 +1,487 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Format String Conversion
+//!
+//! This module provides converters from other format string syntaxes
+//! into [ICU Message Format] patterns. This is useful when migrating
+//! strings from a codebase written in another language into a catalog
+//! that is shared with Rust code.
+//!
+//! Converters produce the text of an ICU pattern rather than a parsed
+//! [`Message`], as the converted output is typically written back into
+//! a catalog. The output uses standard ICU syntax, including apostrophe
+//! quoting for literal braces.
+//!
+//! The supported syntaxes are:
+//!
+//! * [`dotnet`]: .NET composite format strings such as `{0:N2}`.
+//! * [`python`]: Python `str.format` strings such as `{name}` and
+//!   `%`-format strings such as `%(name)s`. ICU patterns can also be
+//!   converted back into either Python syntax.
+//! * [`choice`]: Java `ChoiceFormat` arguments such as
+//!   `{0,choice,0#no files|1#one file|1<{0} files}`, rewritten into
+//!   `plural` or `select` arguments.
+//! * [`gettext`]: the `msgstr[n]` plural forms of a gettext PO file,
+//!   rewritten into a `plural` argument.
+//! * [`i18next`]: i18next interpolation such as `{{name}}` and nesting
+//!   such as `$t(key)`.
+//! * [`mustache`]: the variable subset of Mustache templates such as
+//!   `{{name}}`, converted into a [`Message`] rather than a pattern.
+//! * [`php`]: Symfony and Laravel placeholders such as `%name%` and
+//!   `:name` and pipe pluralizations such as `{0} none|[1,*] some`.
+//! * [`printf`]: printf format strings such as `%2$s`, keeping explicit
+//!   argument positions. ICU patterns can also be converted back.
+//!
+//! [ICU Message Format]: ../icu/index.html
+//! [`Message`]: ../struct.Message.html
+//! [`dotnet`]: dotnet/index.html
+//! [`python`]: python/index.html
+//! [`choice`]: choice/index.html
+//! [`gettext`]: gettext/index.html
+//! [`i18next`]: i18next/index.html
+//! [`mustache`]: mustache/index.html
+//! [`php`]: php/index.html
+//! [`printf`]: printf/index.html
+
+use std::error::Error;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use NumberOptions;
+
+pub mod choice;
+pub mod dotnet;
+pub mod gettext;
+pub mod i18next;
+pub mod mustache;
+pub mod php;
+pub mod printf;
+pub mod python;
+
+/// An error resulting from converting a format string.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConvertError {
+    /// The source string is not well formed in its own syntax.
+    Syntax {
+        /// The byte offset in the source at which the error was found.
+        position: usize,
+        /// A description of what was expected.
+        message: String,
+    },
+    /// The source string uses a construct that has no equivalent in the
+    /// target syntax.
+    Unsupported {
+        /// The byte offset in the source of the construct.
+        position: usize,
+        /// The construct that could not be converted.
+        construct: String,
+    },
+}
+
+impl ConvertError {
+    fn syntax(position: usize, message: &str) -> Self {
+        ConvertError::Syntax {
+            position,
+            message: message.to_string(),
+        }
+    }
+
+    fn unsupported(position: usize, construct: &str) -> Self {
+        ConvertError::Unsupported {
+            position,
+            construct: construct.to_string(),
+        }
+    }
+
+    /// Adjust the position of this error by `offset`, for errors found
+    /// while converting part of a larger string.
+    fn offset(self, offset: usize) -> Self {
+        match self {
+            ConvertError::Syntax { position, message } => ConvertError::Syntax {
+                position: position + offset,
+                message,
+            },
+            ConvertError::Unsupported {
+                position,
+                construct,
+            } => ConvertError::Unsupported {
+                position: position + offset,
+                construct,
+            },
+        }
+    }
+}
+
+impl Error for ConvertError {}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConvertError::Syntax {
+                position,
+                ref message,
+            } => write!(f, "{} at offset {}", message, position),
+            ConvertError::Unsupported {
+                position,
+                ref construct,
+            } => write!(
+                f,
+                "`{}` at offset {} cannot be converted",
+                construct, position
+            ),
+        }
+    }
+}
+
+/// The result of a conversion whose output may not behave exactly like
+/// its source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conversion {
+    /// The converted ICU pattern.
+    pub pattern: String,
+    /// Places where the converted pattern differs in behavior from the
+    /// source.
+    pub warnings: Vec<Warning>,
+}
+
+/// A description of how a converted pattern differs from its source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    /// The byte offset in the source of the construct that was converted.
+    pub position: usize,
+    /// A description of the difference.
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.position)
+    }
+}
+
+/// Append `text` to `output`, quoting any characters that are
+/// special in ICU message text.
+fn push_literal(output: &mut String, text: &str) {
+    push_quoted(output, text, "{}");
+}
+
+/// Append `text` to `output` for use within a `plural` branch, where
+/// `#` is also special.
+fn push_plural_literal(output: &mut String, text: &str) {
+    push_quoted(output, text, "{}#");
+}
+
+/// Append `text` to `output`, quoting runs of `special` characters and
+/// doubling apostrophes.
+fn push_quoted(output: &mut String, text: &str, special: &str) {
+    let mut quoted = false;
+    for c in text.chars() {
+        if c == '\'' {
+            output.push_str("''");
+            continue;
+        }
+        if special.contains(c) != quoted {
+            output.push('\'');
+            quoted = !quoted;
+        }
+        output.push(c);
+    }
+    if quoted {
+        output.push('\'');
+    }
+}
+
+/// A top-level piece of an ICU pattern, used when converting an ICU
+/// pattern into another syntax.
+#[derive(Debug, PartialEq)]
+enum Piece<'a> {
+    /// Literal text, with any ICU quoting removed.
+    Text(String),
+    /// An argument such as `{name}` or `{count, number, integer}`.
+    Argument {
+        /// The byte offset of the opening brace.
+        position: usize,
+        name: &'a str,
+        kind: Option<&'a str>,
+        /// The style, or for `plural` and `select`, the branches.
+        style: Option<&'a str>,
+    },
+}
+
+/// Split an ICU pattern into literal text and top-level arguments.
+fn parse_icu(pattern: &str) -> Result<Vec<Piece<'_>>, ConvertError> {
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let mut chars = pattern.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        match c {
+            '\'' => match chars.peek().map(|&(_, c)| c) {
+                Some('\'') => {
+                    chars.next();
+                    text.push('\'');
+                }
+                Some('{') | Some('}') => {
+                    while let Some((_, c)) = chars.next() {
+                        if c != '\'' {
+                            text.push(c);
+                        } else if chars.peek().map(|&(_, c)| c) == Some('\'') {
+                            chars.next();
+                            text.push('\'');
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                _ => text.push('\''),
+            },
+            '{' => {
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(text.clone()));
+                    text.clear();
+                }
+                let end = find_argument_end(pattern, &mut chars)?;
+                pieces.push(split_argument(pattern, position, end)?);
+            }
+            '}' => return Err(ConvertError::syntax(position, "Unmatched '}'")),
+            _ => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    Ok(pieces)
+}
+
+/// Find the offset of the brace which closes an argument, skipping
+/// over any nested messages and quoted text.
+fn find_argument_end(
+    pattern: &str,
+    chars: &mut Peekable<CharIndices>,
+) -> Result<usize, ConvertError> {
+    let mut depth = 1;
+    let mut quoted = false;
+    while let Some((position, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, c)| c);
+        match c {
+            '\'' if next == Some('\'') => {
+                chars.next();
+            }
+            '\'' if quoted => quoted = false,
+            '\'' => quoted = next.is_some_and(|c| "{}#|".contains(c)),
+            _ if quoted => {}
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(position);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(ConvertError::syntax(pattern.len(), "Expected '}'"))
+}
+
+/// Split the argument between the braces at `start` and `end` into its
+/// name, kind and style.
+fn split_argument(pattern: &str, start: usize, end: usize) -> Result<Piece<'_>, ConvertError> {
+    let mut parts = pattern[start + 1..end].splitn(3, ',').map(str::trim);
+    let name = parts.next().unwrap_or("");
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(ConvertError::syntax(start, "Expected an argument name"));
+    }
+    Ok(Piece::Argument {
+        position: start,
+        name,
+        kind: parts.next(),
+        style: parts.next().filter(|style| !style.is_empty()),
+    })
+}
+
+/// The shape of an ICU number style, used by converters which read or
+/// emit numeric format specifiers of their own.
+#[derive(Debug, PartialEq)]
+struct NumberStyle {
+    grouping: bool,
+    /// The exact number of fraction digits, if the style fixes it.
+    fraction_digits: Option<usize>,
+    /// Whether the number is a ratio written as a percentage.
+    percent: bool,
+}
+
+impl NumberStyle {
+    /// The ICU style of a `number` argument in this shape, which is
+    /// empty if the argument needs no style.
+    fn to_icu(&self) -> String {
+        match (self.grouping, self.fraction_digits, self.percent) {
+            (true, None, false) => String::new(),
+            (true, Some(0), false) => "integer".to_string(),
+            (true, Some(0), true) => "percent".to_string(),
+            (grouping, fraction_digits, percent) => {
+                let mut tokens = vec![];
+                if percent {
+                    tokens.push("percent scale/100".to_string());
+                }
+                match fraction_digits {
+                    Some(0) => tokens.push("precision-integer".to_string()),
+                    Some(digits) => tokens.push(format!(".{}", "0".repeat(digits))),
+                    None => {}
+                }
+                if !grouping {
+                    tokens.push("group-off".to_string());
+                }
+                format!("::{}", tokens.join(" "))
+            }
+        }
+    }
+}
+
+/// Recognize the styles of `{n, number, ...}` which have a simple
+/// equivalent in other syntaxes.
+///
+/// Besides the skeletons which `icu::parse` reads, decimal patterns
+/// such as `#,##0.00` from other ICU implementations are understood.
+fn number_style(style: Option<&str>) -> Option<NumberStyle> {
+    let (grouping, fraction_digits, percent) = match style {
+        None => (true, None, false),
+        Some("integer") => (true, Some(0), false),
+        Some("percent") => (true, Some(0), true),
+        Some(style) if style.starts_with("::") => {
+            let options = NumberOptions::from_skeleton(&style[2..]).ok()?;
+            let scale = if options.percent { 100 } else { 1 };
+            if options.currency.is_some() || options.scale != scale {
+                return None;
+            }
+            // The other syntaxes fix the number of digits, if any.
+            let fraction_digits = match options.maximum_fraction_digits {
+                None if options.minimum_fraction_digits == 0 => None,
+                Some(digits) if digits == options.minimum_fraction_digits => Some(digits as usize),
+                _ => return None,
+            };
+            (options.grouping, fraction_digits, options.percent)
+        }
+        Some(pattern) => {
+            let (pattern, percent) = match pattern.strip_suffix('%') {
+                Some(pattern) => (pattern, true),
+                None => (pattern, false),
+            };
+            let (pattern, grouping) = match pattern.strip_prefix("#,##") {
+                Some(pattern) => (pattern, true),
+                None => (pattern, false),
+            };
+            let digits = match pattern.strip_prefix("0") {
+                Some("") => 0,
+                Some(fraction) => match fraction.strip_prefix('.') {
+                    Some(zeros) if !zeros.is_empty() && zeros.chars().all(|c| c == '0') => {
+                        zeros.len()
+                    }
+                    _ => return None,
+                },
+                None => return None,
+            };
+            (grouping, Some(digits), percent)
+        }
+    };
+    Some(NumberStyle {
+        grouping,
+        fraction_digits,
+        percent,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {icu, Context};
+
+    #[test]
+    fn icu_patterns_are_split() {
+        assert_eq!(
+            parse_icu("It's '{'{name}'}' {n, number, integer}").unwrap(),
+            vec![
+                Piece::Text("It's {".to_string()),
+                Piece::Argument {
+                    position: 8,
+                    name: "name",
+                    kind: None,
+                    style: None,
+                },
+                Piece::Text("} ".to_string()),
+                Piece::Argument {
+                    position: 18,
+                    name: "n",
+                    kind: Some("number"),
+                    style: Some("integer"),
+                },
+            ]
+        );
+        match parse_icu("{n, plural, one {# '{'item} other {# items}}").unwrap()[0] {
+            Piece::Argument { kind, style, .. } => {
+                assert_eq!(kind, Some("plural"));
+                assert_eq!(style, Some("one {# '{'item} other {# items}"));
+            }
+            _ => panic!("Expected an argument"),
+        }
+        assert!(parse_icu("{name").is_err());
+        assert!(parse_icu("{}").is_err());
+    }
+
+    #[test]
+    fn number_styles_are_parsed() {
+        for &(grouping, fraction_digits, percent) in &[
+            (true, None, false),
+            (true, Some(0), false),
+            (false, Some(0), false),
+            (true, Some(2), false),
+            (false, Some(3), false),
+            (true, Some(0), true),
+            (true, None, true),
+            (false, Some(1), true),
+        ] {
+            let style = NumberStyle {
+                grouping,
+                fraction_digits,
+                percent,
+            };
+            let icu = style.to_icu();
+            let read = if icu.is_empty() {
+                None
+            } else {
+                assert!(
+                    icu::parse(&format!("{{n, number, {}}}", icu)).is_ok(),
+                    "{}",
+                    icu
+                );
+                Some(&icu[..])
+            };
+            assert_eq!(number_style(read), Some(style), "{}", icu);
+        }
+        assert_eq!(number_style(Some("::.0#")), None);
+    }
+
+    #[test]
+    fn literals_are_quoted() {
+        let mut output = String::new();
+        push_literal(&mut output, "It's {not} an argument");
+        assert_eq!(output, "It''s '{'not'}' an argument");
+        let message = icu::parse(&output).unwrap();
+        assert_eq!(
+            Context::default().format(&message, None),
+            "It's {not} an argument"
+        );
+
+        let mut output = String::new();
+        push_plural_literal(&mut output, "{}'# {'}");
+        assert_eq!(output, "'{}''#' '{''}'");
+        assert_eq!(
+            parse_icu(&output).unwrap(),
+            vec![Piece::Text("{}'# {'}".to_string())]
+        );
+    }
+}
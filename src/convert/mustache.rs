@@ -0,0 +1,154 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Mustache Templates
+//!
+//! This converts the simple variable subset of Mustache and Handlebars
+//! templates into a [`Message`]:
+//!
+//! * `{{name}}`, `{{{name}}}` and `{{& name}}` become a variable.
+//!   Dotted names such as `{{user.name}}` are kept as they are.
+//! * Comments such as `{{! note}}` are dropped.
+//!
+//! Sections, inverted sections, partials, delimiter changes and
+//! Handlebars helpers have no equivalent in a `Message` and are
+//! reported as errors.
+//!
+//! Mustache HTML escapes the value of `{{name}}`, while a `Message`
+//! writes values as they are. The names of the variables which would
+//! have been escaped are returned so that the caller can escape those
+//! values before formatting.
+//!
+//! Unlike the other converters, this produces a parsed `Message` rather
+//! than the text of an ICU pattern, as templates are usually rendered
+//! directly rather than written back into a catalog.
+//!
+//! [`Message`]: ../../struct.Message.html
+
+use super::ConvertError;
+use icu::ast::{PlainText, SimpleFormat};
+use {Message, MessagePart};
+
+/// A template converted into a [`Message`].
+///
+/// [`Message`]: ../../struct.Message.html
+#[derive(Debug)]
+pub struct Template {
+    /// The converted message.
+    pub message: Message,
+    /// The names of the variables written with `{{name}}`, which
+    /// Mustache would have HTML escaped, in order of first use.
+    pub escaped: Vec<String>,
+}
+
+/// Convert a Mustache template into a [`Message`].
+///
+/// ```
+/// # #[macro_use] extern crate message_format;
+/// # fn main() {
+/// use message_format::convert::mustache;
+/// use message_format::Context;
+///
+/// let template = mustache::parse("Hi {{name}}, see {{{link}}}").unwrap();
+/// assert_eq!(template.escaped, vec!["name".to_string()]);
+///
+/// let ctx = Context::default();
+/// assert_eq!(format_message!(ctx, &template.message, name => "Ann", link => "<a>"),
+///            "Hi Ann, see <a>");
+/// # }
+/// ```
+///
+/// [`Message`]: ../../struct.Message.html
+pub fn parse(source: &str) -> Result<Template, ConvertError> {
+    let mut parts: Vec<Box<dyn MessagePart>> = vec![];
+    let mut escaped = vec![];
+    let mut rest = source;
+
+    while let Some(open) = rest.find("{{") {
+        if open > 0 {
+            parts.push(Box::new(PlainText::new(&rest[..open])));
+        }
+        let position = source.len() - rest.len() + open;
+        let tag = &rest[open..];
+        let (close, triple) = if tag.starts_with("{{{") {
+            (tag.find("}}}").map(|end| end + 3), true)
+        } else {
+            (tag.find("}}").map(|end| end + 2), false)
+        };
+        let close = match close {
+            Some(close) => close,
+            None => return Err(ConvertError::syntax(source.len(), "Expected '}}'")),
+        };
+        let contents = if triple {
+            &tag[3..close - 3]
+        } else {
+            &tag[2..close - 2]
+        };
+        rest = &tag[close..];
+
+        let (name, is_escaped) = match contents.trim_start().chars().next() {
+            _ if triple => (contents, false),
+            Some('!') => continue,
+            Some('&') => (&contents.trim_start()[1..], false),
+            Some('#') | Some('^') | Some('/') | Some('>') | Some('=') | Some('<') | Some('$') => {
+                return Err(ConvertError::unsupported(position, &tag[..close]))
+            }
+            _ => (contents, true),
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ConvertError::syntax(position, "Expected a variable name"));
+        }
+        if name.contains(char::is_whitespace) || name == "else" {
+            return Err(ConvertError::unsupported(position, &tag[..close]));
+        }
+        if is_escaped && !escaped.iter().any(|escaped| escaped == name) {
+            escaped.push(name.to_string());
+        }
+        parts.push(Box::new(SimpleFormat::new(name)));
+    }
+    if !rest.is_empty() {
+        parts.push(Box::new(PlainText::new(rest)));
+    }
+
+    Ok(Template {
+        message: Message::new(parts),
+        escaped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use Context;
+
+    #[test]
+    fn variables_work() {
+        let ctx = Context::default();
+
+        let template = parse("{{ greeting }}, {{{user}}}! {{&html}}{{! dropped }}.").unwrap();
+        assert_eq!(template.escaped, vec!["greeting".to_string()]);
+        assert_eq!(
+            format_message!(ctx, &template.message, greeting => "Hello", user => "Ann", html => "<b>"),
+            "Hello, Ann! <b>."
+        );
+
+        let template = parse("{{a}} and {{a}}").unwrap();
+        assert_eq!(template.escaped, vec!["a".to_string()]);
+        assert_eq!(format_message!(ctx, &template.message, a => 1), "1 and 1");
+    }
+
+    #[test]
+    fn unsupported_tags_fail() {
+        assert!(parse("{{#items}}{{name}}{{/items}}").is_err());
+        assert!(parse("{{^items}}none{{/items}}").is_err());
+        assert!(parse("{{> footer}}").is_err());
+        assert!(parse("{{=<% %>=}}").is_err());
+        assert!(parse("{{format date}}").is_err());
+        assert!(parse("{{name").is_err());
+        assert!(parse("{{}}").is_err());
+    }
+}
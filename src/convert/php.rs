@@ -0,0 +1,443 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Symfony and Laravel Translations
+//!
+//! This converts the translation strings used by Symfony, Laravel and
+//! other PHP frameworks into ICU patterns.
+//!
+//! Placeholders written as `%name%` (Symfony) or `:name` (Laravel)
+//! become `{name}`. Laravel's `:Name` and `:NAME` variants, which change
+//! the case of the value, are kept as separately named arguments.
+//!
+//! A string containing `|` is a pipe pluralization such as
+//! `{0} none|{1} one|[2,*] many`, which becomes a `plural` argument for
+//! the `count` variable:
+//!
+//! * A set such as `{0}` or `{1,2}` becomes one `=n` exact match per
+//!   value.
+//! * An interval such as `[2,5]` or `]1,5]` which covers a few integers
+//!   becomes one `=n` exact match per integer.
+//! * An interval without an upper bound, such as `[2,*]` or `]1,Inf[`,
+//!   becomes the `other` branch.
+//! * Segments without an interval are chosen by the plural rules of the
+//!   language. One such segment becomes `other` and two become `one`
+//!   and `other`, which matches English and most other languages with
+//!   two plural forms.
+//!
+//! Within the branches, the `count` placeholder becomes `#`. A `||`
+//! is a literal `|`. Anything that cannot be mapped exactly is reported
+//! as a [`Warning`].
+//!
+//! [`Warning`]: ../struct.Warning.html
+
+use super::{push_literal, push_plural_literal, Conversion, ConvertError, Warning};
+
+/// The variable which selects the branch of a pipe pluralization.
+const COUNT: &str = "count";
+
+/// The largest number of integers in an interval that is expanded into
+/// one exact match per integer.
+const MAX_EXPANDED_KEYS: f64 = 10.0;
+
+/// Convert a Symfony or Laravel translation string into an ICU pattern.
+///
+/// ```
+/// use message_format::convert::php;
+/// use message_format::{arg, icu, Context};
+///
+/// let conversion = php::to_icu("Hello %name%!").unwrap();
+/// assert_eq!(conversion.pattern, "Hello {name}!");
+///
+/// let conversion = php::to_icu("{0} No apples|{1} One apple|[2,*] :count apples").unwrap();
+/// assert_eq!(conversion.pattern,
+///            "{count, plural, =0 {No apples} =1 {One apple} other {# apples}}");
+/// let message = icu::parse(&conversion.pattern).unwrap();
+/// assert_eq!(Context::default().format(&message, Some(&arg("count", 3))), "3 apples");
+/// ```
+pub fn to_icu(source: &str) -> Result<Conversion, ConvertError> {
+    let segments = split_segments(source);
+    let mut warnings = vec![];
+    let mut pattern = String::new();
+    if segments.len() == 1 {
+        push_text(&mut pattern, &segments[0].1, false);
+    } else {
+        pattern = convert_plural(&segments, &mut warnings)?;
+    }
+    Ok(Conversion { pattern, warnings })
+}
+
+/// Split a string on `|`, returning the byte offset and the unescaped
+/// text of each segment.
+fn split_segments(source: &str) -> Vec<(usize, String)> {
+    let mut segments = vec![];
+    let mut start = 0;
+    let mut text = String::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        if c != '|' {
+            text.push(c);
+        } else if chars.peek().map(|&(_, c)| c) == Some('|') {
+            chars.next();
+            text.push('|');
+        } else {
+            segments.push((start, text.clone()));
+            text.clear();
+            start = position + 1;
+        }
+    }
+    segments.push((start, text));
+    segments
+}
+
+/// Produce the `plural` argument for the segments of a pipe
+/// pluralization.
+fn convert_plural(
+    segments: &[(usize, String)],
+    warnings: &mut Vec<Warning>,
+) -> Result<String, ConvertError> {
+    let mut exact: Vec<(i64, &str)> = vec![];
+    let mut other = None;
+    let mut standard = vec![];
+
+    for &(offset, ref segment) in segments {
+        let leading = segment.len() - segment.trim_start().len();
+        let position = offset + leading;
+        let (values, text) = match parse_interval(segment.trim(), position)? {
+            Some(interval) => interval,
+            None => {
+                standard.push((position, segment.trim()));
+                continue;
+            }
+        };
+        match values {
+            Interval::Values(values) => {
+                for value in values {
+                    if !exact.iter().any(|&(key, _)| key == value) {
+                        exact.push((value, text));
+                    }
+                }
+            }
+            Interval::Range(low, high) if high.is_infinite() => {
+                if other.is_some() {
+                    warnings.push(Warning {
+                        position,
+                        message: "This interval can not be selected".to_string(),
+                    });
+                    continue;
+                }
+                if low.is_finite() && (0..low as i64).any(|n| exact.iter().all(|&(k, _)| k != n)) {
+                    warnings.push(Warning {
+                        position,
+                        message: format!(
+                            "Values below {} which are not matched exactly will \
+                             also use this branch",
+                            low
+                        ),
+                    });
+                }
+                other = Some(text);
+            }
+            Interval::Range(low, high) => {
+                let low = if low.is_finite() {
+                    low
+                } else {
+                    warnings.push(Warning {
+                        position,
+                        message: "Negative values will use the `other` branch".to_string(),
+                    });
+                    0.0
+                };
+                let count = high - low + 1.0;
+                let mut values = vec![];
+                if count < 1.0 {
+                    warnings.push(Warning {
+                        position,
+                        message: "This interval can not be selected by an integer value"
+                            .to_string(),
+                    });
+                } else if count <= MAX_EXPANDED_KEYS {
+                    values.extend(low as i64..=high as i64);
+                } else {
+                    values.push(low as i64);
+                    warnings.push(Warning {
+                        position,
+                        message: format!(
+                            "Only {} is matched exactly, other values in this interval \
+                             will use the `other` branch",
+                            low
+                        ),
+                    });
+                }
+                for value in values {
+                    if !exact.iter().any(|&(key, _)| key == value) {
+                        exact.push((value, text));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut one = None;
+    match standard.len() {
+        0 => {}
+        1 if other.is_none() => other = Some(standard[0].1),
+        2 if other.is_none() => {
+            one = Some(standard[0].1);
+            other = Some(standard[1].1);
+        }
+        1 | 2 => warnings.push(Warning {
+            position: standard[0].0,
+            message: "Segments without an interval can not be selected after an \
+                      interval without an upper bound"
+                .to_string(),
+        }),
+        _ => {
+            return Err(ConvertError::unsupported(
+                standard[2].0,
+                "More than two segments without an interval",
+            ))
+        }
+    }
+    let other = match other {
+        Some(other) => other,
+        None => {
+            let &(position, ref segment) =
+                segments.last().expect("a pipe pluralization has segments");
+            warnings.push(Warning {
+                position,
+                message: "Values outside of the intervals will use this segment".to_string(),
+            });
+            match parse_interval(segment.trim(), position)? {
+                Some((_, text)) => text,
+                None => segment.trim(),
+            }
+        }
+    };
+
+    let mut output = format!("{{{}, plural,", COUNT);
+    let branches = exact
+        .iter()
+        .map(|&(key, text)| (format!("={}", key), text))
+        .chain(one.map(|text| ("one".to_string(), text)))
+        .chain(Some(("other".to_string(), other)));
+    for (key, text) in branches {
+        output.push_str(&format!(" {} {{", key));
+        push_text(&mut output, text, true);
+        output.push('}');
+    }
+    output.push('}');
+    Ok(output)
+}
+
+/// The values selected by an interval at the start of a segment.
+enum Interval {
+    /// A set of values such as `{0,1}`.
+    Values(Vec<i64>),
+    /// The smallest and largest integers within an interval such as
+    /// `]1,Inf[`. Either may be infinite.
+    Range(f64, f64),
+}
+
+/// Split the interval found at the start of a segment from its text.
+fn parse_interval(
+    segment: &str,
+    position: usize,
+) -> Result<Option<(Interval, &str)>, ConvertError> {
+    if segment.starts_with('{') {
+        let end = match segment.find('}') {
+            Some(end) => end,
+            None => return Ok(None),
+        };
+        let values: Result<Vec<i64>, _> = segment[1..end]
+            .split(',')
+            .map(|value| value.trim().parse())
+            .collect();
+        return Ok(values
+            .ok()
+            .map(|values| (Interval::Values(values), segment[end + 1..].trim_start())));
+    }
+    if !segment.starts_with('[') && !segment.starts_with(']') {
+        return Ok(None);
+    }
+    let end = match segment[1..].find([']', '[']) {
+        Some(end) => end + 1,
+        None => return Err(ConvertError::syntax(position, "Expected ']' or '['")),
+    };
+    let mut bounds = segment[1..end].split(',').map(str::trim);
+    let (low, high) = match (bounds.next(), bounds.next(), bounds.next()) {
+        (Some(low), Some(high), None) => (parse_bound(low), parse_bound(high)),
+        _ => return Err(ConvertError::syntax(position, "Expected two bounds")),
+    };
+    let (low, high) = match (low, high) {
+        (Some(low), Some(high)) => (low, high),
+        _ => return Err(ConvertError::syntax(position, "Expected a number")),
+    };
+    let low = if segment.starts_with('[') {
+        low.ceil()
+    } else {
+        low.floor() + 1.0
+    };
+    let high = if segment[end..].starts_with(']') {
+        high.floor()
+    } else {
+        high.ceil() - 1.0
+    };
+    Ok(Some((
+        Interval::Range(low, high),
+        segment[end + 1..].trim_start(),
+    )))
+}
+
+/// Parse one bound of an interval.
+fn parse_bound(bound: &str) -> Option<f64> {
+    match bound {
+        "*" | "Inf" | "+Inf" => Some(f64::INFINITY),
+        "-Inf" => Some(f64::NEG_INFINITY),
+        bound => bound.parse().ok(),
+    }
+}
+
+/// Append `text` to `output`, converting its placeholders into
+/// arguments. Within a `plural` branch, the count becomes `#`.
+fn push_text(output: &mut String, text: &str, in_plural: bool) {
+    let push: fn(&mut String, &str) = if in_plural {
+        push_plural_literal
+    } else {
+        push_literal
+    };
+    let mut literal_start = 0;
+    let mut position = 0;
+    while position < text.len() {
+        let rest = &text[position..];
+        let placeholder = match rest.chars().next() {
+            Some('%') => placeholder_name(&rest[1..])
+                .filter(|name| rest[1 + name.len()..].starts_with('%'))
+                .map(|name| (name, name.len() + 2)),
+            Some(':') => placeholder_name(&rest[1..])
+                .filter(|name| name.starts_with(|c: char| c.is_ascii_alphabetic()))
+                .map(|name| (name, name.len() + 1)),
+            _ => None,
+        };
+        match placeholder {
+            Some((name, length)) => {
+                push(output, &text[literal_start..position]);
+                if in_plural && name == COUNT {
+                    output.push('#');
+                } else {
+                    output.push_str(&format!("{{{}}}", name));
+                }
+                position += length;
+                literal_start = position;
+            }
+            None => position += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    push(output, &text[literal_start..]);
+}
+
+/// The placeholder name at the start of `text`, if any.
+fn placeholder_name(text: &str) -> Option<&str> {
+    let end = text
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '.')
+        .unwrap_or(text.len());
+    let name = text[..end].trim_end_matches('.');
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_icu;
+    use {arg, icu, Context};
+
+    #[test]
+    fn placeholders_work() {
+        let conversion = to_icu("Hello %name%, :user.name is here at 10:30: 100% {sure}").unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "Hello {name}, {user.name} is here at 10:30: 100% '{'sure'}'"
+        );
+        assert_eq!(
+            to_icu("Sent to :email.").unwrap().pattern,
+            "Sent to {email}."
+        );
+    }
+
+    #[test]
+    fn intervals_work() {
+        let conversion = to_icu("{0} none|{1} one %count%|[2,*] %count% many").unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "{count, plural, =0 {none} =1 {one #} other {# many}}"
+        );
+        assert!(conversion.warnings.is_empty());
+
+        let conversion = to_icu("{0} none|[1,3] a few|]3,Inf[ lots of %what%").unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "{count, plural, =0 {none} =1 {a few} =2 {a few} =3 {a few} other {lots of {what}}}"
+        );
+        assert!(conversion.warnings.is_empty());
+
+        let conversion = to_icu("[0,1] a #1 pick || more|[5,Inf] many").unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "{count, plural, =0 {a '#'1 pick | more} =1 {a '#'1 pick | more} other {many}}"
+        );
+        assert_eq!(conversion.warnings.len(), 1);
+    }
+
+    #[test]
+    fn standard_segments_work() {
+        let conversion = to_icu("one apple|:count apples").unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "{count, plural, one {one apple} other {# apples}}"
+        );
+
+        let conversion = to_icu("{0} none|one apple|:count apples").unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "{count, plural, =0 {none} one {one apple} other {# apples}}"
+        );
+
+        let conversion = to_icu("{0} none|[1,2] few").unwrap();
+        assert_eq!(
+            conversion.pattern,
+            "{count, plural, =0 {none} =1 {few} =2 {few} other {few}}"
+        );
+        assert_eq!(conversion.warnings.len(), 1);
+    }
+
+    #[test]
+    fn output_is_formatted() {
+        let ctx = Context::default();
+        let conversion = to_icu("{0} No %what%|[1,2] A #few|]2,Inf[ :count {%what%}").unwrap();
+        let message = icu::parse(&conversion.pattern).unwrap();
+        for &(count, expected) in &[(0, "No pears"), (2, "A #few"), (7, "7 {pears}")] {
+            let args = arg("count", count);
+            assert_eq!(
+                ctx.format(&message, Some(&args.arg("what", "pears"))),
+                expected
+            );
+        }
+        let message = icu::parse(&to_icu("one apple|:count apples").unwrap().pattern).unwrap();
+        assert_eq!(ctx.format(&message, Some(&arg("count", 1))), "one apple");
+        assert_eq!(ctx.format(&message, Some(&arg("count", 3))), "3 apples");
+    }
+
+    #[test]
+    fn invalid_pluralizations_fail() {
+        assert!(to_icu("a|b|c").is_err());
+        assert!(to_icu("[1 one|other").is_err());
+        assert!(to_icu("[1,2,3] one|other").is_err());
+        assert!(to_icu("[a,2] one|other").is_err());
+    }
+}
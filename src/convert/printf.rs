@@ -0,0 +1,249 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! printf Format Strings
+//!
+//! This converts between ICU patterns and the printf format strings used
+//! by C, PHP's `sprintf` and gettext's `c-format` messages.
+//!
+//! Arguments are numbered from `{0}`. Translators often reorder the
+//! arguments of a printf string with explicit positions such as `%2$s`,
+//! so these are kept: `%1$s` becomes `{0}` and `{0}` becomes `%1$s`. As
+//! in POSIX, a string must either number all of its conversions or none
+//! of them.
+//!
+//! A subset of conversions is mapped onto ICU number skeletons: integers
+//! (`d`, `i` and `u`), fixed point (`.2f`), general numbers (`g`) and
+//! the POSIX digit grouping flag (`'`). Length modifiers such as `l` are
+//! dropped. Scientific notation (`e`), other flags and field widths
+//! have no equivalent and are reported as errors, as are named ICU
+//! arguments and `plural`, `select` and date arguments when converting
+//! back into printf.
+
+use super::{number_style, parse_icu, push_literal, ConvertError, NumberStyle, Piece};
+
+/// Convert a printf format string into an ICU pattern.
+///
+/// ```
+/// use message_format::convert::printf;
+///
+/// assert_eq!(printf::to_icu("%2$s sent %1$d files").unwrap(),
+///            "{1} sent {0, number, ::precision-integer group-off} files");
+/// assert_eq!(printf::to_icu("%s of %s").unwrap(), "{0} of {1}");
+/// ```
+pub fn to_icu(source: &str) -> Result<String, ConvertError> {
+    let mut output = String::new();
+    let mut literal = String::new();
+    let mut explicit = None;
+    let mut next_index = 0;
+    let mut position = 0;
+
+    while let Some(offset) = source[position..].find('%') {
+        literal.push_str(&source[position..position + offset]);
+        let start = position + offset;
+        let rest = &source[start + 1..];
+        if rest.starts_with('%') {
+            literal.push('%');
+            position = start + 2;
+            continue;
+        }
+        push_literal(&mut output, &literal);
+        literal.clear();
+
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (index, spec_start) = if digits > 0 && rest[digits..].starts_with('$') {
+            let index = match rest[..digits].parse::<usize>() {
+                Ok(index) if index > 0 => index - 1,
+                _ => return Err(ConvertError::syntax(start, "Expected a position from 1")),
+            };
+            (Some(index), start + digits + 2)
+        } else {
+            (None, start + 1)
+        };
+        if *explicit.get_or_insert(index.is_some()) != index.is_some() {
+            return Err(ConvertError::syntax(
+                start,
+                "Cannot mix numbered and unnumbered conversions",
+            ));
+        }
+        let index = index.unwrap_or_else(|| {
+            next_index += 1;
+            next_index - 1
+        });
+
+        let spec = &source[spec_start..];
+        let end = match spec
+            .find(|c: char| (c.is_ascii_alphabetic() || c == '@') && !"hlLqjzt".contains(c))
+        {
+            Some(end) => end + 1,
+            None => return Err(ConvertError::syntax(source.len(), "Expected a conversion")),
+        };
+        let spec = &spec[..end];
+        position = spec_start + end;
+        match conversion_style(spec) {
+            Some(None) => output.push_str(&format!("{{{}}}", index)),
+            Some(Some(style)) => output.push_str(&format!("{{{}, {}}}", index, style)),
+            None => return Err(ConvertError::unsupported(start, &source[start..position])),
+        }
+    }
+    literal.push_str(&source[position..]);
+    push_literal(&mut output, &literal);
+    Ok(output)
+}
+
+/// Convert an ICU pattern into a printf format string with numbered
+/// conversions.
+///
+/// ```
+/// use message_format::convert::printf;
+///
+/// assert_eq!(printf::from_icu("{1} sent {0, number, integer} files").unwrap(),
+///            "%2$s sent %1$'d files");
+/// ```
+pub fn from_icu(pattern: &str) -> Result<String, ConvertError> {
+    let mut output = String::new();
+    for piece in parse_icu(pattern)? {
+        match piece {
+            Piece::Text(ref text) => output.push_str(&text.replace('%', "%%")),
+            Piece::Argument {
+                position,
+                name,
+                kind,
+                style,
+            } => {
+                let index = match name.parse::<usize>() {
+                    Ok(index) => index + 1,
+                    Err(_) => return Err(ConvertError::unsupported(position, name)),
+                };
+                let construct = style.or(kind).unwrap_or(name);
+                let conversion = match kind {
+                    None => Some("s".to_string()),
+                    Some("number") => number_conversion(style),
+                    Some(_) => None,
+                };
+                match conversion {
+                    Some(conversion) => output.push_str(&format!("%{}${}", index, conversion)),
+                    None => return Err(ConvertError::unsupported(position, construct)),
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Map a printf conversion specification, without its position, onto
+/// the type and style of an ICU argument. `Some(None)` means a plain
+/// argument.
+fn conversion_style(spec: &str) -> Option<Option<String>> {
+    let (grouping, spec) = match spec.strip_prefix('\'') {
+        Some(spec) => (true, spec),
+        None => (false, spec),
+    };
+    let (precision, spec) = match spec.strip_prefix('.') {
+        Some(spec) => {
+            let digits = spec.len() - spec.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            (Some(spec[..digits].parse().unwrap_or(0)), &spec[digits..])
+        }
+        None => (None, spec),
+    };
+    let conversion = spec.trim_start_matches(|c: char| "hlLqjzt".contains(c));
+    if conversion.len() != spec.len() && !"diufFeEgG".contains(conversion) {
+        return None;
+    }
+    let fraction_digits = match (conversion, precision) {
+        ("s", None) | ("@", None) if !grouping => return Some(None),
+        ("d", None) | ("i", None) | ("u", None) => Some(0),
+        ("f", _) | ("F", _) => Some(precision.unwrap_or(6)),
+        ("g", None) | ("G", None) => return Some(Some("number".to_string())),
+        _ => return None,
+    };
+    let style = NumberStyle {
+        grouping,
+        fraction_digits,
+        percent: false,
+    };
+    Some(Some(format!("number, {}", style.to_icu())))
+}
+
+/// The printf conversion for an ICU number style.
+fn number_conversion(style: Option<&str>) -> Option<String> {
+    let style = number_style(style)?;
+    if style.percent {
+        return None;
+    }
+    let grouping = if style.grouping { "'" } else { "" };
+    let conversion = match style.fraction_digits {
+        None => "g".to_string(),
+        Some(0) => format!("{}d", grouping),
+        Some(digits) => format!("{}.{}f", grouping, digits),
+    };
+    Some(conversion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {arg, icu, Context};
+
+    #[test]
+    fn to_icu_works() {
+        assert_eq!(to_icu("%1$s and %1$s").unwrap(), "{0} and {0}");
+        assert_eq!(
+            to_icu("%d%% of %s").unwrap(),
+            "{0, number, ::precision-integer group-off}% of {1}"
+        );
+        assert_eq!(
+            to_icu("%ld {%s}").unwrap(),
+            "{0, number, ::precision-integer group-off} '{'{1}'}'"
+        );
+        assert_eq!(to_icu("%2$.2f").unwrap(), "{1, number, ::.00 group-off}");
+        assert_eq!(to_icu("%'.1f").unwrap(), "{0, number, ::.0}");
+        assert_eq!(to_icu("%'d").unwrap(), "{0, number, integer}");
+        assert!(to_icu("%e").is_err());
+        assert!(to_icu("%1$s and %s").is_err());
+        assert!(to_icu("%0$s").is_err());
+        assert!(to_icu("%5s").is_err());
+        assert!(to_icu("%-d").is_err());
+        assert!(to_icu("%").is_err());
+    }
+
+    #[test]
+    fn from_icu_works() {
+        assert_eq!(from_icu("{0}% of {1}").unwrap(), "%1$s%% of %2$s");
+        assert_eq!(
+            from_icu("{1, number, #,##0.00} {0, number}").unwrap(),
+            "%2$'.2f %1$g"
+        );
+        assert!(from_icu("{name}").is_err());
+        assert!(from_icu("{0, number, percent}").is_err());
+        assert!(from_icu("{0, plural, other {#}}").is_err());
+    }
+
+    #[test]
+    fn output_is_formatted() {
+        let ctx = Context::default();
+        for &(source, expected) in &[
+            ("%2$d of %1$'.2f", "3 of 1,234.50"),
+            ("%1$f, %2$g and {%2$s}", "1234.500000, 3 and {3}"),
+            ("%1$'d%% isn't %2$ld", "1,234% isn't 3"),
+        ] {
+            let pattern = to_icu(source).unwrap();
+            let message = icu::parse(&pattern).unwrap();
+            let first = arg("0", 1234.5);
+            let args = first.arg("1", 3);
+            assert_eq!(ctx.format(&message, Some(&args)), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn reordering_round_trips() {
+        for source in &["%2$s sent %1$d files to %3$s", "%1$.1f%% of %2$'d"] {
+            let pattern = to_icu(source).unwrap();
+            assert_eq!(&from_icu(&pattern).unwrap(), source);
+        }
+        assert_eq!(from_icu(&to_icu("%s, %d").unwrap()).unwrap(), "%1$s, %2$d");
+    }
+}
@@ -0,0 +1,457 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Python Format Strings
+//!
+//! This converts between ICU patterns and both of the format string
+//! syntaxes used by Python:
+//!
+//! * [`Style::Format`]: `str.format` strings such as `"{name} has {count:,d} items"`.
+//! * [`Style::Percent`]: `%`-format strings such as `"%(name)s has %(count)d items"`.
+//!
+//! Named fields keep their names. Automatically numbered fields (`{}`
+//! and positional `%s`) become numbered ICU arguments starting at `{0}`.
+//!
+//! A subset of format specifications is mapped onto ICU number styles:
+//! integers (`d`), fixed point (`.2f`), percentages (`%`) and digit
+//! grouping (`,`). Padding, alignment, signs, scientific notation and
+//! the `!r` conversion have no ICU equivalent and are reported as
+//! errors, as are ICU `plural`, `select` and date arguments when
+//! converting back into Python.
+//!
+//! [`Style::Format`]: enum.Style.html#variant.Format
+//! [`Style::Percent`]: enum.Style.html#variant.Percent
+
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use super::{number_style, parse_icu, push_literal, ConvertError, NumberStyle, Piece};
+
+/// The Python format string syntax to convert from or to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+    /// `str.format` and f-string style replacement fields.
+    Format,
+    /// printf-like `%` formatting.
+    Percent,
+}
+
+/// Convert a Python format string into an ICU pattern.
+///
+/// ```
+/// use message_format::convert::python::{self, Style};
+///
+/// assert_eq!(python::to_icu("{user} has {count:,d} points", Style::Format).unwrap(),
+///            "{user} has {count, number, integer} points");
+/// assert_eq!(python::to_icu("%(user)s logged in", Style::Percent).unwrap(),
+///            "{user} logged in");
+/// ```
+pub fn to_icu(source: &str, style: Style) -> Result<String, ConvertError> {
+    match style {
+        Style::Format => format_to_icu(source),
+        Style::Percent => percent_to_icu(source),
+    }
+}
+
+/// Convert an ICU pattern into a Python format string.
+///
+/// Numbered ICU arguments can only be converted into positional
+/// `%`-format specifiers when they appear in order.
+///
+/// ```
+/// use message_format::convert::python::{self, Style};
+///
+/// assert_eq!(python::from_icu("{user} has {count, number, integer} points", Style::Format)
+///                .unwrap(),
+///            "{user} has {count:,d} points");
+/// assert_eq!(python::from_icu("{0} of {1}", Style::Percent).unwrap(),
+///            "%s of %s");
+/// ```
+pub fn from_icu(pattern: &str, style: Style) -> Result<String, ConvertError> {
+    let pieces = parse_icu(pattern)?;
+    let mut output = String::new();
+    let mut next_index = 0;
+
+    for piece in &pieces {
+        match *piece {
+            Piece::Text(ref text) => match style {
+                Style::Format => output.push_str(&text.replace('{', "{{").replace('}', "}}")),
+                Style::Percent => output.push_str(&text.replace('%', "%%")),
+            },
+            Piece::Argument {
+                position,
+                name,
+                kind,
+                style: argument_style,
+            } => {
+                let construct = argument_style.or(kind).unwrap_or(name);
+                let spec = match kind {
+                    None => String::new(),
+                    Some("number") => match number_spec(argument_style) {
+                        Some(spec) => spec,
+                        None => return Err(ConvertError::unsupported(position, construct)),
+                    },
+                    Some(_) => return Err(ConvertError::unsupported(position, construct)),
+                };
+                match style {
+                    Style::Format if spec.is_empty() => output.push_str(&format!("{{{}}}", name)),
+                    Style::Format => output.push_str(&format!("{{{}:{}}}", name, spec)),
+                    Style::Percent => {
+                        let conversion = match percent_conversion(&spec) {
+                            Some(conversion) => conversion,
+                            None => return Err(ConvertError::unsupported(position, construct)),
+                        };
+                        if name.chars().all(|c| c.is_ascii_digit()) {
+                            if name.parse() != Ok(next_index) {
+                                return Err(ConvertError::unsupported(position, name));
+                            }
+                            next_index += 1;
+                            output.push_str(&format!("%{}", conversion));
+                        } else {
+                            output.push_str(&format!("%({}){}", name, conversion));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Tracks how fields are numbered, as Python does not allow automatic
+/// numbering to be mixed with explicit names or numbers.
+#[derive(Default)]
+struct Numbering {
+    automatic: Option<bool>,
+    next_index: usize,
+}
+
+impl Numbering {
+    /// Return the ICU argument name for a field named `name`, where an
+    /// empty name requests the next automatic number.
+    fn name(&mut self, position: usize, name: &str) -> Result<String, ConvertError> {
+        let automatic = name.is_empty();
+        if *self.automatic.get_or_insert(automatic) != automatic {
+            return Err(ConvertError::syntax(
+                position,
+                "Cannot mix automatic and explicit field names",
+            ));
+        }
+        if automatic {
+            self.next_index += 1;
+            Ok((self.next_index - 1).to_string())
+        } else {
+            Ok(name.to_string())
+        }
+    }
+}
+
+fn format_to_icu(source: &str) -> Result<String, ConvertError> {
+    let mut output = String::new();
+    let mut literal = String::new();
+    let mut numbering = Numbering::default();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        match c {
+            '{' if next_is(&mut chars, '{') => literal.push('{'),
+            '}' if next_is(&mut chars, '}') => literal.push('}'),
+            '{' => {
+                push_literal(&mut output, &literal);
+                literal.clear();
+                let end = match source[position..].find('}') {
+                    Some(offset) => position + offset,
+                    None => return Err(ConvertError::syntax(source.len(), "Expected '}'")),
+                };
+                while chars.peek().is_some_and(|&(p, _)| p <= end) {
+                    chars.next();
+                }
+                let field = &source[position + 1..end];
+                if field.contains('{') {
+                    return Err(ConvertError::unsupported(position, field));
+                }
+                let (field, spec) = match field.find(':') {
+                    Some(colon) => (&field[..colon], &field[colon + 1..]),
+                    None => (field, ""),
+                };
+                let field = match field.find('!') {
+                    Some(bang) if &field[bang..] == "!s" => &field[..bang],
+                    Some(bang) => return Err(ConvertError::unsupported(position, &field[bang..])),
+                    None => field,
+                };
+                if !field.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    return Err(ConvertError::unsupported(position, field));
+                }
+                let name = numbering.name(position, field)?;
+                match format_style(spec) {
+                    Some(None) => output.push_str(&format!("{{{}}}", name)),
+                    Some(Some(style)) => output.push_str(&format!("{{{}, {}}}", name, style)),
+                    None => return Err(ConvertError::unsupported(position, spec)),
+                }
+            }
+            '}' => return Err(ConvertError::syntax(position, "Unmatched '}'")),
+            _ => literal.push(c),
+        }
+    }
+    push_literal(&mut output, &literal);
+    Ok(output)
+}
+
+fn percent_to_icu(source: &str) -> Result<String, ConvertError> {
+    let mut output = String::new();
+    let mut literal = String::new();
+    let mut numbering = Numbering::default();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        if c != '%' {
+            literal.push(c);
+            continue;
+        }
+        if next_is(&mut chars, '%') {
+            literal.push('%');
+            continue;
+        }
+        push_literal(&mut output, &literal);
+        literal.clear();
+
+        let mut name = "";
+        if next_is(&mut chars, '(') {
+            let start = chars.peek().map_or(source.len(), |&(p, _)| p);
+            loop {
+                match chars.next() {
+                    Some((end, ')')) => {
+                        name = &source[start..end];
+                        break;
+                    }
+                    Some(_) => {}
+                    None => return Err(ConvertError::syntax(source.len(), "Expected ')'")),
+                }
+            }
+            if name.is_empty() {
+                return Err(ConvertError::syntax(position, "Expected a mapping key"));
+            }
+        }
+        let spec_start = chars.peek().map_or(source.len(), |&(p, _)| p);
+        let mut spec_end = source.len();
+        for (p, c) in chars.by_ref() {
+            if c.is_ascii_alphabetic() {
+                spec_end = p + c.len_utf8();
+                break;
+            }
+        }
+        let spec = &source[spec_start..spec_end];
+        if spec.is_empty() {
+            return Err(ConvertError::syntax(position, "Expected a conversion type"));
+        }
+        let name = numbering.name(position, name)?;
+        match percent_style(spec) {
+            Some(None) => output.push_str(&format!("{{{}}}", name)),
+            Some(Some(style)) => output.push_str(&format!("{{{}, {}}}", name, style)),
+            None => return Err(ConvertError::unsupported(position, spec)),
+        }
+    }
+    push_literal(&mut output, &literal);
+    Ok(output)
+}
+
+/// Consume the next character if it is `expected`.
+fn next_is(chars: &mut Peekable<CharIndices>, expected: char) -> bool {
+    if chars.peek().map(|&(_, c)| c) == Some(expected) {
+        chars.next();
+        true
+    } else {
+        false
+    }
+}
+
+/// Map a `str.format` format specification onto the type and style of
+/// an ICU argument, such as `number, integer`. `Some(None)` means a
+/// plain argument.
+fn format_style(spec: &str) -> Option<Option<String>> {
+    let (grouping, spec) = match spec.strip_prefix(',') {
+        Some(spec) => (true, spec),
+        None => (false, spec),
+    };
+    let (precision, conversion) = match spec.strip_prefix('.') {
+        Some(spec) => {
+            let digits = spec.len() - spec.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            (Some(spec[..digits].parse().ok()?), &spec[digits..])
+        }
+        None => (None, spec),
+    };
+    numeric_style(grouping, precision, conversion)
+}
+
+/// Map a `%`-format conversion specifier onto the type and style of an
+/// ICU argument. `Some(None)` means a plain argument.
+fn percent_style(spec: &str) -> Option<Option<String>> {
+    let (precision, conversion) = match spec.strip_prefix('.') {
+        Some(spec) => {
+            let (index, _) = spec.char_indices().last()?;
+            (Some(spec[..index].parse().ok()?), &spec[index..])
+        }
+        None => (None, spec),
+    };
+    match conversion {
+        "i" | "u" => numeric_style(false, precision, "d"),
+        "s" | "d" | "f" | "F" | "e" | "E" | "g" | "G" => {
+            numeric_style(false, precision, conversion)
+        }
+        _ => None,
+    }
+}
+
+fn numeric_style(
+    grouping: bool,
+    precision: Option<usize>,
+    conversion: &str,
+) -> Option<Option<String>> {
+    let (fraction_digits, percent) = match (conversion, precision) {
+        ("", None) | ("s", None) if !grouping => return Some(None),
+        ("", None) | ("n", None) | ("g", None) | ("G", None) => {
+            return Some(Some("number".to_string()))
+        }
+        ("d", None) => (Some(0), false),
+        ("f", _) | ("F", _) => (Some(precision.unwrap_or(6)), false),
+        ("%", _) => (Some(precision.unwrap_or(6)), true),
+        _ => return None,
+    };
+    let style = NumberStyle {
+        grouping,
+        fraction_digits,
+        percent,
+    };
+    Some(Some(format!("number, {}", style.to_icu())))
+}
+
+/// The `str.format` specification for an ICU number style.
+fn number_spec(style: Option<&str>) -> Option<String> {
+    let style = number_style(style)?;
+    let grouping = if style.grouping { "," } else { "" };
+    let spec = match (style.fraction_digits, style.percent) {
+        (None, false) => "n".to_string(),
+        (Some(0), false) => format!("{}d", grouping),
+        (Some(digits), false) => format!("{}.{}f", grouping, digits),
+        (Some(digits), true) => format!("{}.{}%", grouping, digits),
+        (None, true) => return None,
+    };
+    Some(spec)
+}
+
+/// The `%`-format conversion for a `str.format` specification produced
+/// by `number_spec`. `%`-formatting cannot group digits.
+fn percent_conversion(spec: &str) -> Option<String> {
+    match spec {
+        "" => Some("s".to_string()),
+        "d" => Some("d".to_string()),
+        _ if spec.starts_with('.') && spec.ends_with('f') => Some(spec.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {arg, icu, Context};
+
+    #[test]
+    fn format_strings_work() {
+        let to_icu = |source| to_icu(source, Style::Format);
+        assert_eq!(to_icu("{} and {}").unwrap(), "{0} and {1}");
+        assert_eq!(to_icu("{{{name}}}").unwrap(), "'{'{name}'}'");
+        assert_eq!(
+            to_icu("{total:.2f}").unwrap(),
+            "{total, number, ::.00 group-off}"
+        );
+        assert_eq!(
+            to_icu("{ratio:.0%}").unwrap(),
+            "{ratio, number, ::percent scale/100 precision-integer group-off}"
+        );
+        assert_eq!(to_icu("{ratio:,.0%}").unwrap(), "{ratio, number, percent}");
+        assert_eq!(to_icu("{name!s}").unwrap(), "{name}");
+        assert!(to_icu("{name!r}").is_err());
+        assert!(to_icu("{name:>10}").is_err());
+        assert!(to_icu("{user.name}").is_err());
+        assert!(to_icu("{} and {name}").is_err());
+    }
+
+    #[test]
+    fn percent_strings_work() {
+        let to_icu = |source| to_icu(source, Style::Percent);
+        assert_eq!(to_icu("%s of %s").unwrap(), "{0} of {1}");
+        assert_eq!(
+            to_icu("%(n)d%% done").unwrap(),
+            "{n, number, ::precision-integer group-off}% done"
+        );
+        assert_eq!(
+            to_icu("%(total).2f").unwrap(),
+            "{total, number, ::.00 group-off}"
+        );
+        assert!(to_icu("%.3e").is_err());
+        assert!(to_icu("%5s").is_err());
+        assert!(to_icu("%(name)s and %s").is_err());
+    }
+
+    #[test]
+    fn non_ascii_conversions_are_unsupported() {
+        let to_icu = |source| to_icu(source, Style::Percent);
+        assert_eq!(to_icu("%.5é"), Err(ConvertError::unsupported(0, ".5é")));
+        assert_eq!(to_icu("%(x).2é"), Err(ConvertError::unsupported(0, ".2é")));
+    }
+
+    #[test]
+    fn from_icu_works() {
+        assert_eq!(
+            from_icu("'{'{total, number, #,##0.00}'}'", Style::Format).unwrap(),
+            "{{{total:,.2f}}}"
+        );
+        assert_eq!(
+            from_icu("{n, number, 0}% done", Style::Percent).unwrap(),
+            "%(n)d%% done"
+        );
+        assert!(from_icu("{n, plural, other {#}}", Style::Format).is_err());
+        assert!(from_icu("{1} of {0}", Style::Percent).is_err());
+        assert!(from_icu("{n, number, integer}", Style::Percent).is_err());
+    }
+
+    #[test]
+    fn output_is_formatted() {
+        let ctx = Context::default();
+        for &(source, style, expected) in &[
+            ("{total:,.2f} for {n}", Style::Format, "1,234.50 for 3"),
+            (
+                "{ratio:.1%}, {n:d} and {{n}}",
+                Style::Format,
+                "12.5%, 3 and {n}",
+            ),
+            (
+                "%(total).1f, %(n)d%% and 'n'",
+                Style::Percent,
+                "1234.5, 3% and 'n'",
+            ),
+        ] {
+            let pattern = to_icu(source, style).unwrap();
+            let message = icu::parse(&pattern).unwrap();
+            let first = arg("total", 1234.5);
+            let second = first.arg("ratio", 0.125);
+            let args = second.arg("n", 3);
+            assert_eq!(ctx.format(&message, Some(&args)), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn round_trips_work() {
+        for source in &[
+            "{name} has {count:,d} points",
+            "{0}: {1:.1%}",
+            "{{literal}}",
+        ] {
+            let pattern = to_icu(source, Style::Format).unwrap();
+            assert_eq!(&from_icu(&pattern, Style::Format).unwrap(), source);
+        }
+    }
+}
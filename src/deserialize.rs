@@ -0,0 +1,160 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "std")]
+use std::hash::BuildHasher;
+#[cfg(feature = "std")]
+use std::marker::PhantomData;
+
+#[cfg(feature = "std")]
+use serde::de::MapAccess;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+
+use icu;
+use Message;
+
+/// Deserialize a `Message` by parsing an ICU pattern string.
+///
+/// This requires the `serde` feature.
+impl<'de> Deserialize<'de> for Message {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(MessageVisitor)
+    }
+}
+
+struct MessageVisitor;
+
+impl<'de> Visitor<'de> for MessageVisitor {
+    type Value = Message;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an ICU message pattern")
+    }
+
+    fn visit_str<E>(self, pattern: &str) -> Result<Message, E>
+    where
+        E: de::Error,
+    {
+        icu::parse(pattern).map_err(|err| {
+            E::custom(format_args!(
+                "invalid message pattern {:?}: {}",
+                pattern, err
+            ))
+        })
+    }
+}
+
+/// Deserialize a map of message keys to ICU patterns, parsing each
+/// pattern into a [`Message`].
+///
+/// Unlike deserializing a `HashMap<String, Message>` directly, a pattern
+/// which fails to parse is reported along with its key. This is meant
+/// for use with `#[serde(deserialize_with = "...")]`:
+///
+/// ```text
+/// #[derive(Deserialize)]
+/// struct Catalog {
+///     #[serde(deserialize_with = "message_format::deserialize_messages")]
+///     messages: HashMap<String, Message>,
+/// }
+/// ```
+///
+/// The map may use any hasher, such as the [`FastBuildHasher`] from the
+/// `fast-hash` feature.
+///
+/// This requires the `serde` and `std` features.
+///
+/// [`Message`]: struct.Message.html
+/// [`FastBuildHasher`]: type.FastBuildHasher.html
+#[cfg(feature = "std")]
+pub fn deserialize_messages<'de, D, S>(
+    deserializer: D,
+) -> Result<HashMap<String, Message, S>, D::Error>
+where
+    D: Deserializer<'de>,
+    S: BuildHasher + Default,
+{
+    deserializer.deserialize_map(MessagesVisitor(PhantomData))
+}
+
+#[cfg(feature = "std")]
+struct MessagesVisitor<S>(PhantomData<S>);
+
+#[cfg(feature = "std")]
+impl<'de, S: BuildHasher + Default> Visitor<'de> for MessagesVisitor<S> {
+    type Value = HashMap<String, Message, S>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of message keys to ICU message patterns")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut messages =
+            HashMap::with_capacity_and_hasher(map.size_hint().unwrap_or(0), S::default());
+        while let Some(key) = map.next_key::<String>()? {
+            let pattern = map.next_value::<String>()?;
+            let message = icu::parse(&pattern).map_err(|err| {
+                de::Error::custom(format_args!(
+                    "invalid message pattern {:?} for key `{}`: {}",
+                    pattern, key, err
+                ))
+            })?;
+            messages.insert(key, message);
+        }
+        Ok(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::de::value::{Error, MapDeserializer, StrDeserializer};
+    use serde::de::{Deserialize, IntoDeserializer};
+
+    use super::deserialize_messages;
+    use {Context, Message};
+
+    #[test]
+    fn message_works() {
+        let ctx = Context::default();
+        let deserializer: StrDeserializer<Error> = "Hello {name}!".into_deserializer();
+        let message = Message::deserialize(deserializer).unwrap();
+        assert_eq!(format_message!(ctx, &message, name => "Ann"), "Hello Ann!");
+
+        let deserializer: StrDeserializer<Error> = "Hello {name".into_deserializer();
+        assert!(Message::deserialize(deserializer).is_err());
+    }
+
+    #[test]
+    fn messages_report_keys() {
+        let ctx = Context::default();
+        let entries = vec![("greeting", "Hi {name}"), ("farewell", "Bye {name}")];
+        let deserializer: MapDeserializer<_, Error> = MapDeserializer::new(entries.into_iter());
+        let messages: HashMap<_, _> = deserialize_messages(deserializer).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(
+            format_message!(ctx, &messages["farewell"], name => "Ann"),
+            "Bye Ann"
+        );
+
+        let entries = vec![("greeting", "Hi {name}"), ("broken", "Bye {name")];
+        let deserializer: MapDeserializer<_, Error> = MapDeserializer::new(entries.into_iter());
+        let error =
+            deserialize_messages::<_, ::std::collections::hash_map::RandomState>(deserializer)
+                .unwrap_err();
+        assert!(error.to_string().contains("`broken`"));
+    }
+}
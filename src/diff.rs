@@ -0,0 +1,444 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use prelude::*;
+use Message;
+
+/// A difference between two messages, from [`diff`].
+///
+/// Parts are described as in the outline from [`Message::dump`], such
+/// as `Argument name` or `Select gender`.
+///
+/// [`diff`]: fn.diff.html
+/// [`Message::dump`]: struct.Message.html#method.dump
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Change {
+    /// Literal text was changed.
+    TextEdited {
+        /// The text before the change.
+        old: String,
+        /// The text after the change.
+        new: String,
+    },
+    /// A part was replaced by another of the same kind, such as an
+    /// argument which was renamed.
+    Changed {
+        /// The part before the change.
+        old: String,
+        /// The part after the change.
+        new: String,
+    },
+    /// A part was added.
+    Added(String),
+    /// A part was removed.
+    Removed(String),
+    /// A `plural` or `select` gained a branch.
+    BranchAdded {
+        /// The part with the branch.
+        part: String,
+        /// The key of the branch.
+        key: String,
+    },
+    /// A `plural` or `select` lost a branch.
+    BranchRemoved {
+        /// The part with the branch.
+        part: String,
+        /// The key of the branch.
+        key: String,
+    },
+    /// The message of a branch of a `plural` or `select` was changed.
+    Branch {
+        /// The part with the branch.
+        part: String,
+        /// The key of the branch.
+        key: String,
+        /// The changes to the message of the branch.
+        changes: Vec<Change>,
+    },
+}
+
+/// A line of the outline of a message and the lines nested under it.
+struct Node<'a> {
+    label: &'a str,
+    children: Vec<Node<'a>>,
+}
+
+impl<'a> Node<'a> {
+    /// The kind of part, such as `Text` or `Argument`.
+    fn kind(&self) -> &'a str {
+        self.label.split(' ').next().unwrap_or("")
+    }
+
+    /// The parts of the message of a branch.
+    fn message_parts(&self) -> &[Node<'a>] {
+        self.children
+            .first()
+            .map(|message| &message.children[..])
+            .unwrap_or(&[])
+    }
+
+    fn same(&self, other: &Node) -> bool {
+        self.label == other.label
+            && self.children.len() == other.children.len()
+            && self
+                .children
+                .iter()
+                .zip(&other.children)
+                .all(|(a, b)| a.same(b))
+    }
+}
+
+/// Read the lines from `*index` which are indented by `indent`, along
+/// with the more deeply indented lines under each.
+fn nodes<'a>(lines: &[(usize, &'a str)], index: &mut usize, indent: usize) -> Vec<Node<'a>> {
+    let mut found = vec![];
+    while *index < lines.len() && lines[*index].0 == indent {
+        let label = lines[*index].1;
+        *index += 1;
+        let children = match lines.get(*index) {
+            Some(&(child_indent, _)) if child_indent > indent => nodes(lines, index, child_indent),
+            _ => vec![],
+        };
+        found.push(Node { label, children });
+    }
+    found
+}
+
+/// The parts of a message, from its outline.
+fn parts(outline: &str) -> Vec<Node<'_>> {
+    let lines: Vec<(usize, &str)> = outline
+        .lines()
+        .map(|line| {
+            let text = line.trim_start_matches(' ');
+            (line.len() - text.len(), text)
+        })
+        .collect();
+    let mut index = 0;
+    nodes(&lines, &mut index, 0)
+        .pop()
+        .map(|message| message.children)
+        .unwrap_or_default()
+}
+
+/// The text of a `Text` part, from the `Debug` form in its outline.
+fn text(label: &str) -> String {
+    let quoted = label["Text ".len()..].trim_matches('"');
+    let mut output = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => output.push('\n'),
+            Some('r') => output.push('\r'),
+            Some('t') => output.push('\t'),
+            Some('0') => output.push('\0'),
+            Some('u') => {
+                let digits: String = chars
+                    .by_ref()
+                    .skip_while(|&c| c == '{')
+                    .take_while(|&c| c != '}')
+                    .collect();
+                let c = u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(::std::char::from_u32);
+                output.extend(c);
+            }
+            Some(c) => output.push(c),
+            None => {}
+        }
+    }
+    output
+}
+
+/// The key of a branch, from its line in the outline, such as `"male":`
+/// or `One:`.
+fn key(label: &str) -> String {
+    label.trim_end_matches(':').trim_matches('"').to_string()
+}
+
+/// Compare the parts of two messages, matching up the parts which are
+/// the same and comparing the rest in place.
+fn diff_parts(old: &[Node], new: &[Node], changes: &mut Vec<Change>) {
+    // The lengths of the longest common runs of parts, by label, of
+    // the ends of `old` and `new`.
+    let mut lengths = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i].label == new[j].label {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let (mut removed, mut added) = (vec![], vec![]);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i].label == new[j].label {
+            unmatched(&removed, &added, changes);
+            removed.clear();
+            added.clear();
+            diff_branches(&old[i], &new[j], changes);
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            added.push(&new[j]);
+            j += 1;
+        } else {
+            removed.push(&old[i]);
+            i += 1;
+        }
+    }
+    unmatched(&removed, &added, changes);
+}
+
+/// Describe parts which were not matched up, pairing each removed part
+/// with the added part in the same place if they are of the same kind.
+fn unmatched(removed: &[&Node], added: &[&Node], changes: &mut Vec<Change>) {
+    for index in 0..removed.len().max(added.len()) {
+        match (removed.get(index), added.get(index)) {
+            (Some(old), Some(new)) if old.kind() == "Text" && new.kind() == "Text" => {
+                changes.push(Change::TextEdited {
+                    old: text(old.label),
+                    new: text(new.label),
+                })
+            }
+            (Some(old), Some(new)) if old.kind() == new.kind() => changes.push(Change::Changed {
+                old: old.label.to_string(),
+                new: new.label.to_string(),
+            }),
+            (old, new) => {
+                if let Some(old) = old {
+                    changes.push(Change::Removed(old.label.to_string()));
+                }
+                if let Some(new) = new {
+                    changes.push(Change::Added(new.label.to_string()));
+                }
+            }
+        }
+    }
+}
+
+/// Compare the branches of one part of two messages.
+fn diff_branches(old: &Node, new: &Node, changes: &mut Vec<Change>) {
+    let part = old.label;
+    for branch in &old.children {
+        match new
+            .children
+            .iter()
+            .find(|other| other.label == branch.label)
+        {
+            None => changes.push(Change::BranchRemoved {
+                part: part.to_string(),
+                key: key(branch.label),
+            }),
+            Some(other) if !branch.same(other) => {
+                let mut branch_changes = vec![];
+                diff_parts(
+                    branch.message_parts(),
+                    other.message_parts(),
+                    &mut branch_changes,
+                );
+                changes.push(Change::Branch {
+                    part: part.to_string(),
+                    key: key(branch.label),
+                    changes: branch_changes,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    for branch in &new.children {
+        if !old.children.iter().any(|other| other.label == branch.label) {
+            changes.push(Change::BranchAdded {
+                part: part.to_string(),
+                key: key(branch.label),
+            });
+        }
+    }
+}
+
+/// The changes which turn the message `old` into `new`, such as text
+/// which was edited, arguments which were added or renamed and branches
+/// which were removed, for showing translators what changed in a source
+/// message since they translated it.
+///
+/// ```
+/// use message_format::{diff, icu, Change};
+///
+/// let old = icu::parse("Hello, {name}!").unwrap();
+/// let new = icu::parse("Hi, {user}!").unwrap();
+/// assert_eq!(
+///     diff(&old, &new),
+///     [
+///         Change::TextEdited { old: "Hello, ".to_string(), new: "Hi, ".to_string() },
+///         Change::Changed { old: "Argument name".to_string(), new: "Argument user".to_string() },
+///     ]
+/// );
+/// ```
+///
+/// The messages are compared by their outlines from [`Message::dump`],
+/// so a part of a kind of its own is compared by what it shows there.
+///
+/// [`Message::dump`]: struct.Message.html#method.dump
+pub fn diff(old: &Message, new: &Message) -> Vec<Change> {
+    let (old, new) = (old.dump(), new.dump());
+    let mut changes = vec![];
+    diff_parts(&parts(&old), &parts(&new), &mut changes);
+    changes
+}
+
+/// Feed the parts of a message into an FNV-1a hash, joining adjacent
+/// text so that a message hashes the same however its text was split.
+fn hash_parts(parts: &[Node], hash: &mut u64) {
+    fn add(hash: &mut u64, bytes: &[u8]) {
+        for &byte in bytes {
+            *hash = (*hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+    let mut index = 0;
+    while index < parts.len() {
+        if parts[index].kind() == "Text" {
+            let mut joined = String::new();
+            while index < parts.len() && parts[index].kind() == "Text" {
+                joined.push_str(&text(parts[index].label));
+                index += 1;
+            }
+            add(hash, b"Text\0");
+            add(hash, joined.as_bytes());
+            add(hash, b"\0");
+            continue;
+        }
+        let part = &parts[index];
+        add(hash, part.label.as_bytes());
+        add(hash, b"\0");
+        for branch in &part.children {
+            add(hash, b"(");
+            add(hash, key(branch.label).as_bytes());
+            add(hash, b"\0");
+            hash_parts(branch.message_parts(), hash);
+            add(hash, b")");
+        }
+        index += 1;
+    }
+}
+
+/// The fingerprint of a message, for [`Message::fingerprint`].
+///
+/// [`Message::fingerprint`]: struct.Message.html#method.fingerprint
+pub(crate) fn fingerprint(message: &Message) -> u64 {
+    let outline = message.dump();
+    let mut hash = 0xcbf2_9ce4_8422_2325;
+    hash_parts(&parts(&outline), &mut hash);
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, Change};
+    use icu::ast::{PlainText, SelectFormat};
+    use icu::parse;
+    use prelude::*;
+    use Message;
+
+    fn select(branches: &[(&str, &str)], other: &str) -> Message {
+        let mut select = SelectFormat::new("gender", parse(other).unwrap());
+        for &(key, pattern) in branches {
+            select.map(key, parse(pattern).unwrap());
+        }
+        Message::new(vec![Box::new(PlainText::new("Sent ")), Box::new(select)])
+    }
+
+    #[test]
+    fn parts_are_matched() {
+        let old = parse("{a} and {b, number}").unwrap();
+        assert_eq!(diff(&old, &old), []);
+        let new = parse("{a} and {c} and {b, number}!").unwrap();
+        assert_eq!(
+            diff(&old, &new),
+            [
+                Change::Added("Argument c".to_string()),
+                Change::Added("Text \" and \"".to_string()),
+                Change::Added("Text \"!\"".to_string()),
+            ]
+        );
+        let new = parse("{a} \"or\" {b, duration}").unwrap();
+        assert_eq!(
+            diff(&old, &new),
+            [
+                Change::TextEdited {
+                    old: " and ".to_string(),
+                    new: " \"or\" ".to_string(),
+                },
+                Change::Removed("Number b".to_string()),
+                Change::Added("Duration b Clock".to_string()),
+            ]
+        );
+        let new = parse("{a} and {b, number, ::.00}").unwrap();
+        assert_eq!(
+            diff(&old, &new),
+            [Change::Changed {
+                old: "Number b".to_string(),
+                new: "Number b fraction 2..2".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn branches_are_compared() {
+        let old = select(&[("male", "him"), ("female", "her")], "them");
+        let new = select(&[("female", "her {x}"), ("neuter", "it")], "them");
+        assert_eq!(
+            diff(&old, &new),
+            [
+                Change::BranchRemoved {
+                    part: "Select gender".to_string(),
+                    key: "male".to_string(),
+                },
+                Change::Branch {
+                    part: "Select gender".to_string(),
+                    key: "female".to_string(),
+                    changes: vec![
+                        Change::TextEdited {
+                            old: "her".to_string(),
+                            new: "her ".to_string(),
+                        },
+                        Change::Added("Argument x".to_string()),
+                    ],
+                },
+                Change::BranchAdded {
+                    part: "Select gender".to_string(),
+                    key: "neuter".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fingerprints_ignore_how_text_was_split() {
+        let parsed = parse("Sent them").unwrap();
+        let built = Message::new(vec![
+            Box::new(PlainText::new("Sent ")),
+            Box::new(PlainText::new("them")),
+        ]);
+        assert_eq!(parsed.fingerprint(), built.fingerprint());
+        assert_eq!(parsed.fingerprint(), 0xa9ba_aea7_407c_de8e);
+
+        let old = select(&[("male", "him")], "them");
+        let new = select(&[("male", "him")], "them");
+        assert_eq!(old.fingerprint(), new.fingerprint());
+        let new = select(&[("female", "him")], "them");
+        assert_ne!(old.fingerprint(), new.fingerprint());
+        assert_ne!(old.fingerprint(), parsed.fingerprint());
+
+        let plain = parse("{n, number}").unwrap();
+        let fixed = parse("{n, number, ::.00}").unwrap();
+        assert_ne!(plain.fingerprint(), fixed.fingerprint());
+    }
+}
@@ -0,0 +1,185 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+use std::time::Duration;
+
+use number;
+use Context;
+
+/// How a `duration` argument is formatted.
+///
+/// Durations are written in whole seconds. Any fraction of a second is
+/// dropped, as a media player or a timer does.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DurationStyle {
+    /// Minutes and seconds, such as `4:05`, with the hours in front if
+    /// there are any, such as `1:04:05`.
+    Clock,
+    /// Hours, minutes and seconds, such as `0:04:05`, for a list of
+    /// durations which should line up.
+    ClockHours,
+    /// The hours, minutes and seconds written out in the language being
+    /// localized for, such as "1 hour, 4 minutes, 5 seconds". Parts
+    /// which are zero are left out.
+    Long,
+}
+
+/// The singular and plural names of hours, minutes and seconds, from
+/// the CLDR.
+const UNIT_NAMES: &[(&str, [(&str, &str); 3])] = &[
+    (
+        "de",
+        [
+            ("Stunde", "Stunden"),
+            ("Minute", "Minuten"),
+            ("Sekunde", "Sekunden"),
+        ],
+    ),
+    (
+        "en",
+        [
+            ("hour", "hours"),
+            ("minute", "minutes"),
+            ("second", "seconds"),
+        ],
+    ),
+    (
+        "fr",
+        [
+            ("heure", "heures"),
+            ("minute", "minutes"),
+            ("seconde", "secondes"),
+        ],
+    ),
+];
+
+/// Write `duration` as a clock, showing the hours if `hours` is set or
+/// if there are any.
+pub(crate) fn write_clock(
+    stream: &mut dyn fmt::Write,
+    duration: Duration,
+    hours: bool,
+) -> fmt::Result {
+    let seconds = duration.as_secs();
+    if hours || seconds >= 3600 {
+        write!(
+            stream,
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    } else {
+        write!(stream, "{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// Write `duration` with the names of its units, in the language of
+/// `ctx`. Languages without names here use the English ones.
+fn write_long(stream: &mut dyn fmt::Write, ctx: &Context, duration: Duration) -> fmt::Result {
+    let (language, names) = UNIT_NAMES
+        .iter()
+        .find(|names| ctx.is_language(names.0))
+        .unwrap_or(&UNIT_NAMES[1]);
+    let symbols = number::symbols(ctx);
+    let seconds = duration.as_secs();
+    let parts = [seconds / 3600, seconds / 60 % 60, seconds % 60];
+    let mut first = true;
+    for (index, &amount) in parts.iter().enumerate() {
+        // A duration of no time at all is written as zero seconds.
+        if amount == 0 && !(seconds == 0 && index == 2) {
+            continue;
+        }
+        if !first {
+            stream.write_str(", ")?;
+        }
+        first = false;
+        let one = match *language {
+            "fr" => amount <= 1,
+            _ => amount == 1,
+        };
+        number::write_digits(stream, amount, symbols, true)?;
+        let name = if one { names[index].0 } else { names[index].1 };
+        write!(stream, " {}", name)?;
+    }
+    Ok(())
+}
+
+/// Write `duration` in the given `style`, in the language of `ctx`.
+pub(crate) fn write_duration(
+    stream: &mut dyn fmt::Write,
+    ctx: &Context,
+    duration: Duration,
+    style: DurationStyle,
+) -> fmt::Result {
+    match style {
+        DurationStyle::Clock => write_clock(stream, duration, false),
+        DurationStyle::ClockHours => write_clock(stream, duration, true),
+        DurationStyle::Long => write_long(stream, ctx, duration),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_duration, DurationStyle};
+    use prelude::*;
+    use std::time::Duration;
+    use Context;
+
+    fn duration(ctx: &Context, seconds: u64, style: DurationStyle) -> String {
+        let mut output = String::new();
+        write_duration(&mut output, ctx, Duration::from_secs(seconds), style).unwrap();
+        output
+    }
+
+    #[test]
+    fn clocks_work() {
+        let ctx = Context::default();
+        for &(seconds, clock, hours) in &[
+            (0, "0:00", "0:00:00"),
+            (65, "1:05", "0:01:05"),
+            (3599, "59:59", "0:59:59"),
+            (3600, "1:00:00", "1:00:00"),
+            (90061, "25:01:01", "25:01:01"),
+        ] {
+            assert_eq!(duration(&ctx, seconds, DurationStyle::Clock), clock);
+            assert_eq!(duration(&ctx, seconds, DurationStyle::ClockHours), hours);
+        }
+        let mut output = String::new();
+        let value = Duration::from_millis(65_999);
+        write_duration(&mut output, &ctx, value, DurationStyle::Clock).unwrap();
+        assert_eq!(output, "1:05");
+    }
+
+    #[test]
+    fn long_durations_work() {
+        let ctx = Context::default();
+        assert_eq!(duration(&ctx, 0, DurationStyle::Long), "0 seconds");
+        assert_eq!(duration(&ctx, 1, DurationStyle::Long), "1 second");
+        assert_eq!(
+            duration(&ctx, 3665, DurationStyle::Long),
+            "1 hour, 1 minute, 5 seconds"
+        );
+        assert_eq!(duration(&ctx, 7200, DurationStyle::Long), "2 hours");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn long_durations_are_localized() {
+        let ctx = Context::for_language;
+        assert_eq!(
+            duration(&ctx("de-AT"), 3720, DurationStyle::Long),
+            "1 Stunde, 2 Minuten"
+        );
+        assert_eq!(duration(&ctx("fr"), 0, DurationStyle::Long), "0 seconde");
+        assert_eq!(
+            duration(&ctx("fr"), 4_500_000, DurationStyle::Long),
+            "1\u{202f}250 heures"
+        );
+        assert_eq!(duration(&ctx("ja"), 60, DurationStyle::Long), "1 minute");
+    }
+}
@@ -0,0 +1,195 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Decoding Message Text
+//!
+//! Catalogs exported from Windows tools are often UTF-16 with a byte
+//! order mark rather than UTF-8. [`decode`] detects the encoding of a
+//! catalog or pattern from its bytes and decodes it into a string, which
+//! can then be parsed as usual.
+//!
+//! [`decode`]: fn.decode.html
+
+#[cfg(not(feature = "std"))]
+use alloc::borrow::Cow;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+#[cfg(feature = "std")]
+use std::error::Error;
+use std::fmt;
+use std::str;
+
+use prelude::*;
+
+/// The encodings that [`decode`] recognizes.
+///
+/// [`decode`]: fn.decode.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    /// UTF-8, with or without a byte order mark.
+    Utf8,
+    /// Little endian UTF-16, as written by most Windows tools.
+    Utf16Le,
+    /// Big endian UTF-16.
+    Utf16Be,
+}
+
+impl Encoding {
+    /// Detect the encoding of `bytes`, returning it along with the
+    /// length of its byte order mark.
+    ///
+    /// Without a byte order mark, text which starts with an ASCII
+    /// character followed or preceded by a zero byte is taken to be
+    /// UTF-16, as no UTF-8 text starts that way. Anything else is
+    /// taken to be UTF-8.
+    pub fn detect(bytes: &[u8]) -> (Encoding, usize) {
+        match *bytes {
+            [0xef, 0xbb, 0xbf, ..] => (Encoding::Utf8, 3),
+            [0xff, 0xfe, ..] => (Encoding::Utf16Le, 2),
+            [0xfe, 0xff, ..] => (Encoding::Utf16Be, 2),
+            [a, 0, ..] if a != 0 && a.is_ascii() => (Encoding::Utf16Le, 0),
+            [0, b, ..] if b != 0 && b.is_ascii() => (Encoding::Utf16Be, 0),
+            _ => (Encoding::Utf8, 0),
+        }
+    }
+}
+
+/// An error resulting from [`decode`].
+///
+/// [`decode`]: fn.decode.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeError {
+    /// The encoding that the text was decoded as.
+    pub encoding: Encoding,
+    /// The byte offset of the first invalid sequence.
+    pub position: usize,
+}
+
+#[cfg(feature = "std")]
+impl Error for DecodeError {}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let encoding = match self.encoding {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+        };
+        write!(f, "invalid {} at offset {}", encoding, self.position)
+    }
+}
+
+/// Decode text after detecting its encoding with [`Encoding::detect`].
+///
+/// Any byte order mark is removed. UTF-8 text is borrowed rather than
+/// copied.
+///
+/// ```
+/// use message_format::encoding;
+///
+/// let bytes = [0xff, 0xfe, b'H', 0, b'i', 0];
+/// assert_eq!(encoding::decode(&bytes).unwrap(), "Hi");
+/// assert_eq!(encoding::decode(b"\xef\xbb\xbfHi").unwrap(), "Hi");
+/// ```
+///
+/// [`Encoding::detect`]: enum.Encoding.html#method.detect
+pub fn decode(bytes: &[u8]) -> Result<Cow<'_, str>, DecodeError> {
+    let (encoding, bom) = Encoding::detect(bytes);
+    let bytes = &bytes[bom..];
+    let error = |position| DecodeError {
+        encoding,
+        position: bom + position,
+    };
+    let big_endian = match encoding {
+        Encoding::Utf8 => {
+            return str::from_utf8(bytes)
+                .map(Cow::Borrowed)
+                .map_err(|e| error(e.valid_up_to()));
+        }
+        Encoding::Utf16Le => false,
+        Encoding::Utf16Be => true,
+    };
+    if bytes.len() % 2 == 1 {
+        return Err(error(bytes.len() - 1));
+    }
+    let units = bytes.chunks(2).map(|pair| {
+        if big_endian {
+            u16::from_be_bytes([pair[0], pair[1]])
+        } else {
+            u16::from_le_bytes([pair[0], pair[1]])
+        }
+    });
+    let mut text = String::with_capacity(bytes.len() / 2);
+    let mut position = 0;
+    for c in ::std::char::decode_utf16(units) {
+        match c {
+            Ok(c) => {
+                position += c.len_utf16() * 2;
+                text.push(c);
+            }
+            Err(_) => return Err(error(position)),
+        }
+    }
+    Ok(Cow::Owned(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, DecodeError, Encoding};
+    use prelude::*;
+
+    fn utf16le(text: &str, bom: bool) -> Vec<u8> {
+        let mut bytes = if bom { vec![0xff, 0xfe] } else { vec![] };
+        for unit in text.encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn detection_works() {
+        assert_eq!(Encoding::detect(b"\xef\xbb\xbfa"), (Encoding::Utf8, 3));
+        assert_eq!(Encoding::detect(b"\xff\xfea\0"), (Encoding::Utf16Le, 2));
+        assert_eq!(Encoding::detect(b"\xfe\xff\0a"), (Encoding::Utf16Be, 2));
+        assert_eq!(Encoding::detect(b"a\0b\0"), (Encoding::Utf16Le, 0));
+        assert_eq!(Encoding::detect(b"\0a\0b"), (Encoding::Utf16Be, 0));
+        assert_eq!(Encoding::detect(b"ab"), (Encoding::Utf8, 0));
+        assert_eq!(Encoding::detect(b""), (Encoding::Utf8, 0));
+    }
+
+    #[test]
+    fn utf16_works() {
+        let text = "Grüße, {name} 😀";
+        assert_eq!(decode(&utf16le(text, true)).unwrap(), text);
+        assert_eq!(decode(&utf16le(text, false)).unwrap(), text);
+
+        let mut bytes = vec![0xfe, 0xff];
+        for unit in text.encode_utf16() {
+            bytes.extend_from_slice(&unit.to_be_bytes());
+        }
+        assert_eq!(decode(&bytes).unwrap(), text);
+    }
+
+    #[test]
+    fn invalid_input_fails() {
+        assert_eq!(
+            decode(b"ab\xff"),
+            Err(DecodeError {
+                encoding: Encoding::Utf8,
+                position: 2,
+            })
+        );
+        // An unpaired surrogate.
+        assert_eq!(
+            decode(&[0xff, 0xfe, b'a', 0, 0x00, 0xd8, b'b', 0]),
+            Err(DecodeError {
+                encoding: Encoding::Utf16Le,
+                position: 4,
+            })
+        );
+        assert!(decode(&[0xff, 0xfe, b'a']).is_err());
+    }
+}
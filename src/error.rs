@@ -0,0 +1,303 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[cfg(feature = "std")]
+use std::error;
+use std::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+use encoding::DecodeError;
+use prelude::*;
+
+/// An error from parsing, loading or formatting messages.
+///
+/// Each kind of error has a stable [`code`] that can be logged or shown
+/// to translators.
+///
+/// [`code`]: #method.code
+#[derive(Debug)]
+pub enum Error {
+    /// A message could not be parsed.
+    Parse {
+        /// The offset in the source at which the error was found.
+        position: usize,
+        /// A description of what was expected.
+        message: String,
+    },
+    /// The bytes of a message or catalog could not be decoded.
+    Encoding(DecodeError),
+    /// An entry in a catalog could not be read.
+    Entry {
+        /// The key of the entry.
+        key: String,
+        /// The line on which the entry starts, counting from 1.
+        line: usize,
+        /// Why the entry could not be read.
+        error: Box<Error>,
+    },
+    /// The output of a message could not be written.
+    Format,
+    /// A message uses an argument which was not given.
+    MissingArgument {
+        /// The name of the argument.
+        name: String,
+    },
+    /// An argument was given a value of a type which the message can
+    /// not use.
+    UnsupportedType {
+        /// The name of the argument.
+        name: String,
+        /// The type of value that the message needs.
+        expected: &'static str,
+    },
+    /// Arguments were given which the message does not use, when
+    /// `Context::strict_arguments` is set.
+    UnusedArguments {
+        /// The names of the arguments, in the order they were given.
+        names: Vec<String>,
+    },
+    /// A message uses an argument which its [`Schema`] does not declare.
+    ///
+    /// [`Schema`]: struct.Schema.html
+    UndeclaredArgument {
+        /// The name of the argument.
+        name: String,
+    },
+    /// A branch of a `select` was given a key which it does not allow,
+    /// such as a misspelled gender.
+    UnknownKey {
+        /// The key of the branch.
+        key: String,
+    },
+    /// A message refers to a message which is not in its catalog, or
+    /// a catalog was asked to format a message which it does not have.
+    MissingMessage {
+        /// The id of the message.
+        id: String,
+    },
+    /// A message refers to itself, through the messages it refers to.
+    ReferenceCycle {
+        /// The ids of the messages in the cycle, starting and ending
+        /// with the same one.
+        ids: Vec<String>,
+    },
+    /// References from message to message went deeper than a catalog
+    /// allows.
+    ReferenceDepth {
+        /// The id of the message which was not formatted.
+        id: String,
+    },
+    /// The output of a message would have been longer than a catalog
+    /// allows.
+    OutputLength {
+        /// The most bytes of output which were allowed.
+        limit: usize,
+    },
+    /// Formatting a message would have formatted more of the messages
+    /// it refers to than a catalog allows.
+    Work {
+        /// The most messages which were allowed to be formatted.
+        limit: usize,
+    },
+    /// A sandboxed catalog was asked to format a message which is not
+    /// pure, such as one with a reference to another message.
+    Sandboxed {
+        /// The id of the message.
+        id: String,
+    },
+    /// A message in a catalog does not match its [`Schema`].
+    ///
+    /// [`Schema`]: struct.Schema.html
+    Schema {
+        /// The key of the message.
+        key: String,
+        /// How the message does not match.
+        error: Box<Error>,
+    },
+    /// A catalog container is of a version which can not be migrated
+    /// to the version that was asked for.
+    Version {
+        /// The version of the container.
+        found: u32,
+        /// The version that the container was to be migrated to.
+        wanted: u32,
+    },
+    /// A locale is not a valid language tag.
+    Locale {
+        /// The locale which was given.
+        tag: String,
+    },
+    /// A catalog could not be read.
+    ///
+    /// This requires the `std` feature.
+    #[cfg(feature = "std")]
+    Io(io::Error),
+}
+
+impl Error {
+    pub(crate) fn parse(position: usize, message: &str) -> Self {
+        Error::Parse {
+            position,
+            message: message.to_string(),
+        }
+    }
+
+    /// Move the position of a parse error along by `offset`, for an
+    /// error found in part of a larger source.
+    pub(crate) fn offset(self, offset: usize) -> Self {
+        match self {
+            Error::Parse { position, message } => Error::Parse {
+                position: position + offset,
+                message,
+            },
+            error => error,
+        }
+    }
+
+    pub(crate) fn missing_argument(name: &str) -> Self {
+        Error::MissingArgument {
+            name: name.to_string(),
+        }
+    }
+
+    pub(crate) fn unsupported_type(name: &str, expected: &'static str) -> Self {
+        Error::UnsupportedType {
+            name: name.to_string(),
+            expected,
+        }
+    }
+
+    /// A short, stable name for the kind of error.
+    ///
+    /// ```
+    /// use message_format::icu;
+    ///
+    /// assert_eq!(icu::parse("{name").unwrap_err().code(), "parse");
+    /// ```
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Error::Parse { .. } => "parse",
+            Error::Encoding(_) => "encoding",
+            Error::Entry { .. } => "entry",
+            Error::Format => "format",
+            Error::MissingArgument { .. } => "missing-argument",
+            Error::UnsupportedType { .. } => "unsupported-type",
+            Error::UnusedArguments { .. } => "unused-arguments",
+            Error::UndeclaredArgument { .. } => "undeclared-argument",
+            Error::UnknownKey { .. } => "unknown-key",
+            Error::MissingMessage { .. } => "missing-message",
+            Error::ReferenceCycle { .. } => "reference-cycle",
+            Error::ReferenceDepth { .. } => "reference-depth",
+            Error::OutputLength { .. } => "output-length",
+            Error::Work { .. } => "work",
+            Error::Sandboxed { .. } => "sandboxed",
+            Error::Schema { .. } => "schema",
+            Error::Version { .. } => "version",
+            Error::Locale { .. } => "locale",
+            #[cfg(feature = "std")]
+            Error::Io(_) => "io",
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Encoding(ref error) => Some(error),
+            Error::Entry { ref error, .. } => Some(&**error),
+            Error::Schema { ref error, .. } => Some(&**error),
+            Error::Io(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Parse {
+                position,
+                ref message,
+            } => write!(f, "{} at offset {}", message, position),
+            Error::Encoding(ref error) => error.fmt(f),
+            Error::Entry {
+                ref key,
+                line,
+                ref error,
+            } => write!(f, "invalid entry `{}` on line {}: {}", key, line, error),
+            Error::Format => f.write_str("the message could not be written"),
+            Error::MissingArgument { ref name } => write!(f, "missing argument `{}`", name),
+            Error::UnsupportedType { ref name, expected } => {
+                write!(f, "argument `{}` must be {}", name, expected)
+            }
+            Error::UnusedArguments { ref names } => {
+                f.write_str("unused arguments")?;
+                for (index, name) in names.iter().enumerate() {
+                    let separator = if index == 0 { " " } else { ", " };
+                    write!(f, "{}`{}`", separator, name)?;
+                }
+                Ok(())
+            }
+            Error::UndeclaredArgument { ref name } => {
+                write!(f, "undeclared argument `{}`", name)
+            }
+            Error::UnknownKey { ref key } => write!(f, "unknown key `{}`", key),
+            Error::MissingMessage { ref id } => write!(f, "missing message `{}`", id),
+            Error::ReferenceCycle { ref ids } => {
+                f.write_str("messages refer to each other:")?;
+                for (index, id) in ids.iter().enumerate() {
+                    let separator = if index == 0 { " " } else { " -> " };
+                    write!(f, "{}`{}`", separator, id)?;
+                }
+                Ok(())
+            }
+            Error::ReferenceDepth { ref id } => {
+                write!(f, "references are nested too deeply at `{}`", id)
+            }
+            Error::OutputLength { limit } => {
+                write!(f, "the output is longer than {} bytes", limit)
+            }
+            Error::Work { limit } => write!(f, "more than {} messages were formatted", limit),
+            Error::Sandboxed { ref id } => write!(
+                f,
+                "message `{}` uses a reference or a custom part, which the catalog does not allow",
+                id
+            ),
+            Error::Schema { ref key, ref error } => {
+                write!(f, "message `{}` does not match its schema: {}", key, error)
+            }
+            Error::Version { found, wanted } => write!(
+                f,
+                "catalog version {} can not be migrated to version {}",
+                found, wanted
+            ),
+            Error::Locale { ref tag } => write!(f, "`{}` is not a language tag", tag),
+            #[cfg(feature = "std")]
+            Error::Io(ref error) => error.fmt(f),
+        }
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(error: DecodeError) -> Self {
+        Error::Encoding(error)
+    }
+}
+
+impl From<fmt::Error> for Error {
+    fn from(_: fmt::Error) -> Self {
+        Error::Format
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
@@ -0,0 +1,301 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Collecting the messages of an application from its Rust source.
+//!
+//! A message which is written in the source with the `message!` macro
+//! has an id, its pattern in the source locale and, optionally, a
+//! description for translators:
+//!
+//! ```
+//! # #[macro_use]
+//! # extern crate message_format;
+//! # fn main() {
+//! let m = message!("greeting", "Hello, {name}!", "Shown at the top of the home page.");
+//! # }
+//! ```
+//!
+//! [`extract`] reads a source file and finds each use of `message!`,
+//! including those inside other macros such as `format_message!`, and
+//! [`write_properties`] writes what it found as a `.properties` catalog
+//! for translators to start from, which [`Catalog::read_properties`]
+//! reads back:
+//!
+//! ```
+//! use message_format::extract::{extract, write_properties};
+//! use message_format::Catalog;
+//!
+//! let source = r#"
+//!     fn title() -> Message {
+//!         message!("title", "Settings", "The title of the window.").unwrap()
+//!     }
+//! "#;
+//! let messages = extract(source).unwrap();
+//! assert_eq!(messages[0].id, "title");
+//! assert_eq!(messages[0].line, 3);
+//!
+//! let mut output = vec![];
+//! write_properties(&messages, &mut output).unwrap();
+//! assert_eq!(output, b"# The title of the window.\ntitle=Settings\n");
+//! let catalog = Catalog::read_properties(&output[..]).unwrap();
+//! assert!(catalog.get("title").is_some());
+//! ```
+//!
+//! This requires the `extract` feature, which uses `syn` to read Rust.
+//!
+//! [`extract`]: fn.extract.html
+//! [`write_properties`]: fn.write_properties.html
+//! [`Catalog::read_properties`]: ../struct.Catalog.html#method.read_properties
+
+use std::io;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::visit::Visit;
+use syn::{Expr, Lit, Macro};
+
+use properties::{self, Entry};
+use {icu, Error};
+
+/// A message found by [`extract`].
+///
+/// [`extract`]: fn.extract.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExtractedMessage {
+    /// The id of the message.
+    pub id: String,
+    /// The pattern of the message in the source locale.
+    pub pattern: String,
+    /// The description of the message for translators, if it has one.
+    pub description: Option<String>,
+    /// The line of the source on which the message is, counting from 1.
+    pub line: usize,
+}
+
+/// Find the messages in a Rust source file, in the order in which they
+/// are written.
+///
+/// Source which is not valid Rust fails with `Error::Parse`. A use of
+/// `message!` which does not have string literals for its id, pattern
+/// and description fails with `Error::Parse` too, since the message
+/// would otherwise be left out of the catalog without a word. Their
+/// positions are byte offsets in `source`. A pattern which can not be
+/// parsed fails with `Error::Entry`, holding the id and line of the
+/// message and the error from parsing the pattern.
+pub fn extract(source: &str) -> Result<Vec<ExtractedMessage>, Error> {
+    let file = syn::parse_file(source).map_err(|e| parse_error(e.span(), &e.to_string()))?;
+    let mut extractor = Extractor {
+        messages: vec![],
+        error: None,
+    };
+    extractor.visit_file(&file);
+    match extractor.error {
+        Some(error) => Err(error),
+        None => Ok(extractor.messages),
+    }
+}
+
+/// Write messages as a `.properties` catalog, with each description in
+/// a comment before its message.
+///
+/// Messages are written in the order in which they are given, so that
+/// the catalog follows the source. A later message with the same id as
+/// an earlier one replaces it when the catalog is read.
+pub fn write_properties<W: io::Write>(
+    messages: &[ExtractedMessage],
+    writer: W,
+) -> Result<(), Error> {
+    let entries: Vec<Entry> = messages
+        .iter()
+        .map(|message| Entry {
+            key: message.id.clone(),
+            pattern: message.pattern.clone(),
+            description: message.description.clone(),
+            line: message.line,
+        })
+        .collect();
+    properties::write(&entries, writer)
+}
+
+fn parse_error(span: Span, message: &str) -> Error {
+    Error::parse(span.byte_range().start, message)
+}
+
+struct Extractor {
+    messages: Vec<ExtractedMessage>,
+    error: Option<Error>,
+}
+
+impl Extractor {
+    /// Look for uses of `message!` in the tokens of another macro,
+    /// which `syn` does not parse.
+    fn scan(&mut self, tokens: TokenStream) {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        for (index, token) in tokens.iter().enumerate() {
+            let group = match *token {
+                TokenTree::Group(ref group) => group,
+                _ => continue,
+            };
+            let previous = if index >= 2 {
+                (&tokens[index - 2], &tokens[index - 1])
+            } else {
+                (token, token)
+            };
+            match previous {
+                (TokenTree::Ident(name), TokenTree::Punct(bang))
+                    if name == "message" && bang.as_char() == '!' =>
+                {
+                    self.read(group.stream(), name.span());
+                }
+                _ => self.scan(group.stream()),
+            }
+        }
+    }
+
+    /// Read the arguments of a use of `message!` at `span`.
+    fn read(&mut self, tokens: TokenStream, span: Span) {
+        if self.error.is_some() {
+            return;
+        }
+        let literals = Punctuated::<Expr, syn::token::Comma>::parse_terminated
+            .parse2(tokens)
+            .ok()
+            .and_then(|args| {
+                args.iter()
+                    .map(|arg| match *arg {
+                        Expr::Lit(ref expr) => match expr.lit {
+                            Lit::Str(ref s) => Some(s.value()),
+                            _ => None,
+                        },
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<String>>>()
+            });
+        let mut literals = match literals {
+            Some(literals) if literals.len() == 2 || literals.len() == 3 => literals,
+            _ => {
+                let message =
+                    "Expected a string literal id and pattern, and an optional description";
+                self.error = Some(parse_error(span, message));
+                return;
+            }
+        };
+        let description = if literals.len() == 3 {
+            literals.pop()
+        } else {
+            None
+        };
+        let pattern = literals.pop().unwrap_or_default();
+        let id = literals.pop().unwrap_or_default();
+        let line = span.start().line;
+        if let Err(error) = icu::parse(&pattern) {
+            self.error = Some(Error::Entry {
+                key: id,
+                line,
+                error: Box::new(error),
+            });
+            return;
+        }
+        self.messages.push(ExtractedMessage {
+            id,
+            pattern,
+            description,
+            line,
+        });
+    }
+}
+
+impl<'ast> Visit<'ast> for Extractor {
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        match mac.path.segments.last() {
+            Some(segment) if segment.ident == "message" => {
+                self.read(mac.tokens.clone(), segment.ident.span());
+            }
+            _ => self.scan(mac.tokens.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract, write_properties, ExtractedMessage};
+    use {Catalog, Error};
+
+    #[test]
+    fn messages_are_extracted() {
+        let source = r#"
+fn main() {
+    let ctx = Context::default();
+    let title = message!("title", "Settings").unwrap();
+    println!("{}", format_message!(ctx, &message_format::message!(
+        "home.greeting",
+        "Hello, {name}!\n",
+        "Shown at the top of the home page.",
+    ).unwrap(), name => "Ann"));
+}
+"#;
+        let messages = extract(source).unwrap();
+        assert_eq!(
+            messages,
+            [
+                ExtractedMessage {
+                    id: "title".to_string(),
+                    pattern: "Settings".to_string(),
+                    description: None,
+                    line: 4,
+                },
+                ExtractedMessage {
+                    id: "home.greeting".to_string(),
+                    pattern: "Hello, {name}!\n".to_string(),
+                    description: Some("Shown at the top of the home page.".to_string()),
+                    line: 5,
+                },
+            ]
+        );
+
+        let mut output = vec![];
+        write_properties(&messages, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output.clone()).unwrap(),
+            "title=Settings\n# Shown at the top of the home page.\nhome.greeting=Hello, {name}!\\n\n"
+        );
+        let catalog = Catalog::read_properties(&output[..]).unwrap();
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(
+            catalog.metadata("home.greeting").unwrap().description,
+            messages[1].description
+        );
+    }
+
+    #[test]
+    fn errors_have_positions() {
+        for &(source, expected) in &[
+            ("fn main() {", 10),
+            ("fn f() { message!(id, \"x\") }", 9),
+            ("fn f() { m!(message!(\"a\")) }", 12),
+        ] {
+            match extract(source) {
+                Err(Error::Parse { position, .. }) => assert_eq!(position, expected, "{}", source),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+        match extract("\nfn f() { message!(\"a\", \"{x\") }") {
+            Err(Error::Entry {
+                ref key,
+                line,
+                ref error,
+            }) => {
+                assert_eq!((&key[..], line), ("a", 2));
+                match **error {
+                    Error::Parse { position, .. } => assert_eq!(position, 2),
+                    ref other => panic!("unexpected error: {:?}", other),
+                }
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
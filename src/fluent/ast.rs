@@ -6,6 +6,8 @@
 
 #![allow(missing_docs)]
 
+use prelude::*;
+
 #[derive(Debug)]
 pub enum Expression {
     IdentifierExpression { name: String },
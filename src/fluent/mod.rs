@@ -10,4 +10,4 @@ mod ast;
 mod parse;
 
 pub use self::ast::*;
-pub use self::parse::parse;
+pub use self::parse::{parse, parse_bytes};
@@ -6,39 +6,21 @@
 
 #![allow(missing_docs)]
 
-use std::error::Error;
-use std::fmt;
 use std::str;
 use super::ast::*;
+use encoding;
+use prelude::*;
+use Error;
 
-pub fn parse(source: &str) -> Result<Vec<Entry>, ParseError> {
+pub fn parse(source: &str) -> Result<Vec<Entry>, Error> {
     let mut p = Parser::new(source);
     p.parse()
 }
 
-#[derive(Debug)]
-pub struct ParseError {
-    pub error_message: String,
-}
-
-impl ParseError {
-    pub fn new(error_message: &str) -> Self {
-        ParseError {
-            error_message: String::from(error_message),
-        }
-    }
-}
-
-impl Error for ParseError {
-    fn description(&self) -> &str {
-        &self.error_message
-    }
-}
-
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        self.description().fmt(f)
-    }
+/// Parse a source whose encoding is detected by `encoding::decode`.
+pub fn parse_bytes(source: &[u8]) -> Result<Vec<Entry>, Error> {
+    let source = encoding::decode(source)?;
+    parse(&source)
 }
 
 struct Parser<'a> {
@@ -56,6 +38,11 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// An error at the current position.
+    fn error(&self, message: &str) -> Error {
+        Error::parse(usize::from(self.pos.saturating_sub(1)), message)
+    }
+
     fn bump(&mut self) {
         self.ch = self.source.next();
 
@@ -78,7 +65,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse(&mut self) -> Result<Vec<Entry>, ParseError> {
+    fn parse(&mut self) -> Result<Vec<Entry>, Error> {
         let mut entries: Vec<Entry> = Vec::new();
 
         self.get_ws();
@@ -100,16 +87,16 @@ impl<'a> Parser<'a> {
         Ok(entries)
     }
 
-    fn get_entry(&mut self, comment: Option<Comment>) -> Result<Entry, ParseError> {
+    fn get_entry(&mut self, comment: Option<Comment>) -> Result<Entry, Error> {
         self.get_entity(comment)
     }
 
-    fn get_entity(&mut self, comment: Option<Comment>) -> Result<Entry, ParseError> {
+    fn get_entity(&mut self, comment: Option<Comment>) -> Result<Entry, Error> {
         let id = try!(self.get_identifier());
         self.get_line_ws();
 
         if !self.ch_is('=') {
-            return Err(ParseError::new("Expected '='"));
+            return Err(self.error("Expected '='"));
         }
         self.bump();
 
@@ -125,12 +112,12 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn get_identifier(&mut self) -> Result<Identifier, ParseError> {
+    fn get_identifier(&mut self) -> Result<Identifier, Error> {
         let mut name = String::new();
 
         let ch = match self.ch {
             Some(c) => c,
-            None => return Err(ParseError::new("Unexpected end of input.")),
+            None => return Err(self.error("Unexpected end of input.")),
         };
 
         match ch {
@@ -150,7 +137,7 @@ impl<'a> Parser<'a> {
         Ok(Identifier { name: name })
     }
 
-    fn get_pattern(&mut self) -> Result<Value, ParseError> {
+    fn get_pattern(&mut self) -> Result<Value, Error> {
         let mut buffer = String::new();
         let mut source = String::new();
         let mut content = vec![];
@@ -165,7 +152,7 @@ impl<'a> Parser<'a> {
             match self.ch {
                 Some(c) if c == '\n' => {
                     if quote_delimited {
-                        return Err(ParseError::new("Unclosed string"));
+                        return Err(self.error("Unclosed string"));
                     }
                     self.bump();
                     self.get_line_ws();
@@ -174,7 +161,7 @@ impl<'a> Parser<'a> {
                         break;
                     }
                     if first_line && !buffer.is_empty() {
-                        return Err(ParseError::new(
+                        return Err(self.error(
                             "Multiline string should have the ID line \
                              empty",
                         ));
@@ -205,7 +192,7 @@ impl<'a> Parser<'a> {
         }
 
         if quote_delimited {
-            return Err(ParseError::new("Unclosed string"));
+            return Err(self.error("Unclosed string"));
         }
 
         if !buffer.is_empty() {
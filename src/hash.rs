@@ -0,0 +1,101 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::hash::{BuildHasherDefault, Hasher};
+
+/// A fast, non-cryptographic hasher for maps keyed by argument names
+/// and message keys.
+///
+/// This is the hash function used by the Rust compiler. It is much
+/// faster than the default SipHash for short keys, but it does not
+/// resist deliberate collisions, so it should not be used for maps
+/// whose keys are chosen by an attacker.
+///
+/// This requires the `fast-hash` feature.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FastHasher {
+    hash: u64,
+}
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+impl FastHasher {
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+}
+
+impl Hasher for FastHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in chunks.by_ref() {
+            let mut word = [0; 8];
+            word.copy_from_slice(chunk);
+            self.add(u64::from_le_bytes(word));
+        }
+        for &byte in chunks.remainder() {
+            self.add(u64::from(byte));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add(u64::from(i));
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add(u64::from(i));
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// A `BuildHasher` for [`FastHasher`], for use as the hasher of a
+/// `HashMap` of arguments or messages.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use message_format::{icu, Context, FastBuildHasher, Value};
+///
+/// let mut args: HashMap<&str, Value, FastBuildHasher> = HashMap::default();
+/// args.insert("name", Value::Str("John"));
+///
+/// let ctx = Context::default();
+/// let m = icu::parse("Hello, {name}!").unwrap();
+/// assert_eq!(ctx.format(&m, Some(&args)), "Hello, John!");
+/// ```
+///
+/// This requires the `fast-hash` feature.
+///
+/// [`FastHasher`]: struct.FastHasher.html
+pub type FastBuildHasher = BuildHasherDefault<FastHasher>;
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{BuildHasher, Hash};
+
+    use super::FastBuildHasher;
+
+    fn hash<T: Hash>(value: T) -> u64 {
+        FastBuildHasher::default().hash_one(value)
+    }
+
+    #[test]
+    fn hashes_differ() {
+        assert_eq!(hash("count"), hash("count"));
+        assert_ne!(hash("count"), hash("counts"));
+        assert_ne!(hash("a_long_argument_name"), hash("a_long_argument_nam"));
+    }
+}
@@ -0,0 +1,112 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use number::{self, ByteUnits};
+use prelude::*;
+use schema::add_argument;
+use text::{self, Text};
+use {ArgumentType, Arguments, Context, Error, MappedOutput, MessagePart, SpanKind};
+
+/// Format a number of bytes as a size that people can read, such as
+/// "1.5 MB", from `{size, bytes}`.
+#[derive(Debug)]
+pub struct BytesFormat {
+    /// The name of the variable whose value should be formatted.
+    variable_name: Text,
+    units: ByteUnits,
+}
+
+impl BytesFormat {
+    /// Construct a `BytesFormat`.
+    pub fn new(variable_name: &str, units: ByteUnits) -> Self {
+        BytesFormat {
+            variable_name: Text::from(variable_name),
+            units,
+        }
+    }
+}
+
+impl MessagePart for BytesFormat {
+    fn apply_format(
+        &self,
+        ctx: &Context,
+        stream: &mut dyn fmt::Write,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let arg = args.and_then(|args| args.lookup(&self.variable_name));
+        match arg.and_then(|arg| arg.number(ctx.coerce_arguments)) {
+            Some(value) => number::write_bytes(stream, ctx, value, self.units),
+            None => Err(fmt::Error {}),
+        }
+    }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Argument(self.variable_name.to_string()));
+        Ok(())
+    }
+
+    fn check_arguments(&self, ctx: &Context, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        let arg = args
+            .and_then(|args| args.lookup(&self.variable_name))
+            .ok_or_else(|| Error::missing_argument(&self.variable_name))?;
+        match arg.number(ctx.coerce_arguments) {
+            Some(_) => Ok(()),
+            None => Err(Error::unsupported_type(&self.variable_name, "a number")),
+        }
+    }
+
+    fn collect_arguments(&self, arguments: &mut Vec<(String, ArgumentType)>) {
+        add_argument(arguments, &self.variable_name, ArgumentType::Number);
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(
+            stream,
+            "{:indent$}Bytes {} {:?}",
+            "",
+            &*self.variable_name,
+            self.units,
+            indent = indent
+        )
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self) + text::heap_size(&self.variable_name)
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use icu::parse;
+    use Context;
+
+    #[test]
+    fn it_works() {
+        let ctx = Context::default();
+        let m = parse("{done, bytes} of {total, bytes, binary}").unwrap();
+        assert_eq!(
+            format_message!(ctx, &m, done => 1_500_000, total => 3_221_225_472_i64),
+            "1.5 MB of 3 GiB"
+        );
+        assert_eq!(
+            m.dump(),
+            "Message\n  Bytes done Decimal\n  Text \" of \"\n  Bytes total Binary\n"
+        );
+    }
+}
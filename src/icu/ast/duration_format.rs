@@ -0,0 +1,122 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use duration::{self, DurationStyle};
+use prelude::*;
+use schema::add_argument;
+use text::{self, Text};
+use {ArgumentType, Arguments, Context, Error, MappedOutput, MessagePart, SpanKind};
+
+/// Format a duration, such as the length of a song or the time left on
+/// a timer, as a clock or with the names of its units.
+///
+/// The value may be a `Value::Duration` or a number of seconds.
+#[derive(Debug)]
+pub struct DurationFormat {
+    /// The name of the variable whose value should be formatted.
+    variable_name: Text,
+    style: DurationStyle,
+}
+
+impl DurationFormat {
+    /// Construct a `DurationFormat`.
+    pub fn new(variable_name: &str, style: DurationStyle) -> Self {
+        DurationFormat {
+            variable_name: Text::from(variable_name),
+            style,
+        }
+    }
+}
+
+impl MessagePart for DurationFormat {
+    fn apply_format(
+        &self,
+        ctx: &Context,
+        stream: &mut dyn fmt::Write,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let arg = args.and_then(|args| args.lookup(&self.variable_name));
+        match arg.and_then(|arg| arg.duration(ctx.coerce_arguments)) {
+            Some(value) => duration::write_duration(stream, ctx, value, self.style),
+            None => Err(fmt::Error {}),
+        }
+    }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Argument(self.variable_name.to_string()));
+        Ok(())
+    }
+
+    fn check_arguments(&self, ctx: &Context, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        let arg = args
+            .and_then(|args| args.lookup(&self.variable_name))
+            .ok_or_else(|| Error::missing_argument(&self.variable_name))?;
+        match arg.duration(ctx.coerce_arguments) {
+            Some(_) => Ok(()),
+            None => Err(Error::unsupported_type(&self.variable_name, "a duration")),
+        }
+    }
+
+    fn collect_arguments(&self, arguments: &mut Vec<(String, ArgumentType)>) {
+        add_argument(arguments, &self.variable_name, ArgumentType::Duration);
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(
+            stream,
+            "{:indent$}Duration {} {:?}",
+            "",
+            &*self.variable_name,
+            self.style,
+            indent = indent
+        )
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self) + text::heap_size(&self.variable_name)
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use icu::parse;
+    use std::time::Duration;
+    use {Context, Value};
+
+    #[test]
+    fn it_works() {
+        let ctx = Context::default();
+        let m = parse("{elapsed, duration} / {length, duration, clock-hours}").unwrap();
+        assert_eq!(
+            format_message!(ctx, &m, elapsed => Duration::from_secs(75), length => 3725),
+            "1:15 / 1:02:05"
+        );
+        let m = parse("Ready in {left, duration, long}").unwrap();
+        assert_eq!(
+            format_message!(ctx, &m, left => Duration::from_secs(90)),
+            "Ready in 1 minute, 30 seconds"
+        );
+
+        let args = [("left", Value::Number(-1))];
+        assert_eq!(
+            ctx.try_format(&m, Some(&args)).unwrap_err().code(),
+            "unsupported-type"
+        );
+    }
+}
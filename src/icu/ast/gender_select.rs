@@ -0,0 +1,192 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::SelectFormat;
+use prelude::*;
+use {Error, Message, Value};
+
+/// The grammatical gender of a person, as the value of the argument of
+/// a [`GenderSelect`].
+///
+/// An application with a gender type of its own can convert it to this
+/// with `From`, so that it can be passed straight to `format_message!`.
+///
+/// [`GenderSelect`]: struct.GenderSelect.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Gender {
+    /// Selects the `male` branch.
+    Male,
+    /// Selects the `female` branch.
+    Female,
+    /// Selects the `other` branch, such as for a person whose gender is
+    /// not known.
+    Other,
+}
+
+impl Gender {
+    /// The key of the branch which this gender selects.
+    pub fn key(self) -> &'static str {
+        match self {
+            Gender::Male => "male",
+            Gender::Female => "female",
+            Gender::Other => "other",
+        }
+    }
+}
+
+impl<'a> From<Gender> for Value<'a> {
+    fn from(gender: Gender) -> Value<'a> {
+        Value::Str(gender.key())
+    }
+}
+
+/// Build a `SelectFormat` on a gender, checking the keys of its branches.
+///
+/// Gendered messages are the most common use of `select`, and a
+/// misspelled key such as `femal` would otherwise only show up as the
+/// wrong text in one language.
+///
+/// ```
+/// use message_format::icu::ast::{Gender, GenderSelect, PlainText};
+/// use message_format::{Context, Message, Value};
+///
+/// let text = |text| Message::new(vec![Box::new(PlainText::new(text))]);
+/// let select = GenderSelect::new("gender")
+///     .branch("female", text("her"))
+///     .unwrap()
+///     .branch("other", text("their"))
+///     .unwrap();
+/// assert!(select.warnings().is_empty());
+/// assert!(GenderSelect::new("gender").branch("femal", text("her")).is_err());
+///
+/// let ctx = Context::default();
+/// let message = Message::new(vec![Box::new(select.build())]);
+/// let args = [("gender", Value::from(Gender::Female))];
+/// assert_eq!(ctx.format(&message, Some(&args)), "her");
+/// ```
+#[derive(Debug)]
+pub struct GenderSelect {
+    variable_name: String,
+    keys: Vec<String>,
+    branches: Vec<(String, Message)>,
+}
+
+impl GenderSelect {
+    /// Start a select on the argument with the given name, allowing the
+    /// keys `male`, `female` and `other`.
+    pub fn new(variable_name: &str) -> Self {
+        GenderSelect {
+            variable_name: variable_name.to_string(),
+            keys: vec![
+                "male".to_string(),
+                "female".to_string(),
+                "other".to_string(),
+            ],
+            branches: vec![],
+        }
+    }
+
+    /// Allow the given keys instead, such as for a language with a
+    /// neuter gender. `other` is always allowed.
+    pub fn keys(mut self, keys: &[&str]) -> Self {
+        self.keys = keys.iter().map(|key| key.to_string()).collect();
+        self
+    }
+
+    /// Add a branch, failing with `Error::UnknownKey` if `key` is not
+    /// allowed. A second branch with the same key replaces the first.
+    pub fn branch(mut self, key: &str, message: Message) -> Result<Self, Error> {
+        if key != "other" && !self.keys.iter().any(|allowed| allowed == key) {
+            return Err(Error::UnknownKey {
+                key: key.to_string(),
+            });
+        }
+        self.branches.retain(|branch| branch.0 != key);
+        self.branches.push((key.to_string(), message));
+        Ok(self)
+    }
+
+    /// Descriptions of the problems which do not stop the select from
+    /// being built, such as a missing `other` branch, which leaves
+    /// nothing to write for a gender without a branch of its own.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = vec![];
+        if !self.branches.iter().any(|branch| branch.0 == "other") {
+            warnings.push(format!(
+                "the select on `{}` has no `other` branch",
+                self.variable_name
+            ));
+        }
+        warnings
+    }
+
+    /// Build the `SelectFormat`. Without an `other` branch, a value
+    /// without a branch writes nothing.
+    pub fn build(self) -> SelectFormat {
+        let mut other = None;
+        let mut mappings = vec![];
+        for (key, message) in self.branches {
+            if key == "other" {
+                other = Some(message);
+            } else {
+                mappings.push((key, message));
+            }
+        }
+        let mut select = SelectFormat::new(
+            &self.variable_name,
+            other.unwrap_or_else(|| Message::text("")),
+        );
+        for (key, message) in mappings {
+            select.map(&key, message);
+        }
+        select
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gender, GenderSelect};
+    use icu::ast::PlainText;
+    use prelude::*;
+    use {Context, Error, Message};
+
+    fn text(text: &str) -> Message {
+        Message::new(vec![Box::new(PlainText::new(text))])
+    }
+
+    #[test]
+    fn keys_are_checked() {
+        let select = GenderSelect::new("g")
+            .keys(&["masculine", "feminine", "neuter"])
+            .branch("neuter", text("es"))
+            .unwrap();
+        assert_eq!(
+            select.warnings(),
+            ["the select on `g` has no `other` branch"]
+        );
+        match select.branch("male", text("er")) {
+            Err(Error::UnknownKey { ref key }) => assert_eq!(key, "male"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn genders_select_branches() {
+        let ctx = Context::default();
+        let select = GenderSelect::new("g")
+            .branch("male", text("he"))
+            .unwrap()
+            .branch("female", text("she"))
+            .unwrap()
+            .branch("male", text("him"))
+            .unwrap()
+            .build();
+        let message = Message::new(vec![Box::new(select)]);
+        assert_eq!(format_message!(ctx, &message, g => Gender::Male), "him");
+        assert_eq!(format_message!(ctx, &message, g => Gender::Female), "she");
+        assert_eq!(format_message!(ctx, &message, g => Gender::Other), "");
+    }
+}
@@ -0,0 +1,143 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use prelude::*;
+use schema::add_argument;
+use text::{self, Text};
+use {ArgumentType, Arguments, Context, Error, MappedOutput, MessagePart, SpanKind};
+
+/// Format a word in a grammatical case, such as the name of a city in
+/// the locative, from `{city, inflect, locative}`.
+///
+/// The form is looked up in the `Inflections` of the context. A word
+/// without that form, or a context without inflections, writes the
+/// word as it is given.
+#[derive(Debug)]
+pub struct InflectFormat {
+    /// The name of the variable whose value should be formatted.
+    variable_name: Text,
+    /// The name of the case to write the value in.
+    case: Text,
+}
+
+impl InflectFormat {
+    /// Construct an `InflectFormat`.
+    pub fn new(variable_name: &str, case: &str) -> Self {
+        InflectFormat {
+            variable_name: Text::from(variable_name),
+            case: Text::from(case),
+        }
+    }
+}
+
+impl MessagePart for InflectFormat {
+    fn apply_format(
+        &self,
+        ctx: &Context,
+        stream: &mut dyn fmt::Write,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let arg = args.and_then(|args| args.lookup(&self.variable_name));
+        let word = match arg.and_then(|arg| arg.string(ctx.coerce_arguments)) {
+            Some(word) => word,
+            None => return Err(fmt::Error {}),
+        };
+        #[cfg(feature = "std")]
+        {
+            let form = ctx
+                .inflections
+                .as_ref()
+                .and_then(|inflections| inflections.get(&word, &self.case));
+            if let Some(form) = form {
+                return stream.write_str(form);
+            }
+        }
+        stream.write_str(&word)
+    }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Argument(self.variable_name.to_string()));
+        Ok(())
+    }
+
+    fn check_arguments(&self, ctx: &Context, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        let arg = args
+            .and_then(|args| args.lookup(&self.variable_name))
+            .ok_or_else(|| Error::missing_argument(&self.variable_name))?;
+        match arg.string(ctx.coerce_arguments) {
+            Some(_) => Ok(()),
+            None => Err(Error::unsupported_type(&self.variable_name, "a string")),
+        }
+    }
+
+    fn collect_arguments(&self, arguments: &mut Vec<(String, ArgumentType)>) {
+        add_argument(arguments, &self.variable_name, ArgumentType::Str);
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(
+            stream,
+            "{:indent$}Inflect {} {}",
+            "",
+            &*self.variable_name,
+            &*self.case,
+            indent = indent
+        )
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self) + text::heap_size(&self.variable_name) + text::heap_size(&self.case)
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use icu::parse;
+    use Context;
+
+    #[test]
+    fn words_without_forms_are_kept() {
+        let ctx = Context::default();
+        let m = parse("Welcome to {city, inflect, locative}").unwrap();
+        assert_eq!(format_message!(ctx, &m, city => "Brno"), "Welcome to Brno");
+        assert_eq!(
+            m.dump(),
+            "Message\n  Text \"Welcome to \"\n  Inflect city locative\n"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn forms_are_looked_up() {
+        use std::sync::Arc;
+        use Inflections;
+
+        let mut inflections = Inflections::new();
+        inflections.insert("Kraków", "locative", "Krakowie");
+        let ctx = Context {
+            inflections: Some(Arc::new(inflections)),
+            ..Context::default()
+        };
+        let m = parse("w {city, inflect, locative}, nie {city}").unwrap();
+        assert_eq!(
+            format_message!(ctx, &m, city => "Kraków"),
+            "w Krakowie, nie Kraków"
+        );
+    }
+}
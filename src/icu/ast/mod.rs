@@ -9,16 +9,52 @@
 //! This module provides the various [`MessagePart`] implementations
 //! for the ICU Message Format functionality.
 //!
+//! Parsed messages are built once and then only read, so the parts
+//! hold their children in boxed slices rather than in vectors, which
+//! would keep a capacity and often some spare room as well.
+//!
 //! [`MessagePart`]: ../../trait.MessagePart.html
 
+use std::mem;
+
+use prelude::*;
+
+mod bytes_format;
+mod duration_format;
+mod gender_select;
+mod inflect_format;
+mod number_format;
+mod ordinal_format;
 mod placeholder_format;
 mod plain_text;
 mod plural_format;
+mod quote_format;
+mod reference_format;
 mod select_format;
 mod simple_format;
+mod transform_format;
 
+pub use self::bytes_format::BytesFormat;
+pub use self::duration_format::DurationFormat;
+pub use self::gender_select::{Gender, GenderSelect};
+pub use self::inflect_format::InflectFormat;
+pub use self::number_format::NumberFormat;
+pub use self::ordinal_format::OrdinalFormat;
 pub use self::placeholder_format::PlaceholderFormat;
 pub use self::plain_text::PlainText;
 pub use self::plural_format::PluralFormat;
+pub use self::quote_format::QuoteFormat;
+pub use self::reference_format::ReferenceFormat;
 pub use self::select_format::SelectFormat;
 pub use self::simple_format::SimpleFormat;
+pub use self::transform_format::{Transform, TransformFormat};
+
+/// Append an item to a boxed slice.
+///
+/// This reallocates the slice each time, which is fine for the handful
+/// of branches that a `plural` or `select` usually has.
+fn push<T>(items: &mut Box<[T]>, item: T) {
+    let mut vec = mem::replace(items, Box::new([])).into_vec();
+    vec.push(item);
+    *items = vec.into_boxed_slice();
+}
@@ -0,0 +1,208 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use number::{self, Number, NumberOptions};
+use prelude::*;
+use schema::add_argument;
+use text::{self, Text};
+use {
+    ArgumentType, Arguments, Context, Error, MappedOutput, MessagePart, NonFiniteNumbers, SpanKind,
+    Value,
+};
+
+/// Format a number in the style of the language being localized for.
+#[derive(Debug)]
+pub struct NumberFormat {
+    /// The name of the variable whose value should be formatted.
+    variable_name: Text,
+    options: NumberOptions,
+}
+
+impl NumberFormat {
+    /// Construct a `NumberFormat`.
+    pub fn new(variable_name: &str, options: NumberOptions) -> Self {
+        NumberFormat {
+            variable_name: Text::from(variable_name),
+            options,
+        }
+    }
+}
+
+/// The number held by `arg`, unless it is infinite or not a number and
+/// `ctx` does not accept those.
+fn number(ctx: &Context, arg: Value) -> Option<Number> {
+    arg.decimal(ctx.coerce_arguments)
+        .filter(|n| n.is_finite() || ctx.non_finite_numbers == NonFiniteNumbers::Symbols)
+}
+
+impl MessagePart for NumberFormat {
+    fn apply_format(
+        &self,
+        ctx: &Context,
+        stream: &mut dyn fmt::Write,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let arg = args.and_then(|args| args.lookup(&self.variable_name));
+        match arg.and_then(|arg| number(ctx, arg)) {
+            Some(value) => number::write_number(stream, ctx, value, &self.options),
+            None => Err(fmt::Error {}),
+        }
+    }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Argument(self.variable_name.to_string()));
+        Ok(())
+    }
+
+    fn check_arguments(&self, ctx: &Context, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        let arg = args
+            .and_then(|args| args.lookup(&self.variable_name))
+            .ok_or_else(|| Error::missing_argument(&self.variable_name))?;
+        match number(ctx, arg) {
+            Some(_) => Ok(()),
+            None => Err(Error::unsupported_type(&self.variable_name, "a number")),
+        }
+    }
+
+    fn collect_arguments(&self, arguments: &mut Vec<(String, ArgumentType)>) {
+        add_argument(arguments, &self.variable_name, ArgumentType::Number);
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        write!(
+            stream,
+            "{:indent$}Number {}",
+            "",
+            &*self.variable_name,
+            indent = indent
+        )?;
+        if let Some(ref currency) = self.options.currency {
+            write!(stream, " {} {:?}", currency, self.options.currency_display)?;
+        }
+        if !self.options.grouping {
+            stream.write_str(" ungrouped")?;
+        }
+        if self.options.percent {
+            stream.write_str(" percent")?;
+        }
+        if self.options.scale != 1 {
+            write!(stream, " scale {}", self.options.scale)?;
+        }
+        let minimum = self.options.minimum_fraction_digits;
+        match self.options.maximum_fraction_digits {
+            Some(maximum) => write!(stream, " fraction {}..{}", minimum, maximum)?,
+            None if minimum > 0 => write!(stream, " fraction {}..", minimum)?,
+            None => {}
+        }
+        writeln!(stream)
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self)
+            + text::heap_size(&self.variable_name)
+            + self
+                .options
+                .currency
+                .as_ref()
+                .map_or(0, |currency| currency.capacity())
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use icu::parse;
+    use {Context, NonFiniteNumbers, Value};
+
+    #[test]
+    fn it_works() {
+        let ctx = Context::default();
+        let m = parse("{count, number} files").unwrap();
+        assert_eq!(format_message!(ctx, &m, count => 12345), "12,345 files");
+        assert_eq!(
+            ctx.try_format(&m, Some(&[("count", Value::Str("many"))]))
+                .unwrap_err()
+                .code(),
+            "unsupported-type"
+        );
+        assert_eq!(m.dump(), "Message\n  Number count\n  Text \" files\"\n");
+    }
+
+    #[test]
+    fn wide_and_non_finite_numbers_work() {
+        let m = parse("{n, number}").unwrap();
+        let mut ctx = Context::default();
+        assert_eq!(
+            format_message!(ctx, &m, n => u128::MAX),
+            "340,282,366,920,938,463,463,374,607,431,768,211,455"
+        );
+        assert_eq!(
+            format_message!(ctx, &m, n => i128::MIN),
+            "-170,141,183,460,469,231,731,687,303,715,884,105,728"
+        );
+        assert_eq!(format_message!(ctx, &m, n => f64::NEG_INFINITY), "-∞");
+        assert_eq!(format_message!(ctx, &m, n => f64::NAN), "NaN");
+
+        ctx.non_finite_numbers = NonFiniteNumbers::Fail;
+        let args = [("n", Value::Float(f64::INFINITY))];
+        assert_eq!(
+            ctx.try_format(&m, Some(&args)).unwrap_err().code(),
+            "unsupported-type"
+        );
+    }
+
+    #[test]
+    fn percentages_can_be_scaled() {
+        let ctx = Context::default();
+        let m = parse("{done, number, ::percent} or {ratio, number, percent}").unwrap();
+        assert_eq!(
+            format_message!(ctx, &m, done => 50, ratio => 1),
+            "50% or 100%"
+        );
+        assert_eq!(
+            m.dump(),
+            "Message\n  Number done percent\n  Text \" or \"\n  Number ratio percent scale 100 fraction 0..0\n"
+        );
+    }
+
+    #[test]
+    fn fraction_digits_are_dumped() {
+        for &(pattern, expected) in &[
+            ("{n, number, ::.00}", "Number n fraction 2..2"),
+            ("{n, number, ::.0##}", "Number n fraction 1..3"),
+            ("{n, number, integer}", "Number n fraction 0..0"),
+        ] {
+            let m = parse(pattern).unwrap();
+            assert_eq!(
+                m.dump(),
+                format!("Message\n  {}\n", expected),
+                "{}",
+                pattern
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn currency_names_work() {
+        let ctx = Context::for_language("de");
+        let m = parse("{n, number, ::currency/RUB unit-width-full-name}").unwrap();
+        assert_eq!(format_message!(ctx, &m, n => 1), "1 Russischer Rubel");
+        assert_eq!(format_message!(ctx, &m, n => 2000), "2.000 Russische Rubel");
+    }
+}
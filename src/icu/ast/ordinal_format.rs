@@ -0,0 +1,107 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use number;
+use prelude::*;
+use schema::add_argument;
+use text::{self, Text};
+use {ArgumentType, Arguments, Context, Error, MappedOutput, MessagePart, SpanKind};
+
+/// Format a number as an ordinal, such as "1st" or "2nd", from the
+/// `{place, ordinal}` shorthand.
+///
+/// This saves writing out a `selectordinal` with a branch for each
+/// suffix when only the number and its suffix are needed.
+#[derive(Debug)]
+pub struct OrdinalFormat {
+    /// The name of the variable whose value should be formatted.
+    variable_name: Text,
+}
+
+impl OrdinalFormat {
+    /// Construct an `OrdinalFormat`.
+    pub fn new(variable_name: &str) -> Self {
+        OrdinalFormat {
+            variable_name: Text::from(variable_name),
+        }
+    }
+}
+
+impl MessagePart for OrdinalFormat {
+    fn apply_format(
+        &self,
+        ctx: &Context,
+        stream: &mut dyn fmt::Write,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let arg = args.and_then(|args| args.lookup(&self.variable_name));
+        match arg.and_then(|arg| arg.number(ctx.coerce_arguments)) {
+            Some(value) => number::write_ordinal(stream, ctx, value),
+            None => Err(fmt::Error {}),
+        }
+    }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Argument(self.variable_name.to_string()));
+        Ok(())
+    }
+
+    fn check_arguments(&self, ctx: &Context, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        let arg = args
+            .and_then(|args| args.lookup(&self.variable_name))
+            .ok_or_else(|| Error::missing_argument(&self.variable_name))?;
+        match arg.number(ctx.coerce_arguments) {
+            Some(_) => Ok(()),
+            None => Err(Error::unsupported_type(&self.variable_name, "a number")),
+        }
+    }
+
+    fn collect_arguments(&self, arguments: &mut Vec<(String, ArgumentType)>) {
+        add_argument(arguments, &self.variable_name, ArgumentType::Number);
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(
+            stream,
+            "{:indent$}Ordinal {}",
+            "",
+            &*self.variable_name,
+            indent = indent
+        )
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self) + text::heap_size(&self.variable_name)
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use icu::parse;
+    use Context;
+
+    #[test]
+    fn it_works() {
+        let ctx = Context::default();
+        let m = parse("You came {place, ordinal}!").unwrap();
+        assert_eq!(format_message!(ctx, &m, place => 2), "You came 2nd!");
+        assert_eq!(format_message!(ctx, &m, place => 11), "You came 11th!");
+        assert_eq!(m.arguments()[0].0, "place");
+    }
+}
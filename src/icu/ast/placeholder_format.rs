@@ -6,7 +6,7 @@
 
 use std::fmt;
 
-use {Args, Context, MessagePart};
+use {Arguments, Context, MappedOutput, MessagePart, SpanKind};
 
 /// A placeholder for a value. Used by `PluralFormat`.
 #[derive(Debug, Default)]
@@ -24,7 +24,7 @@ impl MessagePart for PlaceholderFormat {
         &self,
         ctx: &Context,
         stream: &mut fmt::Write,
-        _args: Option<&Args>,
+        _args: Option<&dyn Arguments>,
     ) -> fmt::Result {
         if let Some(value) = ctx.placeholder_value {
             try!(write!(stream, "{}", value));
@@ -33,11 +33,32 @@ impl MessagePart for PlaceholderFormat {
             Err(fmt::Error {})
         }
     }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Placeholder);
+        Ok(())
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(stream, "{:indent$}Placeholder", "", indent = indent)
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::PlaceholderFormat;
+    use prelude::*;
     use {Context, Message};
 
     #[test]
@@ -6,21 +6,22 @@
 
 use std::fmt;
 
-use {Args, Context, MessagePart};
+use text::{self, Text};
+use {Arguments, Context, MappedOutput, MessagePart, SpanKind};
 
 /// A string that should be output. Used for the text in between
 /// formats.
 #[derive(Debug)]
 pub struct PlainText {
     /// The text that should be output.
-    text: String,
+    text: Text,
 }
 
 impl PlainText {
     /// Construct a `PlainText`.
     pub fn new(text: &str) -> Self {
         PlainText {
-            text: text.to_string(),
+            text: Text::from(text),
         }
     }
 }
@@ -30,16 +31,47 @@ impl MessagePart for PlainText {
         &self,
         _ctx: &Context,
         stream: &mut fmt::Write,
-        _args: Option<&Args>,
+        _args: Option<&dyn Arguments>,
     ) -> fmt::Result {
-        try!(stream.write_str(self.text.as_str()));
+        try!(stream.write_str(&self.text));
         Ok(())
     }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Text);
+        Ok(())
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(
+            stream,
+            "{:indent$}Text {:?}",
+            "",
+            self.text,
+            indent = indent
+        )
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self) + text::heap_size(&self.text)
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::PlainText;
+    use prelude::*;
     use {Context, Message};
 
     #[test]
@@ -4,10 +4,18 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::convert::TryFrom;
 use std::fmt;
 
-use english_cardinal_classifier;
-use {Args, Context, Message, MessagePart, PluralCategory, Value};
+use super::push;
+use plural_classifiers::{cardinal_category, PluralOperands};
+use prelude::*;
+use schema::add_argument;
+use text::{self, Text};
+use {
+    ArgumentType, Arguments, Context, Decimal, Error, MappedOutput, Message, MessagePart,
+    PluralCategory, Value,
+};
 
 #[derive(Debug)]
 struct PluralMapping {
@@ -19,15 +27,12 @@ struct PluralMapping {
 #[derive(Debug)]
 pub struct PluralFormat {
     /// The name of the variable whose value should be formatted.
-    variable_name: String,
-    classifier: fn(i64) -> PluralCategory,
-    literals: Vec<PluralMapping>,
+    variable_name: Text,
+    literals: Box<[PluralMapping]>,
     offset: i64,
-    zero: Option<Message>,
-    one: Option<Message>,
-    two: Option<Message>,
-    few: Option<Message>,
-    many: Option<Message>,
+    /// The messages for each category other than `Other`, which are
+    /// usually few.
+    categories: Box<[(PluralCategory, Message)]>,
     other: Message,
 }
 
@@ -35,25 +40,25 @@ impl PluralFormat {
     /// Construct a `PluralFormat`.
     pub fn new(variable_name: &str, other: Message) -> Self {
         PluralFormat {
-            variable_name: variable_name.to_string(),
-            classifier: english_cardinal_classifier,
-            literals: vec![],
+            variable_name: Text::from(variable_name),
+            literals: Box::new([]),
             offset: 0,
-            zero: None,
-            one: None,
-            two: None,
-            few: None,
-            many: None,
+            categories: Box::new([]),
             other: other,
         }
     }
 
-    /// Set the `message` to be used for a literal value.
+    /// Set the `message` to be used for a literal value, which is
+    /// compared with the value before the `offset` is applied, as in
+    /// ICU.
     pub fn literal(&mut self, literal: i64, message: Message) {
-        self.literals.push(PluralMapping {
-            value: literal,
-            message: message,
-        });
+        push(
+            &mut self.literals,
+            PluralMapping {
+                value: literal,
+                message: message,
+            },
+        );
     }
 
     /// Apply an `offset`.
@@ -63,64 +68,113 @@ impl PluralFormat {
 
     /// Set the `message` for `PluralCategory::Zero`.
     pub fn zero(&mut self, message: Message) {
-        self.zero = Some(message);
+        self.category(PluralCategory::Zero, message);
     }
 
     /// Set the `message` for `PluralCategory::One`.
     pub fn one(&mut self, message: Message) {
-        self.one = Some(message);
+        self.category(PluralCategory::One, message);
     }
 
     /// Set the `message` for `PluralCategory::Two`.
     pub fn two(&mut self, message: Message) {
-        self.two = Some(message);
+        self.category(PluralCategory::Two, message);
     }
 
     /// Set the `message` for `PluralCategory::Few`.
     pub fn few(&mut self, message: Message) {
-        self.few = Some(message);
+        self.category(PluralCategory::Few, message);
     }
 
     /// Set the `message` for `PluralCategory::Many`.
     pub fn many(&mut self, message: Message) {
-        self.many = Some(message);
+        self.category(PluralCategory::Many, message);
     }
 
-    /// Given a value adjusted by the `offset`, determine which `Message` to use.
-    fn lookup_message(&self, offset_value: i64) -> &Message {
-        if let Some(literal_message) = self.literals
+    fn category(&mut self, category: PluralCategory, message: Message) {
+        match self.categories.iter().position(|&(c, _)| c == category) {
+            Some(index) => self.categories[index].1 = message,
+            None => push(&mut self.categories, (category, message)),
+        }
+    }
+
+    /// The message for a literal `value`, if there is one.
+    fn literal_message(&self, value: i64) -> Option<&Message> {
+        self.literals
             .iter()
-            .find(|mapping| mapping.value == offset_value)
+            .find(|mapping| mapping.value == value)
             .map(|mapping| &mapping.message)
-        {
-            literal_message
-        } else {
-            let category = (self.classifier)(offset_value);
-            match category {
-                PluralCategory::Zero => self.zero.as_ref().unwrap_or(&self.other),
-                PluralCategory::One => self.one.as_ref().unwrap_or(&self.other),
-                PluralCategory::Two => self.two.as_ref().unwrap_or(&self.other),
-                PluralCategory::Few => self.few.as_ref().unwrap_or(&self.other),
-                PluralCategory::Many => self.many.as_ref().unwrap_or(&self.other),
-                PluralCategory::Other => &self.other,
-            }
+    }
+
+    /// The message for the plural category of a number with the
+    /// given `operands` in the language of `ctx`.
+    fn category_message(&self, ctx: &Context, operands: PluralOperands) -> &Message {
+        let category = cardinal_category(ctx, operands);
+        self.categories
+            .iter()
+            .find(|&&(c, _)| c == category)
+            .map_or(&self.other, |category| &category.1)
+    }
+
+    /// The message for a decimal which is not a whole `i64`. A whole
+    /// decimal such as `1.0` may use a literal, and otherwise the
+    /// operands of its digits after the `offset` choose the category,
+    /// so that `1.5` is `one` in French and `many` in Czech.
+    fn decimal_message(&self, ctx: &Context, decimal: Decimal) -> &Message {
+        let unit = 10i128.checked_pow(decimal.scale);
+        let whole = unit
+            .filter(|&unit| decimal.mantissa % unit == 0)
+            .and_then(|unit| i64::try_from(decimal.mantissa / unit).ok());
+        if let Some(message) = whole.and_then(|value| self.literal_message(value)) {
+            return message;
+        }
+        let operands = unit
+            .and_then(|unit| unit.checked_mul(i128::from(self.offset)))
+            .and_then(|offset| decimal.mantissa.checked_sub(offset))
+            .and_then(|mantissa| PluralOperands::decimal(Decimal::new(mantissa, decimal.scale)));
+        match operands {
+            Some(operands) => self.category_message(ctx, operands),
+            None => &self.other,
+        }
+    }
+
+    /// The message to use for the argument `arg`, along with the value
+    /// for a `#` in it, or `None` if the argument is not a number.
+    ///
+    /// A number which is too large for the plural rules or which is not
+    /// finite uses the `other` message, in which there is no `#`, and
+    /// neither is there in the message for a decimal with a fraction.
+    fn choose(&self, ctx: &Context, arg: Value) -> Option<(&Message, Option<i64>)> {
+        match arg.number(ctx.coerce_arguments) {
+            Some(value) => match value.checked_sub(self.offset) {
+                Some(offset_value) => {
+                    let message = self.literal_message(value).unwrap_or_else(|| {
+                        self.category_message(ctx, PluralOperands::integer(offset_value))
+                    });
+                    Some((message, Some(offset_value)))
+                }
+                None => Some((&self.other, None)),
+            },
+            None => match arg {
+                Value::Decimal(decimal) => Some((self.decimal_message(ctx, decimal), None)),
+                _ if arg.is_unbounded() => Some((&self.other, None)),
+                _ => None,
+            },
         }
     }
 }
 
 impl MessagePart for PluralFormat {
-    fn apply_format<'f>(
+    fn apply_format(
         &self,
         ctx: &Context,
         stream: &mut fmt::Write,
-        args: Option<&Args<'f>>,
+        args: Option<&dyn Arguments>,
     ) -> fmt::Result {
-        let arg = args.and_then(|args| args.get(&self.variable_name));
-        if let Some(&Value::Number(value)) = arg.map(|a| a.value()) {
-            let offset_value = value - self.offset;
-            let message = self.lookup_message(offset_value);
+        let arg = args.and_then(|args| args.lookup(&self.variable_name));
+        if let Some((message, placeholder_value)) = arg.and_then(|arg| self.choose(ctx, arg)) {
             let ctx = Context {
-                placeholder_value: Some(offset_value),
+                placeholder_value,
                 ..ctx.clone()
             };
             try!(message.write_message(&ctx, stream, args));
@@ -129,13 +183,122 @@ impl MessagePart for PluralFormat {
             Err(fmt::Error {})
         }
     }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let arg = args.and_then(|args| args.lookup(&self.variable_name));
+        match arg.and_then(|arg| self.choose(ctx, arg)) {
+            Some((message, placeholder_value)) => {
+                let ctx = Context {
+                    placeholder_value,
+                    ..ctx.clone()
+                };
+                message.write_mapped(&ctx, output, args)
+            }
+            None => Err(fmt::Error {}),
+        }
+    }
+
+    fn check_arguments(&self, ctx: &Context, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        let arg = args
+            .and_then(|args| args.lookup(&self.variable_name))
+            .ok_or_else(|| Error::missing_argument(&self.variable_name))?;
+        match self.choose(ctx, arg) {
+            Some((message, placeholder_value)) => {
+                let ctx = Context {
+                    placeholder_value,
+                    ..ctx.clone()
+                };
+                message.check_arguments(&ctx, args)
+            }
+            None => Err(Error::unsupported_type(&self.variable_name, "a number")),
+        }
+    }
+
+    fn collect_arguments(&self, arguments: &mut Vec<(String, ArgumentType)>) {
+        add_argument(arguments, &self.variable_name, ArgumentType::Number);
+        for mapping in self.literals.iter() {
+            mapping.message.collect_arguments(arguments);
+        }
+        for category in self.categories.iter() {
+            category.1.collect_arguments(arguments);
+        }
+        self.other.collect_arguments(arguments);
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        write!(
+            stream,
+            "{:indent$}Plural {}",
+            "",
+            &*self.variable_name,
+            indent = indent
+        )?;
+        if self.offset != 0 {
+            write!(stream, " offset {}", self.offset)?;
+        }
+        writeln!(stream)?;
+        for mapping in self.literals.iter() {
+            writeln!(
+                stream,
+                "{:indent$}={}:",
+                "",
+                mapping.value,
+                indent = indent + 2
+            )?;
+            mapping.message.dump_to(stream, indent + 4)?;
+        }
+        for category in self.categories.iter() {
+            writeln!(
+                stream,
+                "{:indent$}{:?}:",
+                "",
+                category.0,
+                indent = indent + 2
+            )?;
+            category.1.dump_to(stream, indent + 4)?;
+        }
+        writeln!(stream, "{:indent$}Other:", "", indent = indent + 2)?;
+        self.other.dump_to(stream, indent + 4)
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self)
+            + text::heap_size(&self.variable_name)
+            + size_of_val(&*self.literals)
+            + self
+                .literals
+                .iter()
+                .map(|mapping| mapping.message.heap_size())
+                .sum::<usize>()
+            + size_of_val(&*self.categories)
+            + self
+                .categories
+                .iter()
+                .map(|category| category.1.heap_size())
+                .sum::<usize>()
+            + self.other.heap_size()
+    }
+
+    fn is_pure(&self) -> bool {
+        self.literals
+            .iter()
+            .all(|mapping| mapping.message.is_pure())
+            && self.categories.iter().all(|category| category.1.is_pure())
+            && self.other.is_pure()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use icu::parse;
     use super::PluralFormat;
-    use {Context, Message};
+    use icu::parse;
+    use prelude::*;
+    use {Context, Decimal, Error, Message, Value};
 
     #[test]
     fn it_works() {
@@ -156,6 +319,95 @@ mod tests {
         assert_eq!("Other", output);
     }
 
+    #[test]
+    fn strings_can_be_coerced() {
+        let mut fmt = PluralFormat::new("count", parse("Many").unwrap());
+        fmt.one(parse("One").unwrap());
+        let msg = Message::new(vec![Box::new(fmt)]);
+
+        let ctx = Context::default();
+        let args = [("count", Value::Str("1"))];
+        match ctx.try_format(&msg, Some(&args)) {
+            Err(Error::UnsupportedType { expected, .. }) => assert_eq!(expected, "a number"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let ctx = Context {
+            coerce_arguments: true,
+            ..Context::default()
+        };
+        assert_eq!(ctx.try_format(&msg, Some(&args)).unwrap(), "One");
+        let args = [("count", Value::Str("one"))];
+        assert_eq!(
+            ctx.try_format(&msg, Some(&args)).unwrap_err().code(),
+            "unsupported-type"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn decimals_use_their_operands() {
+        let msg = parse("{n, plural, =1 {Exactly} one {One} few {Few} many {Many} other {Other}}")
+            .unwrap();
+        let format = |language: &str, mantissa, scale| {
+            let ctx = Context::for_language(language);
+            let args = [("n", Value::Decimal(Decimal::new(mantissa, scale)))];
+            ctx.try_format(&msg, Some(&args)).unwrap()
+        };
+        for &(language, mantissa, scale, output) in &[
+            ("en", 15, 1, "Other"),
+            ("en", 100, 2, "Exactly"),
+            ("en", 200, 2, "Other"),
+            ("fr", 15, 1, "One"),
+            ("fr", 5, 1, "One"),
+            ("fr", 25, 1, "Other"),
+            ("fr", 2_000_000, 0, "Many"),
+            ("cs", 15, 1, "Many"),
+            ("cs", 300, 2, "Many"),
+            ("cs", 3, 0, "Few"),
+            ("cs", 5, 0, "Other"),
+        ] {
+            assert_eq!(
+                format(language, mantissa, scale),
+                output,
+                "{} {}",
+                language,
+                mantissa
+            );
+        }
+
+        let msg = parse("{n, plural, offset:1 one {One} other {Other}}").unwrap();
+        let ctx = Context::default();
+        let args = [("n", Value::Decimal(Decimal::new(200, 2)))];
+        assert_eq!(ctx.try_format(&msg, Some(&args)).unwrap(), "Other");
+        let args = [("n", Value::Decimal(Decimal::new(10, 40)))];
+        assert_eq!(ctx.try_format(&msg, Some(&args)).unwrap(), "Other");
+    }
+
+    #[test]
+    fn unbounded_numbers_choose_other() {
+        let mut fmt = PluralFormat::new("count", parse("Other").unwrap());
+        fmt.one(parse("One").unwrap());
+        fmt.offset(1);
+        let msg = Message::new(vec![Box::new(fmt)]);
+
+        let ctx = Context::default();
+        for &value in &[
+            Value::Float(f64::NAN),
+            Value::Float(f64::NEG_INFINITY),
+            Value::I128(i128::MAX),
+            Value::U128(u128::MAX),
+            Value::Number(i64::MIN),
+        ] {
+            let args = [("count", value)];
+            assert_eq!(ctx.try_format(&msg, Some(&args)).unwrap(), "Other");
+        }
+        let args = [("count", Value::I128(2))];
+        assert_eq!(ctx.try_format(&msg, Some(&args)).unwrap(), "One");
+        let args = [("count", Value::Decimal(Decimal::new(2, 0)))];
+        assert_eq!(ctx.try_format(&msg, Some(&args)).unwrap(), "One");
+    }
+
     #[test]
     fn literals_work() {
         let ctx = Context::default();
@@ -179,4 +431,49 @@ mod tests {
         let output = format_message!(ctx, &msg, count => 0);
         assert_eq!("Other", output);
     }
+
+    #[test]
+    fn memory_footprint_counts_branches() {
+        let ctx = Context::default();
+
+        let mut fmt = PluralFormat::new("count", parse("Other").unwrap());
+        let other_only = Message::new(vec![Box::new(PluralFormat::new(
+            "count",
+            parse("Other").unwrap(),
+        ))]);
+        fmt.one(parse("First").unwrap());
+        fmt.one(parse("One").unwrap());
+        let msg = Message::new(vec![Box::new(fmt)]);
+
+        assert_eq!("One", format_message!(ctx, &msg, count => 1));
+        assert!(msg.memory_footprint() > other_only.memory_footprint());
+        assert!(other_only.memory_footprint() > size_of::<Message>() + size_of::<PluralFormat>());
+    }
+
+    #[test]
+    fn dump_works() {
+        let mut fmt = PluralFormat::new("count", parse("{count} items").unwrap());
+        fmt.offset(1);
+        fmt.literal(0, parse("None").unwrap());
+        fmt.one(parse("One item").unwrap());
+        let msg = Message::new(vec![Box::new(fmt)]);
+
+        assert_eq!(
+            msg.dump(),
+            "\
+Message
+  Plural count offset 1
+    =0:
+      Message
+        Text \"None\"
+    One:
+      Message
+        Text \"One item\"
+    Other:
+      Message
+        Argument count
+        Text \" items\"
+"
+        );
+    }
 }
@@ -0,0 +1,144 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use prelude::*;
+use schema::add_argument;
+use text::{self, Text};
+use {ArgumentType, Arguments, Context, Error, MappedOutput, MessagePart, SpanKind};
+
+/// The primary quotation marks of the languages which do not use those
+/// of English, from the CLDR. French puts a space inside of them.
+const QUOTATION_MARKS: &[(&str, &str, &str)] = &[
+    ("cs", "„", "“"),
+    ("de", "„", "“"),
+    ("es", "«", "»"),
+    ("fi", "”", "”"),
+    ("fr", "«\u{a0}", "\u{a0}»"),
+    ("hu", "„", "”"),
+    ("it", "«", "»"),
+    ("ja", "「", "」"),
+    ("nb", "«", "»"),
+    ("nl", "‘", "’"),
+    ("pl", "„", "”"),
+    ("ru", "«", "»"),
+    ("sk", "„", "“"),
+    ("sv", "”", "”"),
+    ("uk", "«", "»"),
+];
+
+/// The opening and closing quotation marks for the language of `ctx`.
+pub(crate) fn quotation_marks(ctx: &Context) -> (&'static str, &'static str) {
+    QUOTATION_MARKS
+        .iter()
+        .find(|marks| ctx.is_language(marks.0))
+        .map_or(("“", "”"), |marks| (marks.1, marks.2))
+}
+
+/// Format a value inside of the quotation marks of the language being
+/// localized for, from `{title, quote}`, so that messages need not
+/// hard-code the quotation marks of English.
+#[derive(Debug)]
+pub struct QuoteFormat {
+    /// The name of the variable whose value should be formatted.
+    variable_name: Text,
+}
+
+impl QuoteFormat {
+    /// Construct a `QuoteFormat`.
+    pub fn new(variable_name: &str) -> Self {
+        QuoteFormat {
+            variable_name: Text::from(variable_name),
+        }
+    }
+}
+
+impl MessagePart for QuoteFormat {
+    fn apply_format(
+        &self,
+        ctx: &Context,
+        stream: &mut dyn fmt::Write,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        match args.and_then(|args| args.lookup(&self.variable_name)) {
+            Some(value) => {
+                let (open, close) = quotation_marks(ctx);
+                write!(stream, "{}{}{}", open, value, close)
+            }
+            None => Err(fmt::Error {}),
+        }
+    }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Argument(self.variable_name.to_string()));
+        Ok(())
+    }
+
+    fn check_arguments(&self, _ctx: &Context, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        match args.and_then(|args| args.lookup(&self.variable_name)) {
+            Some(_) => Ok(()),
+            None => Err(Error::missing_argument(&self.variable_name)),
+        }
+    }
+
+    fn collect_arguments(&self, arguments: &mut Vec<(String, ArgumentType)>) {
+        add_argument(arguments, &self.variable_name, ArgumentType::Any);
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(
+            stream,
+            "{:indent$}Quote {}",
+            "",
+            &*self.variable_name,
+            indent = indent
+        )
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self) + text::heap_size(&self.variable_name)
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use icu::parse;
+    use Context;
+
+    #[test]
+    fn it_works() {
+        let ctx = Context::default();
+        let m = parse("Saved {title, quote}").unwrap();
+        assert_eq!(format_message!(ctx, &m, title => "Notes"), "Saved “Notes”");
+        assert_eq!(m.dump(), "Message\n  Text \"Saved \"\n  Quote title\n");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn quotation_marks_depend_on_language() {
+        let m = parse("{title, quote}").unwrap();
+        let quote = |language: &str| {
+            let ctx = Context::for_language(language);
+            format_message!(ctx, &m, title => "Faust")
+        };
+        assert_eq!(quote("de-AT"), "„Faust“");
+        assert_eq!(quote("fr"), "«\u{a0}Faust\u{a0}»");
+        assert_eq!(quote("ja"), "「Faust」");
+        assert_eq!(quote("en-GB"), "“Faust”");
+    }
+}
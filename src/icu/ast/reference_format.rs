@@ -0,0 +1,105 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use prelude::*;
+use text::{self, Text};
+use {Arguments, Context, Error, MappedOutput, MessagePart, SpanKind};
+
+/// Format another message of the same catalog, from `{@id}`, such as
+/// the name of the application which many messages use.
+///
+/// The message is looked up through the arguments when it is
+/// formatted, so this only writes anything when the message is
+/// formatted with `Catalog::format`.
+#[derive(Debug)]
+pub struct ReferenceFormat {
+    /// The id of the message which should be formatted.
+    id: Text,
+}
+
+impl ReferenceFormat {
+    /// Construct a `ReferenceFormat`.
+    pub fn new(id: &str) -> Self {
+        ReferenceFormat { id: Text::from(id) }
+    }
+}
+
+impl MessagePart for ReferenceFormat {
+    fn apply_format(
+        &self,
+        ctx: &Context,
+        stream: &mut dyn fmt::Write,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        args.and_then(|args| args.write_reference(ctx, &self.id, stream))
+            .unwrap_or(Err(fmt::Error {}))
+    }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Reference(self.id.to_string()));
+        Ok(())
+    }
+
+    /// A reference which can be resolved is fine here, since the
+    /// catalog reports what went wrong in the message it refers to.
+    fn check_arguments(&self, ctx: &Context, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        let mut output = String::new();
+        match args.and_then(|args| args.write_reference(ctx, &self.id, &mut output)) {
+            Some(_) => Ok(()),
+            None => Err(Error::MissingMessage {
+                id: self.id.to_string(),
+            }),
+        }
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(
+            stream,
+            "{:indent$}Reference {}",
+            "",
+            &*self.id,
+            indent = indent
+        )
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self) + text::heap_size(&self.id)
+    }
+
+    fn is_pure(&self) -> bool {
+        // What is written for the reference is up to the arguments.
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use icu::parse;
+    use Context;
+
+    #[test]
+    fn references_need_a_catalog() {
+        let ctx = Context::default();
+        let m = parse("Welcome to {@app.name}").unwrap();
+        assert_eq!(
+            m.dump(),
+            "Message\n  Text \"Welcome to \"\n  Reference app.name\n"
+        );
+        assert_eq!(
+            ctx.try_format(&m, None).unwrap_err().code(),
+            "missing-message"
+        );
+    }
+}
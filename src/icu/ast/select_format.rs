@@ -6,11 +6,15 @@
 
 use std::fmt;
 
-use {Args, Context, Message, MessagePart, Value};
+use super::push;
+use prelude::*;
+use schema::add_argument;
+use text::{self, Text};
+use {ArgumentType, Arguments, Context, Error, MappedOutput, Message, MessagePart};
 
 #[derive(Debug)]
 struct SelectMapping {
-    value: String,
+    value: Text,
     message: Message,
 }
 
@@ -18,9 +22,9 @@ struct SelectMapping {
 #[derive(Debug)]
 pub struct SelectFormat {
     /// The name of the variable whose value should be formatted.
-    variable_name: String,
+    variable_name: Text,
     /// Given a value of a variable, this maps that to a message format.
-    mappings: Vec<SelectMapping>,
+    mappings: Box<[SelectMapping]>,
     /// The message format to use if no valid mapping is found for
     /// the variable value.
     default: Message,
@@ -30,52 +34,129 @@ impl SelectFormat {
     /// Construct a `SelectFormat`.
     pub fn new(variable_name: &str, default: Message) -> Self {
         SelectFormat {
-            variable_name: variable_name.to_string(),
-            mappings: vec![],
+            variable_name: Text::from(variable_name),
+            mappings: Box::new([]),
             default: default,
         }
     }
 
     /// Map a value for a particular message.
     pub fn map(&mut self, value: &str, message: Message) {
-        self.mappings.push(SelectMapping {
-            value: value.to_string(),
-            message: message,
-        });
+        push(
+            &mut self.mappings,
+            SelectMapping {
+                value: Text::from(value),
+                message: message,
+            },
+        );
     }
 
     /// Given a value, determine which `Message` to use.
     pub fn lookup_message(&self, value: &str) -> &Message {
         self.mappings
             .iter()
-            .find(|mapping| mapping.value == value)
+            .find(|mapping| &*mapping.value == value)
             .map_or(&self.default, |mapping| &mapping.message)
     }
 }
 
 impl MessagePart for SelectFormat {
-    fn apply_format<'f>(
+    fn apply_format(
         &self,
         ctx: &Context,
         stream: &mut fmt::Write,
-        args: Option<&Args<'f>>,
+        args: Option<&dyn Arguments>,
     ) -> fmt::Result {
-        let arg = args.and_then(|args| args.get(&self.variable_name));
-        if let Some(&Value::Str(value)) = arg.map(|a| a.value()) {
-            let message = self.lookup_message(value);
+        let arg = args.and_then(|args| args.lookup(&self.variable_name));
+        if let Some(value) = arg.and_then(|arg| arg.string(ctx.coerce_arguments)) {
+            let message = self.lookup_message(&value);
             try!(message.write_message(ctx, stream, args));
             Ok(())
         } else {
             Err(fmt::Error {})
         }
     }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let arg = args.and_then(|args| args.lookup(&self.variable_name));
+        match arg.and_then(|arg| arg.string(ctx.coerce_arguments)) {
+            Some(value) => self.lookup_message(&value).write_mapped(ctx, output, args),
+            None => Err(fmt::Error {}),
+        }
+    }
+
+    fn check_arguments(&self, ctx: &Context, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        let arg = args
+            .and_then(|args| args.lookup(&self.variable_name))
+            .ok_or_else(|| Error::missing_argument(&self.variable_name))?;
+        match arg.string(ctx.coerce_arguments) {
+            Some(value) => self.lookup_message(&value).check_arguments(ctx, args),
+            None => Err(Error::unsupported_type(&self.variable_name, "a string")),
+        }
+    }
+
+    fn collect_arguments(&self, arguments: &mut Vec<(String, ArgumentType)>) {
+        add_argument(arguments, &self.variable_name, ArgumentType::Str);
+        for mapping in self.mappings.iter() {
+            mapping.message.collect_arguments(arguments);
+        }
+        self.default.collect_arguments(arguments);
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(
+            stream,
+            "{:indent$}Select {}",
+            "",
+            &*self.variable_name,
+            indent = indent
+        )?;
+        for mapping in self.mappings.iter() {
+            writeln!(
+                stream,
+                "{:indent$}{:?}:",
+                "",
+                mapping.value,
+                indent = indent + 2
+            )?;
+            mapping.message.dump_to(stream, indent + 4)?;
+        }
+        writeln!(stream, "{:indent$}other:", "", indent = indent + 2)?;
+        self.default.dump_to(stream, indent + 4)
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self)
+            + text::heap_size(&self.variable_name)
+            + size_of_val(&*self.mappings)
+            + self
+                .mappings
+                .iter()
+                .map(|mapping| text::heap_size(&mapping.value) + mapping.message.heap_size())
+                .sum::<usize>()
+            + self.default.heap_size()
+    }
+
+    fn is_pure(&self) -> bool {
+        self.mappings
+            .iter()
+            .all(|mapping| mapping.message.is_pure())
+            && self.default.is_pure()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use icu::parse;
     use super::SelectFormat;
-    use {Context, Message};
+    use icu::ast::PlainText;
+    use icu::parse;
+    use prelude::*;
+    use {Context, Error, Message, SpanKind, Value};
 
     #[test]
     fn it_works() {
@@ -92,4 +173,74 @@ mod tests {
         let output = format_message!(ctx, &msg, type => "span");
         assert_eq!("Default", output);
     }
+
+    #[test]
+    fn errors_are_reported() {
+        let ctx = Context::default();
+        let mut fmt = SelectFormat::new("type", parse("Default").unwrap());
+        fmt.map("named", parse("{name}").unwrap());
+        let msg = Message::new(vec![Box::new(fmt)]);
+
+        let args = [("type", Value::Number(1))];
+        let error = ctx.try_format(&msg, Some(&args)).unwrap_err();
+        assert_eq!(error.code(), "unsupported-type");
+
+        let args = [("type", Value::Str("named"))];
+        match ctx.try_format(&msg, Some(&args)) {
+            Err(Error::MissingArgument { ref name }) => assert_eq!(name, "name"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn numbers_can_be_coerced() {
+        let mut fmt = SelectFormat::new("floor", parse("Upstairs").unwrap());
+        fmt.map("0", parse("Ground floor").unwrap());
+        let msg = Message::new(vec![Box::new(fmt)]);
+        let args = [("floor", Value::Number(0))];
+
+        let ctx = Context::default();
+        assert!(ctx.try_format(&msg, Some(&args)).is_err());
+        let ctx = Context {
+            coerce_arguments: true,
+            ..Context::default()
+        };
+        assert_eq!(ctx.try_format(&msg, Some(&args)).unwrap(), "Ground floor");
+    }
+
+    #[test]
+    fn spans_are_mapped() {
+        let ctx = Context::default();
+        let mut fmt = SelectFormat::new("type", parse("Default").unwrap());
+        fmt.map("named", parse("{name} and {other}").unwrap());
+        let msg = Message::new(vec![Box::new(PlainText::new("To: ")), Box::new(fmt)]);
+
+        let args = [
+            ("type", Value::Str("named")),
+            ("name", Value::Str("Ann")),
+            ("other", Value::Str("Bob")),
+        ];
+        let output = ctx.format_mapped(&msg, Some(&args));
+        assert_eq!(output.text(), "To: Ann and Bob");
+        let spans: Vec<_> = output
+            .spans()
+            .iter()
+            .map(|span| {
+                (
+                    &output.text()[span.start..span.end],
+                    &span.path[..],
+                    &span.kind,
+                )
+            })
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("To: ", &[0][..], &SpanKind::Text),
+                ("Ann", &[1, 0][..], &SpanKind::Argument("name".to_string())),
+                (" and ", &[1, 1][..], &SpanKind::Text),
+                ("Bob", &[1, 2][..], &SpanKind::Argument("other".to_string())),
+            ]
+        );
+    }
 }
@@ -6,43 +6,88 @@
 
 use std::fmt;
 
-use {Args, Context, MessagePart};
+use prelude::*;
+use schema::add_argument;
+use text::{self, Text};
+use {ArgumentType, Arguments, Context, Error, MappedOutput, MessagePart, SpanKind};
 
 /// A simple message consisting of a value to be formatted.
 #[derive(Debug)]
 pub struct SimpleFormat {
     /// The name of the variable whose value should be formatted.
-    variable_name: String,
+    variable_name: Text,
 }
 
 impl SimpleFormat {
     /// Construct a `SimpleFormat`.
     pub fn new(variable_name: &str) -> Self {
         SimpleFormat {
-            variable_name: variable_name.to_string(),
+            variable_name: Text::from(variable_name),
         }
     }
 }
 
 impl MessagePart for SimpleFormat {
-    fn apply_format<'f>(
+    fn apply_format(
         &self,
         _ctx: &Context,
         stream: &mut fmt::Write,
-        args: Option<&Args<'f>>,
+        args: Option<&dyn Arguments>,
     ) -> fmt::Result {
-        if let Some(arg) = args.and_then(|args| args.get(&self.variable_name)) {
-            try!(write!(stream, "{}", arg.value()));
+        if let Some(value) = args.and_then(|args| args.lookup(&self.variable_name)) {
+            try!(write!(stream, "{}", value));
             Ok(())
         } else {
             Err(fmt::Error {})
         }
     }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Argument(self.variable_name.to_string()));
+        Ok(())
+    }
+
+    fn check_arguments(&self, _ctx: &Context, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        match args.and_then(|args| args.lookup(&self.variable_name)) {
+            Some(_) => Ok(()),
+            None => Err(Error::missing_argument(&self.variable_name)),
+        }
+    }
+
+    fn collect_arguments(&self, arguments: &mut Vec<(String, ArgumentType)>) {
+        add_argument(arguments, &self.variable_name, ArgumentType::Any);
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(
+            stream,
+            "{:indent$}Argument {}",
+            "",
+            &*self.variable_name,
+            indent = indent
+        )
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self) + text::heap_size(&self.variable_name)
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::SimpleFormat;
+    use prelude::*;
     use {Context, Message};
 
     #[test]
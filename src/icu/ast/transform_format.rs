@@ -0,0 +1,200 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use prelude::*;
+use schema::add_argument;
+use text::{self, Text};
+use {ArgumentType, Arguments, Context, Error, MappedOutput, MessagePart, SpanKind};
+
+/// A change to the case of the value of an argument.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Transform {
+    /// Make the first letter a capital, as at the start of a sentence,
+    /// leaving the rest as it is.
+    Capitalize,
+    /// Make every letter lower case.
+    Lowercase,
+    /// Make every letter upper case.
+    Uppercase,
+}
+
+/// Whether the language of `ctx` has a dotted and a dotless `i`, each
+/// with its own capital.
+fn has_dotless_i(ctx: &Context) -> bool {
+    ctx.is_language("tr") || ctx.is_language("az")
+}
+
+/// The title case form of `c`, which differs from the upper case form
+/// only for the few letters which are digraphs, such as `ǆ`.
+fn title_case(c: char) -> Option<char> {
+    match c {
+        'Ǆ' | 'ǅ' | 'ǆ' => Some('ǅ'),
+        'Ǉ' | 'ǈ' | 'ǉ' => Some('ǈ'),
+        'Ǌ' | 'ǋ' | 'ǌ' => Some('ǋ'),
+        'Ǳ' | 'ǲ' | 'ǳ' => Some('ǲ'),
+        _ => None,
+    }
+}
+
+/// Apply `transform` to `text`, following the case rules of the language
+/// of `ctx`.
+fn apply(
+    ctx: &Context,
+    transform: Transform,
+    text: &str,
+    output: &mut dyn fmt::Write,
+) -> fmt::Result {
+    let dotless_i = has_dotless_i(ctx);
+    match transform {
+        Transform::Lowercase if dotless_i => {
+            let text = text.replace('I', "ı").replace('İ', "i");
+            output.write_str(&text.to_lowercase())
+        }
+        Transform::Lowercase => output.write_str(&text.to_lowercase()),
+        Transform::Uppercase if dotless_i => {
+            output.write_str(&text.replace('i', "İ").to_uppercase())
+        }
+        Transform::Uppercase => output.write_str(&text.to_uppercase()),
+        Transform::Capitalize => {
+            let mut chars = text.chars();
+            let first = match chars.next() {
+                Some(first) => first,
+                None => return Ok(()),
+            };
+            let rest = chars.as_str();
+            if dotless_i && first == 'i' {
+                output.write_char('İ')?;
+            } else if let Some(title) = title_case(first) {
+                output.write_char(title)?;
+            } else if ctx.is_language("nl") && first == 'i' && rest.starts_with('j') {
+                // The Dutch digraph "ij" is capitalized as a whole.
+                output.write_str("IJ")?;
+                return output.write_str(&rest[1..]);
+            } else {
+                for c in first.to_uppercase() {
+                    output.write_char(c)?;
+                }
+            }
+            output.write_str(rest)
+        }
+    }
+}
+
+/// Format the value of an argument with its case changed, such as from
+/// `{dayName, capitalize}` for a term which starts a sentence, so that
+/// a catalog does not need a second entry for it.
+#[derive(Debug)]
+pub struct TransformFormat {
+    /// The name of the variable whose value should be formatted.
+    variable_name: Text,
+    transform: Transform,
+}
+
+impl TransformFormat {
+    /// Construct a `TransformFormat`.
+    pub fn new(variable_name: &str, transform: Transform) -> Self {
+        TransformFormat {
+            variable_name: Text::from(variable_name),
+            transform,
+        }
+    }
+}
+
+impl MessagePart for TransformFormat {
+    fn apply_format(
+        &self,
+        ctx: &Context,
+        stream: &mut dyn fmt::Write,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        match args.and_then(|args| args.lookup(&self.variable_name)) {
+            Some(value) => apply(ctx, self.transform, &value.to_string(), stream),
+            None => Err(fmt::Error {}),
+        }
+    }
+
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Argument(self.variable_name.to_string()));
+        Ok(())
+    }
+
+    fn check_arguments(&self, _ctx: &Context, args: Option<&dyn Arguments>) -> Result<(), Error> {
+        match args.and_then(|args| args.lookup(&self.variable_name)) {
+            Some(_) => Ok(()),
+            None => Err(Error::missing_argument(&self.variable_name)),
+        }
+    }
+
+    fn collect_arguments(&self, arguments: &mut Vec<(String, ArgumentType)>) {
+        add_argument(arguments, &self.variable_name, ArgumentType::Any);
+    }
+
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(
+            stream,
+            "{:indent$}Transform {} {:?}",
+            "",
+            &*self.variable_name,
+            self.transform,
+            indent = indent
+        )
+    }
+
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self) + text::heap_size(&self.variable_name)
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use icu::parse;
+    use Context;
+
+    #[test]
+    fn it_works() {
+        let ctx = Context::default();
+        let m = parse("{day, capitalize}, not {day}").unwrap();
+        assert_eq!(format_message!(ctx, &m, day => "ǆuma"), "ǅuma, not ǆuma");
+        let m = parse("{a, lowercase} {b, uppercase}").unwrap();
+        assert_eq!(
+            format_message!(ctx, &m, a => "ΟΔΟΣ", b => "straße"),
+            "οδος STRASSE"
+        );
+        assert_eq!(
+            m.dump(),
+            "Message\n  Transform a Lowercase\n  Text \" \"\n  Transform b Uppercase\n"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn case_follows_language() {
+        let m = parse("{a, capitalize} {b, lowercase} {b, uppercase}").unwrap();
+        let transform = |language: &str, a, b| {
+            let ctx = Context::for_language(language);
+            format_message!(ctx, &m, a => a, b => b)
+        };
+        assert_eq!(
+            transform("tr", "istanbul", "DİYARBAKIR"),
+            "İstanbul diyarbakır DİYARBAKIR"
+        );
+        assert_eq!(transform("en", "istanbul", "Iи"), "Istanbul iи IИ");
+        assert_eq!(transform("nl", "ijsselmeer", "X"), "IJsselmeer x X");
+    }
+}
@@ -0,0 +1,538 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Information about patterns for editors and language servers.
+//!
+//! There are the completions at a cursor, the classes of tokens to
+//! highlight, the ranges which can be folded and the problems in a
+//! pattern, with the fixes which an editor can offer for them.
+//!
+//! These work on the text of a pattern as it is being written, so they
+//! do not need it to parse: an argument which has not been closed yet
+//! runs to the end of the pattern. All positions are byte offsets in
+//! the pattern.
+//!
+//! ```
+//! use message_format::icu::editor::{completions, semantic_tokens, TokenKind};
+//!
+//! let pattern = "{count, num";
+//! let labels: Vec<String> = completions(pattern, pattern.len(), &[])
+//!     .into_iter()
+//!     .map(|completion| completion.label)
+//!     .collect();
+//! assert_eq!(labels, ["number"]);
+//!
+//! let kinds: Vec<TokenKind> = semantic_tokens("Hi {name}")
+//!     .into_iter()
+//!     .map(|token| token.kind)
+//!     .collect();
+//! assert_eq!(
+//!     kinds,
+//!     [TokenKind::Text, TokenKind::Brace, TokenKind::Argument, TokenKind::Brace]
+//! );
+//! ```
+
+use std::ops::Range;
+
+use super::parse;
+use super::parse::{block_end, quoted};
+use prelude::*;
+use Error;
+
+/// The format types which can follow the name of an argument.
+const FORMAT_TYPES: [&str; 11] = [
+    "bytes",
+    "capitalize",
+    "duration",
+    "inflect",
+    "lowercase",
+    "number",
+    "ordinal",
+    "plural",
+    "quote",
+    "select",
+    "uppercase",
+];
+
+/// The styles which can follow a format type.
+fn styles(format_type: &str) -> &'static [&'static str] {
+    match format_type {
+        "number" => &["integer", "percent", "::"],
+        "duration" => &["clock", "clock-hours", "long"],
+        "bytes" => &["decimal", "binary"],
+        _ => &[],
+    }
+}
+
+/// What a [`Token`] is.
+///
+/// [`Token`]: struct.Token.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TokenKind {
+    /// Text which is written as it is.
+    Text,
+    /// A brace opening or closing an argument.
+    Brace,
+    /// The name of an argument.
+    Argument,
+    /// The id of a message which is referred to, with its `@`.
+    Reference,
+    /// A comma between the parts of an argument.
+    Comma,
+    /// The format type of an argument, such as `number`.
+    FormatType,
+    /// The style of an argument, such as `percent`.
+    Style,
+}
+
+/// A classified piece of a pattern, for highlighting it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Token {
+    /// Where the token is in the pattern.
+    pub range: Range<usize>,
+    /// What the token is.
+    pub kind: TokenKind,
+}
+
+/// What a [`Completion`] would insert.
+///
+/// [`Completion`]: struct.Completion.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompletionKind {
+    /// The name of an argument.
+    Argument,
+    /// A format type.
+    FormatType,
+    /// A style of the format type before it.
+    Style,
+}
+
+/// Something which could be written at the cursor.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Completion {
+    /// The text to write.
+    pub label: String,
+    /// What the text is.
+    pub kind: CompletionKind,
+    /// The part of the pattern which the text replaces, which is what
+    /// has been written of it so far.
+    pub range: Range<usize>,
+}
+
+/// An argument in a pattern, from its opening brace to its closing
+/// brace, or to the end of the pattern if it is not closed.
+struct Block {
+    start: usize,
+    end: usize,
+    closed: bool,
+}
+
+/// The top-level arguments of a pattern, each with the messages of a
+/// `plural` or `select` inside of it, skipping over quoted text.
+fn blocks(pattern: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut chars = pattern.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\'' => quoted(&mut chars, false, &mut String::new()),
+            '{' => {
+                let (end, closed) = match block_end(pattern, start + 1, false) {
+                    Some(close) => (close, true),
+                    None => (pattern.len(), false),
+                };
+                blocks.push(Block { start, end, closed });
+                while chars.peek().is_some_and(|&(next, _)| next <= end) {
+                    chars.next();
+                }
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// The parts of the inside of a block, which starts at `offset`, split
+/// at commas, with the range of each without its surrounding space.
+fn segments(text: &str, offset: usize) -> Vec<Range<usize>> {
+    let mut segments = vec![];
+    let mut start = 0;
+    for part in text.split(',') {
+        let leading = part.len() - part.trim_start().len();
+        let trimmed = part.trim();
+        let begin = offset + start + leading;
+        segments.push(begin..begin + trimmed.len());
+        start += part.len() + 1;
+    }
+    segments
+}
+
+/// Classify the pieces of a pattern, in order, for highlighting it.
+/// Space inside of an argument is not part of any token.
+pub fn semantic_tokens(pattern: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let token = |range: Range<usize>, kind| Token { range, kind };
+    let mut text_start = 0;
+    for block in blocks(pattern) {
+        if block.start > text_start {
+            tokens.push(token(text_start..block.start, TokenKind::Text));
+        }
+        tokens.push(token(block.start..block.start + 1, TokenKind::Brace));
+        let inside = &pattern[block.start + 1..block.end];
+        let commas: Vec<usize> = inside
+            .match_indices(',')
+            .map(|(comma, _)| block.start + 1 + comma)
+            .collect();
+        for (index, range) in segments(inside, block.start + 1).into_iter().enumerate() {
+            if index > 0 {
+                let comma = commas[index - 1];
+                tokens.push(token(comma..comma + 1, TokenKind::Comma));
+            }
+            if range.start == range.end {
+                continue;
+            }
+            let kind = match index {
+                0 if pattern[range.clone()].starts_with('@') => TokenKind::Reference,
+                0 => TokenKind::Argument,
+                1 => TokenKind::FormatType,
+                _ => TokenKind::Style,
+            };
+            tokens.push(token(range, kind));
+        }
+        if block.closed {
+            tokens.push(token(block.end..block.end + 1, TokenKind::Brace));
+        }
+        text_start = block.end + 1;
+    }
+    if text_start < pattern.len() {
+        tokens.push(token(text_start..pattern.len(), TokenKind::Text));
+    }
+    tokens
+}
+
+/// What could be written at `offset` in `pattern`, given the names of
+/// the arguments which the message is formatted with, such as from the
+/// `arguments` of the message in the source language.
+///
+/// In the name of an argument, this is the names which start with what
+/// has been written of it. After its first comma, it is the format
+/// types, and after its second comma, the styles of its format type.
+/// Outside of an argument there is nothing to complete, and neither is
+/// there after `plural` or `select`, which do not take branches yet.
+pub fn completions(pattern: &str, offset: usize, arguments: &[&str]) -> Vec<Completion> {
+    let block = match blocks(pattern)
+        .into_iter()
+        .find(|block| block.start < offset && offset <= block.end)
+    {
+        Some(block) => block,
+        None => return vec![],
+    };
+    let inside = &pattern[block.start + 1..block.end];
+    let segments = segments(inside, block.start + 1);
+    let index = pattern[block.start + 1..offset].matches(',').count();
+    let segment = &segments[index];
+    let start = segment.start.min(offset);
+    let typed = &pattern[start..offset];
+    let (kind, candidates): (CompletionKind, Vec<&str>) = match index {
+        0 => (CompletionKind::Argument, arguments.to_vec()),
+        1 => (CompletionKind::FormatType, FORMAT_TYPES.to_vec()),
+        2 => {
+            let format_type = &pattern[segments[1].clone()];
+            (CompletionKind::Style, styles(format_type).to_vec())
+        }
+        _ => return vec![],
+    };
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(typed))
+        .map(|candidate| Completion {
+            label: candidate.to_string(),
+            kind,
+            range: start..offset,
+        })
+        .collect()
+}
+
+/// The ranges of the arguments which span more than one line, from
+/// their opening brace through to their closing brace, which an editor
+/// can fold away.
+pub fn folding_ranges(pattern: &str) -> Vec<Range<usize>> {
+    blocks(pattern)
+        .into_iter()
+        .filter(|block| pattern[block.start..block.end].contains('\n'))
+        .map(|block| {
+            let end = if block.closed {
+                block.end + 1
+            } else {
+                block.end
+            };
+            block.start..end
+        })
+        .collect()
+}
+
+/// A change to a pattern which repairs a [`Diagnostic`].
+///
+/// [`Diagnostic`]: struct.Diagnostic.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Fix {
+    /// A description of the change, to show to the user.
+    pub title: String,
+    /// The part of the pattern which is replaced.
+    pub range: Range<usize>,
+    /// The text which replaces it.
+    pub replacement: String,
+}
+
+impl Fix {
+    /// Make the change to the pattern that it was found in.
+    pub fn apply(&self, pattern: &str) -> String {
+        let mut output = String::with_capacity(pattern.len() + self.replacement.len());
+        output.push_str(&pattern[..self.range.start]);
+        output.push_str(&self.replacement);
+        output.push_str(&pattern[self.range.end..]);
+        output
+    }
+}
+
+/// A problem in a pattern, with the changes which would repair it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// The part of the pattern with the problem.
+    pub range: Range<usize>,
+    /// A description of the problem.
+    pub message: String,
+    /// The changes which would repair it, if any can be made without
+    /// knowing what was meant.
+    pub fixes: Vec<Fix>,
+}
+
+/// Find the problems in a pattern, given the names of the arguments
+/// which the message is formatted with. Unlike [`parse`], this finds
+/// one problem in each argument rather than stopping at the first.
+///
+/// An argument which is not closed can be closed, an empty argument can
+/// be removed, and a misspelled format type, style or argument name can
+/// be changed to the one it is closest to:
+///
+/// ```
+/// use message_format::icu::editor::diagnostics;
+///
+/// let pattern = "{count, numbr} new messages for {nmae}";
+/// let found = diagnostics(pattern, &["count", "name"]);
+/// assert_eq!(found.len(), 2);
+/// assert_eq!(found[1].message, "Unknown argument `nmae`");
+/// let fixed = found[0].fixes[0].apply(pattern);
+/// assert_eq!(fixed, "{count, number} new messages for {nmae}");
+/// ```
+///
+/// Names are only checked when `arguments` is not empty.
+///
+/// [`parse`]: ../fn.parse.html
+pub fn diagnostics(pattern: &str, arguments: &[&str]) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for block in blocks(pattern) {
+        if !block.closed {
+            diagnostics.push(Diagnostic {
+                range: block.start..pattern.len(),
+                message: "Expected '}'".to_string(),
+                fixes: vec![Fix {
+                    title: "Close the argument".to_string(),
+                    range: pattern.len()..pattern.len(),
+                    replacement: "}".to_string(),
+                }],
+            });
+            continue;
+        }
+        let inside = &pattern[block.start + 1..block.end];
+        let segments = segments(inside, block.start + 1);
+        if let Err(Error::Parse { position, message }) = parse(&pattern[block.start..=block.end]) {
+            let position = (block.start + position).min(block.end);
+            let index = pattern[block.start + 1..position].matches(',').count();
+            let segment = segments[index].clone();
+            let (range, fixes) = if inside.trim().is_empty() {
+                let fix = Fix {
+                    title: "Remove the empty argument".to_string(),
+                    range: block.start..block.end + 1,
+                    replacement: String::new(),
+                };
+                (block.start..block.end + 1, vec![fix])
+            } else {
+                let text = &pattern[segment.clone()];
+                let fixes = match index {
+                    1 => suggest(text, &FORMAT_TYPES, segment.clone()),
+                    2 => suggest(text, styles(&pattern[segments[1].clone()]), segment.clone()),
+                    _ => vec![],
+                };
+                (segment, fixes)
+            };
+            diagnostics.push(Diagnostic {
+                range,
+                message,
+                fixes,
+            });
+            continue;
+        }
+        let name = &pattern[segments[0].clone()];
+        if !arguments.is_empty() && !name.starts_with('@') && !arguments.contains(&name) {
+            diagnostics.push(Diagnostic {
+                range: segments[0].clone(),
+                message: format!("Unknown argument `{}`", name),
+                fixes: suggest(name, arguments, segments[0].clone()),
+            });
+        }
+    }
+    diagnostics
+}
+
+/// The fix which changes `text` at `range` to the closest of
+/// `candidates`, if one is close enough to be a misspelling of it.
+fn suggest(text: &str, candidates: &[&str], range: Range<usize>) -> Vec<Fix> {
+    candidates
+        .iter()
+        .map(|candidate| (distance(text, candidate), candidate))
+        .filter(|&(distance, candidate)| distance <= 2 && distance < candidate.len())
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| Fix {
+            title: format!("Change to `{}`", candidate),
+            range,
+            replacement: candidate.to_string(),
+        })
+        .into_iter()
+        .collect()
+}
+
+/// The number of characters which must be inserted, removed or changed
+/// to turn `a` into `b`.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &b) in b.iter().enumerate() {
+            let changed = previous[j] + if a == b { 0 } else { 1 };
+            current.push(changed.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        completions, diagnostics, folding_ranges, semantic_tokens, CompletionKind, Token, TokenKind,
+    };
+    use prelude::*;
+
+    #[test]
+    fn tokens_cover_the_pattern() {
+        let pattern = "Hi { n , number, ::percent}{@app}{x,";
+        let tokens: Vec<(&str, TokenKind)> = semantic_tokens(pattern)
+            .into_iter()
+            .map(|token: Token| (&pattern[token.range], token.kind))
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                ("Hi ", TokenKind::Text),
+                ("{", TokenKind::Brace),
+                ("n", TokenKind::Argument),
+                (",", TokenKind::Comma),
+                ("number", TokenKind::FormatType),
+                (",", TokenKind::Comma),
+                ("::percent", TokenKind::Style),
+                ("}", TokenKind::Brace),
+                ("{", TokenKind::Brace),
+                ("@app", TokenKind::Reference),
+                ("}", TokenKind::Brace),
+                ("{", TokenKind::Brace),
+                ("x", TokenKind::Argument),
+                (",", TokenKind::Comma),
+            ]
+        );
+    }
+
+    #[test]
+    fn completions_follow_the_cursor() {
+        let labels = |pattern: &str, offset| -> Vec<(String, CompletionKind)> {
+            completions(pattern, offset, &["count", "city"])
+                .into_iter()
+                .map(|completion| (completion.label, completion.kind))
+                .collect()
+        };
+        let argument = |label: &str| (label.to_string(), CompletionKind::Argument);
+        assert_eq!(labels("{c}", 2), [argument("count"), argument("city")]);
+        assert_eq!(labels("{ci}", 3), [argument("city")]);
+        assert_eq!(labels("Hi {c}", 2), []);
+        assert_eq!(
+            labels("{n, d", 5),
+            [("duration".to_string(), CompletionKind::FormatType)]
+        );
+        assert_eq!(
+            labels("{n, bytes, }", 11),
+            [
+                ("decimal".to_string(), CompletionKind::Style),
+                ("binary".to_string(), CompletionKind::Style),
+            ]
+        );
+        assert_eq!(completions("{n, num", 7, &[])[0].range, 4..7);
+    }
+
+    #[test]
+    fn arguments_over_lines_fold() {
+        let pattern = "a {b} {c,\n number} {d,\n";
+        assert_eq!(folding_ranges(pattern), [6..18, 19..pattern.len()]);
+    }
+
+    #[test]
+    fn diagnostics_have_fixes() {
+        let fixed = |pattern: &str| -> Vec<(String, Vec<String>)> {
+            diagnostics(pattern, &["count", "size"])
+                .into_iter()
+                .map(|diagnostic| {
+                    let fixes = diagnostic
+                        .fixes
+                        .iter()
+                        .map(|fix| fix.apply(pattern))
+                        .collect();
+                    (diagnostic.message, fixes)
+                })
+                .collect()
+        };
+        assert_eq!(fixed("{count} of {size, bytes}"), []);
+        assert_eq!(
+            fixed("{count, plural, one {# '{'} other {{size} more}} '{x' isn''t {size}"),
+            []
+        );
+        assert_eq!(
+            fixed("a {} b {size, bytes, binray}"),
+            [
+                (
+                    "Expected an argument name".to_string(),
+                    vec!["a  b {size, bytes, binray}".to_string()]
+                ),
+                (
+                    "Expected 'decimal' or 'binary'".to_string(),
+                    vec!["a {} b {size, bytes, binary}".to_string()]
+                ),
+            ]
+        );
+        assert_eq!(
+            fixed("{cuont, date} {count"),
+            [
+                (
+                    "Expected a format type such as 'plural', 'select' or 'number'".to_string(),
+                    vec![]
+                ),
+                (
+                    "Expected '}'".to_string(),
+                    vec!["{cuont, date} {count}".to_string()]
+                ),
+            ]
+        );
+        assert_eq!(fixed("{cuont}")[0].1, ["{count}"]);
+    }
+}
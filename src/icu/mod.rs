@@ -11,7 +11,8 @@
 //! environments.
 //!
 //! This library aims to support all of the ICU Message Format with the
-//! exception of the deprecated `ChoiceFormat`.
+//! exception of the deprecated `ChoiceFormat`. Patterns which use it can
+//! be migrated to `plural` and `select` with [`convert::choice`].
 //!
 //! The important functionality provided here is the [`icu::parse`]
 //! function which generates [`Message`] from a string.
@@ -35,19 +36,192 @@
 //! "Connecting to {application} on {host}..."
 //! ```
 //!
+//! An apostrophe before a brace quotes the text up to the next
+//! apostrophe, so that braces can be written in the text, and two
+//! apostrophes are written as one. Other apostrophes are kept as they
+//! are:
+//!
+//! ```text
+//! "Don't write '{'{name}'}' as ''{name}''"
+//! ```
+//!
 //! ## Pluralized Messages
 //!
-//! Parsing of `plural` messages is not yet supported.
+//! A `plural` argument chooses a message by the plural category of a
+//! number, with the rules of French, Czech or Slovak in those
+//! languages and those of English otherwise. A branch for an exact
+//! value is written as `=N`, and `#` in a branch is the number, less
+//! any `offset`:
+//!
+//! ```text
+//! "{count, plural, =0 {No files} one {# file} other {# files}}"
+//! "{guests, plural, offset:1 =0 {Nobody} one {{host} and # other} other {{host} and # others}}"
+//! ```
+//!
+//! In a branch of a `plural`, an apostrophe before `#` quotes it as
+//! well, as in `'#'1`.
 //!
 //! ## Select Messages
 //!
-//! Parsing of `select` messages is not yet supported.
+//! A `select` argument chooses a message by the value of a string, or
+//! its `other` branch for any other value:
+//!
+//! ```text
+//! "{pronoun, select, she {her files} he {his files} other {their files}}"
+//! ```
+//!
+//! ## Numbers
+//!
+//! A `number` argument is written with the digit grouping of the
+//! language being localized for:
+//!
+//! ```text
+//! "{count, number} files"
+//! ```
+//!
+//! The number may be a `Value::Float`, which is written with the
+//! decimal separator of the language and at most 15 significant
+//! digits, so that `0.1 + 0.2` is written as `0.3`. An `i128` or `u128`
+//! keeps all of its digits, and so does a `Value::Decimal`, such as an
+//! amount of money, along with the digits after its separator.
+//! Infinity is written as `∞` and a value which is not a number with
+//! the symbol of the language, unless `Context::non_finite_numbers`
+//! says to fail instead. A `plural` chooses its `other` branch for
+//! these and for numbers too large for its rules, but cannot write
+//! them with `#`. It chooses the branch for a decimal by the digits
+//! before and after its separator, as in CLDR, so that `1.5` is `one`
+//! in French, but cannot write a decimal with a fraction with `#`
+//! either.
+//!
+//! Its style may be `integer`, `percent` or an ICU number skeleton
+//! after `::`. The skeleton tokens `currency/XXX`, `unit-width-short`,
+//! `unit-width-iso-code`, `unit-width-full-name`, `group-off`, `percent`
+//! and `scale/N` are supported, so an amount can be written with the
+//! name of its currency in the right plural form:
+//!
+//! ```text
+//! "Price: {price, number, ::currency/USD unit-width-full-name}"
+//! ```
+//!
+//! The number is rounded to a whole number by `precision-integer` and
+//! by the `integer` and `percent` styles, and to a fixed number of
+//! digits after the separator by a token such as `.00`. Each `#` after
+//! the zeros allows one more digit, which is left out if it is zero:
+//!
+//! ```text
+//! "Total: {total, number, ::.00}, average: {average, number, ::.0##}"
+//! ```
+//!
+//! The `percent` skeleton token writes the number with the percent
+//! sign of the language, as in `50%` or `50 %`. A ratio is multiplied
+//! by 100 with `scale/100`, which is what the `percent` style does:
+//!
+//! ```text
+//! "{done, number, ::percent} done, {ratio, number, percent} of all"
+//! ```
+//!
+//! An `ordinal` argument is a number with the ordinal suffix of the
+//! language, such as "1st", "2nd" and "3rd" in English:
+//!
+//! ```text
+//! "You finished {place, ordinal}."
+//! ```
+//!
+//! ## Quotations
+//!
+//! A `quote` argument is written inside of the quotation marks of the
+//! language being localized for, such as “…” in English, „…“ in German
+//! and « … » in French:
+//!
+//! ```text
+//! "Saved {title, quote}"
+//! ```
+//!
+//! ## References
+//!
+//! A message in a [`Catalog`] can include another message of the
+//! catalog by its key, such as the name of the application, which is
+//! formatted with the same arguments by [`Catalog::format`]:
+//!
+//! ```text
+//! "Welcome to {@app.name}, {name}!"
+//! ```
+//!
+//! The reference may also be written as `{@msg app.name}`, which is
+//! what [`convert::i18next`] writes for the nesting of i18next.
+//!
+//! ## Case
+//!
+//! `capitalize`, `lowercase` and `uppercase` change the case of the
+//! value of an argument with the rules of the language being localized
+//! for, such as the dotted capital `İ` of Turkish, so that a term at the
+//! start of a sentence does not need a second entry in the catalog:
+//!
+//! ```text
+//! "{dayName, capitalize} is fully booked."
+//! ```
+//!
+//! ## Inflections
+//!
+//! An `inflect` argument writes a word in a grammatical case, such as
+//! the name of a city in the locative for "in Prague" in Czech. The
+//! forms come from the [`Inflections`] that the application gives the
+//! `Context`, and a word without the form is written as it is given:
+//!
+//! ```text
+//! "Bydlím v {city, inflect, locative}."
+//! ```
+//!
+//! ## Durations and Sizes
+//!
+//! A `duration` argument takes a `Value::Duration`, such as one made
+//! from a `std::time::Duration`, or a number of seconds. It is written
+//! as a clock, like `4:05` or `1:04:05`, by default. The style
+//! `clock-hours` always shows the hours, and `long` writes the units
+//! out in the language being localized for:
+//!
+//! ```text
+//! "{position, duration} / {length, duration, clock-hours}"
+//! "Ready in {left, duration, long}"
+//! ```
+//!
+//! A `bytes` argument is a number of bytes, written as a size such as
+//! "1.5 MB", or "1,5 Mo" in French. The style `binary` uses powers of
+//! 1024, as in "1.5 MiB", instead of powers of 1000:
+//!
+//! ```text
+//! "Downloaded {done, bytes} of {total, bytes}"
+//! ```
+//!
+//! ## Pseudo-localization
+//!
+//! [`pseudo_localize`] rewrites the text of a pattern with accented
+//! letters, as in "[Ĥééļļöö, {name}!]", so that text which is not
+//! localized and layouts which can not take longer translations show up
+//! before there are any translations.
+//!
+//! ## Editors
+//!
+//! The [`editor` module] gives editors and language servers what they
+//! need to work on patterns which are still being written, such as
+//! the completions at the cursor, the classes of tokens to highlight
+//! and the problems in a pattern with fixes which can be applied.
 //!
+//! [`editor` module]: editor/index.html
 //! [`icu::parse`]: fn.parse.html
+//! [`pseudo_localize`]: fn.pseudo_localize.html
+//! [`Inflections`]: ../struct.Inflections.html
+//! [`Catalog`]: ../struct.Catalog.html
+//! [`Catalog::format`]: ../struct.Catalog.html#method.format
+//! [`convert::choice`]: ../convert/choice/index.html
+//! [`convert::i18next`]: ../convert/i18next/index.html
 //! [`Message`]: ../struct.Message.html
 //! [ICU-style message formatting]: http://userguide.icu-project.org/formatparse/messages
 
 pub mod ast;
+pub mod editor;
 mod parse;
+mod pseudo;
 
-pub use self::parse::parse;
+pub use self::parse::{parse, parse_bytes};
+pub use self::pseudo::pseudo_localize;
@@ -4,110 +4,517 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::error::Error;
-use std::fmt;
-use std::str;
+use std::iter::Peekable;
+use std::mem;
+use std::str::CharIndices;
 
-use nom::{multispace, IResult};
+use super::ast::{self, Transform};
+use encoding;
+use prelude::*;
+use {ByteUnits, DurationStyle, Error, Message, MessagePart, NumberOptions};
 
-use super::ast;
-use {Message, MessagePart};
+/// Parse a format, which starts after the '{' and extends through
+/// to the matching '}'.
+///
+/// The variable name is the first thing in the format and extends to
+/// the first ',' or '}'. '{name}' has a variable name of 'name'.
+/// '{@id}' refers to the message 'id' instead of an argument, as does
+/// the longer '{@msg id}'.
+/// The `position` is that of the format within the message, and
+/// `plural` is whether it is within a branch of a `plural`, where
+/// `#` is the number.
+fn format(position: usize, format: &str, plural: bool) -> Result<Box<MessagePart>, Error> {
+    let (name, kind) = match format.find(',') {
+        Some(comma) => (&format[..comma], Some(&format[comma + 1..])),
+        None => (format, None),
+    };
+    if name.is_empty() {
+        return Err(Error::parse(position, "Expected an argument name"));
+    }
+    if let Some(id) = name.strip_prefix('@') {
+        let id = id.trim();
+        let id = match id.strip_prefix("msg") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => rest.trim_start(),
+            _ => id,
+        };
+        return match kind {
+            None if !id.is_empty() => Ok(Box::new(ast::ReferenceFormat::new(id))),
+            None => Err(Error::parse(position + 1, "Expected a message id")),
+            Some(_) => Err(Error::parse(position + name.len(), "Expected '}'")),
+        };
+    }
+    let kind_position = position + name.len() + 1;
+    let (kind, style) = match kind {
+        None => return Ok(Box::new(ast::SimpleFormat::new(name))),
+        Some(kind) => match kind.find(',') {
+            Some(comma) => (
+                kind[..comma].trim(),
+                Some((kind_position + comma + 1, &kind[comma + 1..])),
+            ),
+            None => (kind.trim(), None),
+        },
+    };
+    match (kind, style) {
+        ("plural", None) | ("select", None) => Ok(Box::new(ast::SimpleFormat::new(name))),
+        ("plural", Some((style_position, style))) => plural_format(name, style_position, style),
+        ("select", Some((style_position, style))) => {
+            select_format(name, style_position, style, plural)
+        }
+        ("number", None) => Ok(Box::new(ast::NumberFormat::new(
+            name,
+            NumberOptions::default(),
+        ))),
+        ("number", Some((style_position, style))) => {
+            let options = number_style(style_position, style)?;
+            Ok(Box::new(ast::NumberFormat::new(name, options)))
+        }
+        ("ordinal", None) => Ok(Box::new(ast::OrdinalFormat::new(name))),
+        ("duration", None) => Ok(Box::new(ast::DurationFormat::new(
+            name,
+            DurationStyle::Clock,
+        ))),
+        ("duration", Some((style_position, style))) => {
+            let style = duration_style(style_position, style)?;
+            Ok(Box::new(ast::DurationFormat::new(name, style)))
+        }
+        ("bytes", None) => Ok(Box::new(ast::BytesFormat::new(name, ByteUnits::Decimal))),
+        ("bytes", Some((style_position, style))) => {
+            let units = bytes_style(style_position, style)?;
+            Ok(Box::new(ast::BytesFormat::new(name, units)))
+        }
+        ("quote", None) => Ok(Box::new(ast::QuoteFormat::new(name))),
+        ("capitalize", None) => Ok(Box::new(ast::TransformFormat::new(
+            name,
+            Transform::Capitalize,
+        ))),
+        ("lowercase", None) => Ok(Box::new(ast::TransformFormat::new(
+            name,
+            Transform::Lowercase,
+        ))),
+        ("uppercase", None) => Ok(Box::new(ast::TransformFormat::new(
+            name,
+            Transform::Uppercase,
+        ))),
+        ("inflect", Some((_, case))) if !case.trim().is_empty() => {
+            Ok(Box::new(ast::InflectFormat::new(name, case.trim())))
+        }
+        ("inflect", _) => Err(Error::parse(
+            position + format.len(),
+            "Expected the name of a case",
+        )),
+        _ => Err(Error::parse(
+            kind_position,
+            "Expected a format type such as 'plural', 'select' or 'number'",
+        )),
+    }
+}
 
-/// An error resulting from `parse`.
-#[derive(Clone, Debug)]
-pub enum ParseError {
-    /// The message could not be parsed.
-    NotImplemented,
+/// Parse the style of a `number` format, which is `integer`, `percent`
+/// or a number skeleton after `::`, found at `position` in the message.
+///
+/// As in ICU, the `percent` style is for a ratio, which is multiplied
+/// by 100, unlike the `percent` skeleton token, and both styles round
+/// the number to a whole number.
+fn number_style(position: usize, style: &str) -> Result<NumberOptions, Error> {
+    let trimmed = style.trim_start();
+    let position = position + style.len() - trimmed.len();
+    let trimmed = trimmed.trim_end();
+    match trimmed {
+        "integer" => {
+            return Ok(NumberOptions {
+                maximum_fraction_digits: Some(0),
+                ..NumberOptions::default()
+            })
+        }
+        "percent" => {
+            return Ok(NumberOptions {
+                percent: true,
+                scale: 100,
+                maximum_fraction_digits: Some(0),
+                ..NumberOptions::default()
+            })
+        }
+        _ => {}
+    }
+    match trimmed.strip_prefix("::") {
+        Some(skeleton) => {
+            NumberOptions::from_skeleton(skeleton).map_err(|error| error.offset(position + 2))
+        }
+        None => Err(Error::parse(
+            position,
+            "Expected 'integer', 'percent' or a '::' skeleton",
+        )),
+    }
+}
+
+/// Parse the branches of a `plural` format, found at `position` in the
+/// message, such as `offset:1 =0 {nobody} one {# other} other {# others}`.
+fn plural_format(name: &str, position: usize, style: &str) -> Result<Box<dyn MessagePart>, Error> {
+    let trimmed = style.trim_start();
+    let (offset, position, style) = match trimmed.strip_prefix("offset:") {
+        Some(rest) => {
+            let digits_position = position + style.len() - rest.len();
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let offset = rest[..end]
+                .parse()
+                .map_err(|_| Error::parse(digits_position, "Expected a whole number offset"))?;
+            (offset, digits_position + end, &rest[end..])
+        }
+        None => (0, position, style),
+    };
+    let mut branches = branches(position, style, true)?;
+    let other = match branches.iter().position(|branch| branch.0 == "other") {
+        Some(index) => branches.remove(index).2,
+        None => return Err(Error::parse(position + style.len(), "Expected 'other'")),
+    };
+    let mut format = ast::PluralFormat::new(name, other);
+    format.offset(offset);
+    let mut literals = vec![];
+    for (selector, selector_position, message) in branches {
+        match selector {
+            "zero" => format.zero(message),
+            "one" => format.one(message),
+            "two" => format.two(message),
+            "few" => format.few(message),
+            "many" => format.many(message),
+            _ => match selector.strip_prefix('=').map(str::parse) {
+                Some(Ok(literal)) if literals.contains(&literal) => {
+                    return Err(Error::parse(
+                        selector_position,
+                        "Expected a selector which is not repeated",
+                    ))
+                }
+                Some(Ok(literal)) => {
+                    literals.push(literal);
+                    format.literal(literal, message)
+                }
+                Some(Err(_)) => {
+                    return Err(Error::parse(
+                        selector_position + 1,
+                        "Expected a whole number",
+                    ))
+                }
+                None => {
+                    return Err(Error::parse(
+                        selector_position,
+                        "Expected '=N' or a plural category such as 'one' or 'other'",
+                    ))
+                }
+            },
+        }
+    }
+    Ok(Box::new(format))
+}
+
+/// Parse the branches of a `select` format, found at `position` in the
+/// message, such as `female {her} male {his} other {their}`.
+fn select_format(
+    name: &str,
+    position: usize,
+    style: &str,
+    plural: bool,
+) -> Result<Box<dyn MessagePart>, Error> {
+    let mut branches = branches(position, style, plural)?;
+    let other = match branches.iter().position(|branch| branch.0 == "other") {
+        Some(index) => branches.remove(index).2,
+        None => return Err(Error::parse(position + style.len(), "Expected 'other'")),
+    };
+    let mut format = ast::SelectFormat::new(name, other);
+    for (selector, _, message) in branches {
+        format.map(selector, message);
+    }
+    Ok(Box::new(format))
 }
 
-impl Error for ParseError {
-    fn description(&self) -> &str {
-        match *self {
-            ParseError::NotImplemented => "Not implemented.",
+/// Parse the `selector {message}` branches of a `plural` or `select`
+/// format, found at `position` in the message, along with the position
+/// of each selector. `plural` is whether `#` is the number in the
+/// messages.
+fn branches(
+    position: usize,
+    style: &str,
+    plural: bool,
+) -> Result<Vec<(&str, usize, Message)>, Error> {
+    let mut branches = vec![];
+    let mut rest = style;
+    loop {
+        let trimmed = rest.trim_start();
+        let selector_position = position + style.len() - trimmed.len();
+        if trimmed.is_empty() {
+            return Ok(branches);
+        }
+        let end = trimmed
+            .find(|c: char| c.is_whitespace() || c == '{')
+            .unwrap_or(trimmed.len());
+        let selector = &trimmed[..end];
+        let after = trimmed[end..].trim_start();
+        let open = position + style.len() - after.len();
+        if selector.is_empty() {
+            return Err(Error::parse(selector_position, "Expected a selector"));
+        }
+        if !after.starts_with('{') {
+            return Err(Error::parse(open, "Expected '{'"));
         }
+        if branches
+            .iter()
+            .any(|branch: &(&str, usize, Message)| branch.0 == selector)
+        {
+            return Err(Error::parse(
+                selector_position,
+                "Expected a selector which is not repeated",
+            ));
+        }
+        let close = message_end(after, 1, plural)
+            .ok_or_else(|| Error::parse(position + style.len(), "Expected '}'"))?;
+        let message = to_message(scan(&after[1..close], open + 1, plural)?, plural)?;
+        branches.push((selector, selector_position, message));
+        rest = &after[close + 1..];
     }
 }
 
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        self.description().fmt(f)
+/// Parse the style of a `duration` format, found at `position` in the
+/// message.
+fn duration_style(position: usize, style: &str) -> Result<DurationStyle, Error> {
+    let trimmed = style.trim_start();
+    let position = position + style.len() - trimmed.len();
+    match trimmed.trim_end() {
+        "clock" => Ok(DurationStyle::Clock),
+        "clock-hours" => Ok(DurationStyle::ClockHours),
+        "long" => Ok(DurationStyle::Long),
+        _ => Err(Error::parse(
+            position,
+            "Expected 'clock', 'clock-hours' or 'long'",
+        )),
     }
 }
 
-/// Given a name, create a `SimpleFormat`.
-fn mk_simple(name: &str) -> Box<MessagePart> {
-    Box::new(ast::SimpleFormat::new(name))
+/// Parse the style of a `bytes` format, found at `position` in the
+/// message.
+fn bytes_style(position: usize, style: &str) -> Result<ByteUnits, Error> {
+    let trimmed = style.trim_start();
+    let position = position + style.len() - trimmed.len();
+    match trimmed.trim_end() {
+        "decimal" => Ok(ByteUnits::Decimal),
+        "binary" => Ok(ByteUnits::Binary),
+        _ => Err(Error::parse(position, "Expected 'decimal' or 'binary'")),
+    }
 }
 
-/// This grabs the variable name from a format, which is
-/// the first thing after the '{' and extends to the first
-/// ',' or '}'.
+/// A piece of a message before it has been turned into parts.
+pub(super) enum Piece<'a> {
+    /// Plain text, which extends up through to the start of the next
+    /// format block, with its quoting undone.
+    Text(String),
+    /// The position of a format block after its opening brace, and its
+    /// contents without the braces.
+    Format(usize, &'a str),
+    /// A `#` in a branch of a `plural`, which is the number.
+    Placeholder,
+}
+
+/// Split a message into its pieces. There must be at least one piece
+/// for now.
+///
+/// As in ICU, an apostrophe before a brace quotes the text up to the
+/// next apostrophe, so that `'{'` is a literal brace, and two
+/// apostrophes are one apostrophe. Any other apostrophe is kept as it
+/// is, so that "Don't" does not need to be written as "Don''t".
+pub(super) fn pieces(message: &str) -> Result<Vec<Piece<'_>>, Error> {
+    let pieces = scan(message, 0, false)?;
+    if pieces.is_empty() {
+        return Err(Error::parse(0, "Expected a message"));
+    }
+    Ok(pieces)
+}
+
+/// Split a message found at `position` into its pieces. `plural` is
+/// whether the message is a branch of a `plural`, where `#` is the
+/// number and an apostrophe before it quotes it as well.
+fn scan(message: &str, position: usize, plural: bool) -> Result<Vec<Piece<'_>>, Error> {
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let mut chars = message.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\'' => quoted(&mut chars, plural, &mut text),
+            '{' => {
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(mem::take(&mut text)));
+                }
+                let end = block_end(message, offset + 1, plural)
+                    .ok_or_else(|| Error::parse(position + message.len(), "Expected '}'"))?;
+                pieces.push(Piece::Format(
+                    position + offset + 1,
+                    &message[offset + 1..end],
+                ));
+                while chars.peek().is_some_and(|&(next, _)| next <= end) {
+                    chars.next();
+                }
+            }
+            '#' if plural => {
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(mem::take(&mut text)));
+                }
+                pieces.push(Piece::Placeholder);
+            }
+            _ => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    Ok(pieces)
+}
+
+/// Read the text quoted by an apostrophe which `chars` has just given,
+/// adding it to `text` with its quoting undone.
 ///
-/// '{name}' has a variable name of 'name'.
-named!(variable_name <&str, &str>, is_not_s!(",}"));
-
-/// A simple format has only a name, delimited by braces.
-named!(simple_format <&str, Box<MessagePart> >,
-    map!(
-        delimited!(
-            tag_s!("{"),
-            variable_name,
-            tag_s!("}")),
-        mk_simple));
-
-named!(plural_format <&str, Box<MessagePart> >,
-    delimited!(
-        tag_s!("{"),
-        do_parse!(
-            name: variable_name >>
-            tag_s!(",") >> opt!(multispace) >>
-            tag_s!("plural") >> opt!(multispace) >>
-            (Box::new(ast::SimpleFormat::new(name)))),
-        tag_s!("}")));
-
-named!(select_format <&str, Box<MessagePart> >,
-    delimited!(
-        tag_s!("{"),
-        do_parse!(
-            name: variable_name >>
-            tag_s!(",") >> opt!(multispace) >>
-            tag_s!("select") >> opt!(multispace) >>
-            (Box::new(ast::SimpleFormat::new(name)))),
-        tag_s!("}")));
-
-/// Plain text extends up through to the start of the next format
-/// block.
-named!(plain_text <&str, Box<MessagePart> >,
-    map!(is_not_s!("{"), |text| Box::new(ast::PlainText::new(text))));
-
-/// Message parts must be 1 of the various part types. And there must
-/// be at least one of them for now.
-named!(message_parts <&str, Vec<Box<MessagePart> > >,
-    many1!(
-        alt!(call!(simple_format) |
-             call!(plural_format) |
-             call!(select_format) |
-             call!(plain_text))));
-
-/// Given a set of `MessagePart`s, create a `Message`.
-named!(pub message_parser <&str, Message>,
-    map!(message_parts, Message::new));
+/// An apostrophe before a brace, or before `#` if `plural` is set,
+/// quotes the text up to the next apostrophe which is not doubled, as
+/// in `scan`. Quoted text which is not closed extends to the end of
+/// the message, as in ICU.
+pub(super) fn quoted(chars: &mut Peekable<CharIndices>, plural: bool, text: &mut String) {
+    match chars.peek().map(|&(_, c)| c) {
+        Some('\'') => {
+            chars.next();
+            text.push('\'');
+        }
+        Some(c) if c == '{' || c == '}' || (plural && c == '#') => {
+            while let Some((_, c)) = chars.next() {
+                if c != '\'' {
+                    text.push(c);
+                } else if chars.peek().map(|&(_, c)| c) == Some('\'') {
+                    chars.next();
+                    text.push('\'');
+                } else {
+                    break;
+                }
+            }
+        }
+        _ => text.push('\''),
+    }
+}
+
+/// The offset of the brace which closes the format block whose
+/// contents start at `start` in `message`, skipping over the messages
+/// nested in it with the quoting rules of `scan`. `plural` is whether
+/// the block is within a branch of a `plural`.
+pub(super) fn block_end(message: &str, start: usize, plural: bool) -> Option<usize> {
+    let mut branch_plural = None;
+    let mut chars = message[start..].char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\'' => quoted(&mut chars, false, &mut String::new()),
+            '{' => {
+                // The branches of a `plural` are where `#` is the
+                // number, and those of a `select` inherit it.
+                let branch_plural = *branch_plural.get_or_insert_with(|| {
+                    let kind = message[start..start + offset].split(',').nth(1);
+                    plural || kind.map(str::trim) == Some("plural")
+                });
+                let end = message_end(message, start + offset + 1, branch_plural)?;
+                while chars.peek().is_some_and(|&(next, _)| start + next <= end) {
+                    chars.next();
+                }
+            }
+            '}' => return Some(start + offset),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The offset of the brace which closes the message in a branch of a
+/// `plural` or `select` which starts at `start` in `message`, with the
+/// quoting rules of `scan`.
+fn message_end(message: &str, start: usize, plural: bool) -> Option<usize> {
+    let mut chars = message[start..].char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\'' => quoted(&mut chars, plural, &mut String::new()),
+            '{' => {
+                let end = block_end(message, start + offset + 1, plural)?;
+                while chars.peek().is_some_and(|&(next, _)| start + next <= end) {
+                    chars.next();
+                }
+            }
+            '}' => return Some(start + offset),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether a format block is a lone `{name}` argument.
+fn is_simple(format: &str) -> bool {
+    !format.is_empty() && !format.contains(',') && !format.starts_with('@')
+}
+
+/// Turn the pieces of a message into a `Message`. `plural` is whether
+/// the message is within a branch of a `plural`.
+fn to_message(pieces: Vec<Piece<'_>>, plural: bool) -> Result<Message, Error> {
+    match pieces[..] {
+        [] => return Ok(Message::text("")),
+        [Piece::Text(ref text)] => return Ok(Message::text(text)),
+        [Piece::Format(_, name)] if is_simple(name) => return Ok(Message::argument("", name, "")),
+        [Piece::Text(ref prefix), Piece::Format(_, name)] if is_simple(name) => {
+            return Ok(Message::argument(prefix, name, ""))
+        }
+        [Piece::Format(_, name), Piece::Text(ref suffix)] if is_simple(name) => {
+            return Ok(Message::argument("", name, suffix))
+        }
+        [Piece::Text(ref prefix), Piece::Format(_, name), Piece::Text(ref suffix)]
+            if is_simple(name) =>
+        {
+            return Ok(Message::argument(prefix, name, suffix))
+        }
+        _ => {}
+    }
+
+    let mut parts: Vec<Box<dyn MessagePart>> = Vec::with_capacity(pieces.len());
+    for piece in pieces {
+        match piece {
+            Piece::Text(text) => parts.push(Box::new(ast::PlainText::new(&text))),
+            Piece::Format(position, format_text) => {
+                parts.push(format(position, format_text, plural)?)
+            }
+            Piece::Placeholder => parts.push(Box::new(ast::PlaceholderFormat::new())),
+        }
+    }
+    Ok(Message::new(parts))
+}
 
 /// Parse some text and hopefully return a [`Message`].
 ///
+/// Messages which are only text, or a single `{name}` argument with
+/// text either side of it, are stored in a form which is quicker to
+/// format.
+///
 /// [`Message`]: ../struct.Message.html
-pub fn parse(message: &str) -> Result<Message, ParseError> {
-    match message_parser(message) {
-        IResult::Error(_) | IResult::Incomplete(_) => Err(ParseError::NotImplemented),
-        IResult::Done(_, m) => Ok(m),
-    }
+pub fn parse(message: &str) -> Result<Message, Error> {
+    to_message(pieces(message)?, false)
+}
+
+/// Parse a message from bytes whose encoding is detected by
+/// [`encoding::decode`], such as UTF-16 text with a byte order mark.
+///
+/// ```
+/// use message_format::icu;
+///
+/// let bytes = [0xff, 0xfe, b'{', 0, b'n', 0, b'}', 0];
+/// assert!(icu::parse_bytes(&bytes).is_ok());
+/// ```
+///
+/// [`encoding::decode`]: ../encoding/fn.decode.html
+pub fn parse_bytes(message: &[u8]) -> Result<Message, Error> {
+    let message = encoding::decode(message)?;
+    parse(&message)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use {arg, Context};
-    use nom::IResult;
 
     #[test]
     fn it_works() {
@@ -125,34 +532,192 @@ mod tests {
 
     #[test]
     fn incomplete_fails() {
-        match message_parser("{name") {
-            IResult::Incomplete(_) => {}
-            IResult::Error(e) => panic!("Expected incomplete failure: Got {}", e),
-            IResult::Done(_, _) => panic!("Expected incomplete failure, but succeeded."),
+        assert!(parse("{name").is_err());
+    }
+
+    #[test]
+    fn errors_have_positions() {
+        for &(pattern, expected) in &[
+            ("Hi {name", 8),
+            ("Hi {}", 4),
+            ("Hi {n, date}", 6),
+            ("", 0),
+            ("{n, number, ::currency/us}", 23),
+            ("{n, number, currency}", 12),
+            ("{t, duration, hms}", 14),
+            ("{size, bytes, si}", 14),
+            ("{city, inflect}", 14),
+            ("{@}", 2),
+            ("{@app, select}", 5),
+        ] {
+            match parse(pattern) {
+                Err(Error::Parse { position, .. }) => assert_eq!(position, expected, "{}", pattern),
+                other => panic!("unexpected result: {:?}", other),
+            }
         }
     }
 
     #[test]
-    fn all_text_works() {
-        match message_parser("Hello, world!") {
-            IResult::Done(_, _) => {}
-            _ => panic!("Expected successful parse."),
+    fn quoting_works() {
+        let ctx = Context::default();
+        let args = arg("name", "Ann");
+        for &(pattern, output) in &[
+            ("'{'{name}'}'", "{Ann}"),
+            ("Don't", "Don't"),
+            ("Don''t", "Don't"),
+            ("'{name}' is {name}", "{name} is Ann"),
+            ("'{it''s}'", "{it's}"),
+            ("'{open", "{open"),
+            ("'not quoted'", "'not quoted'"),
+        ] {
+            assert_eq!(ctx.format(&parse(pattern).unwrap(), Some(&args)), output);
         }
     }
 
     #[test]
-    fn plural_format_works() {
-        match message_parser("{count,plural}") {
-            IResult::Done(_, _) => {}
-            _ => panic!("Expected successful parse."),
+    fn quoting_works_in_branches() {
+        let ctx = Context::default();
+        let args = arg("n", 3);
+        for &(pattern, output) in &[
+            ("{n, plural, other {'{it''s}'}}", "{it's}"),
+            ("{g, select, male {'{it''s}'} other {x}}", "{it's}"),
+            ("{g, select, male {it'#1} other {x}}", "it'#1"),
+            ("{n, plural, other {'#'1 #}}", "#1 3"),
+            (
+                "{n, plural, other {{g, select, male {'#' is #} other {x}}}}",
+                "# is 3",
+            ),
+            (
+                "{g, select, other {{n, plural, other {it''s '{#}'}}}}",
+                "it's {#}",
+            ),
+        ] {
+            let message = parse(pattern).unwrap();
+            assert_eq!(
+                ctx.format(&message, Some(&args.arg("g", "male"))),
+                output,
+                "{}",
+                pattern
+            );
         }
     }
 
+    #[test]
+    fn all_text_works() {
+        assert!(parse("Hello, world!").is_ok());
+    }
+
+    #[test]
+    fn plural_format_works() {
+        assert!(parse("{count,plural}").is_ok());
+    }
+
     #[test]
     fn select_format_works() {
-        match message_parser("{type,select}") {
-            IResult::Done(_, _) => {}
-            _ => panic!("Expected successful parse."),
+        assert!(parse("{type,select}").is_ok());
+    }
+
+    #[test]
+    fn plural_branches_work() {
+        let ctx = Context::default();
+        let message =
+            parse("{count, plural, =0 {No files} one {# file} other {# files in '{'{dir}'}'}}")
+                .unwrap();
+        for &(count, output) in &[(0, "No files"), (1, "1 file"), (12, "12 files in {tmp}")] {
+            let args = arg("count", count);
+            assert_eq!(ctx.format(&message, Some(&args.arg("dir", "tmp"))), output);
+        }
+
+        let message = parse(
+            "{guests, plural, offset:1 =0 {Nobody} =1 {{host}} \
+             one {{host} and # other} other {{host} and # others, '#'1}}",
+        )
+        .unwrap();
+        for &(guests, output) in &[
+            (0, "Nobody"),
+            (1, "Ann"),
+            (2, "Ann and 1 other"),
+            (4, "Ann and 3 others, #1"),
+        ] {
+            let args = arg("guests", guests);
+            assert_eq!(ctx.format(&message, Some(&args.arg("host", "Ann"))), output);
+        }
+        assert_eq!(
+            ctx.format(
+                &parse("{n, plural, other {}}# left").unwrap(),
+                Some(&arg("n", 1))
+            ),
+            "# left"
+        );
+    }
+
+    #[test]
+    fn select_branches_work() {
+        let ctx = Context::default();
+        let message = parse(
+            "{n, plural, one {{who, select, self {You have # item} other {{who} has # item}}} \
+             other {{who, select, self {You have # items} other {{who} has # items}}}}",
+        )
+        .unwrap();
+        let args = arg("n", 2);
+        assert_eq!(
+            ctx.format(&message, Some(&args.arg("who", "self"))),
+            "You have 2 items"
+        );
+        let args = arg("n", 1);
+        assert_eq!(
+            ctx.format(&message, Some(&args.arg("who", "Ann"))),
+            "Ann has 1 item"
+        );
+        let message = parse("{who, select, self {#1} other {{who}}}").unwrap();
+        assert_eq!(ctx.format(&message, Some(&arg("who", "self"))), "#1");
+    }
+
+    #[test]
+    fn branch_errors_have_positions() {
+        for &(pattern, expected) in &[
+            ("{n, plural, one {#}}", 19),
+            ("{n, plural, one {#} two}", 23),
+            ("{n, plural, few {#} =x {} other {}}", 21),
+            ("{n, plural, lots {#} other {}}", 12),
+            ("{n, plural, offset:a other {}}", 19),
+            ("{n, plural, other {{m, number, money}}}", 31),
+            ("{g, select, male {he}}", 21),
+            ("{g, select, male {he} other {they}", 34),
+            ("{n, plural, one {a} one {b} other {c}}", 20),
+            ("{n, plural, =1 {a} =01 {b} other {c}}", 19),
+            ("{n, plural, other {a} other {b}}", 22),
+            ("{g, select, he {a} he {b} other {c}}", 19),
+            ("{g, select, other {a} other {b}}", 22),
+        ] {
+            match parse(pattern) {
+                Err(Error::Parse { position, .. }) => assert_eq!(position, expected, "{}", pattern),
+                other => panic!("unexpected result: {:?}", other),
+            }
         }
     }
+
+    #[test]
+    fn fast_paths_work() {
+        let ctx = Context::default();
+        let args = arg("name", "Ann");
+        for &(pattern, output) in &[
+            ("Hello!", "Hello!"),
+            ("{name}", "Ann"),
+            ("Hello, {name}", "Hello, Ann"),
+            ("{name}!", "Ann!"),
+            ("Hello, {name}!", "Hello, Ann!"),
+            ("{name} and {name}", "Ann and Ann"),
+        ] {
+            assert_eq!(ctx.format(&parse(pattern).unwrap(), Some(&args)), output);
+        }
+
+        let mut output = String::new();
+        let message = parse("Hello, {name}!").unwrap();
+        assert!(ctx.write(&message, &mut output, None).is_err());
+        assert!(parse("Hello, {}!").is_err());
+
+        let parts = Message::new(vec![Box::new(ast::PlainText::new("Hello!"))]);
+        assert!(parse("Hello!").unwrap().memory_footprint() < parts.memory_footprint());
+    }
 }
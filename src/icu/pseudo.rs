@@ -0,0 +1,101 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::parse::{parse, pieces, Piece};
+use prelude::*;
+use Error;
+
+/// The accented forms of the ASCII letters, from `a` to `z` and then
+/// from `A` to `Z`.
+const ACCENTED: [char; 52] = [
+    'á', 'ƀ', 'ç', 'ð', 'é', 'ƒ', 'ĝ', 'ĥ', 'í', 'ĵ', 'ķ', 'ļ', 'ɱ', 'ñ', 'ö', 'þ', 'ǫ', 'ŕ', 'š',
+    'ţ', 'û', 'ṽ', 'ŵ', 'ẋ', 'ý', 'ž', 'Å', 'Ɓ', 'Ç', 'Ð', 'É', 'Ƒ', 'Ĝ', 'Ĥ', 'Î', 'Ĵ', 'Ķ', 'Ļ',
+    'Ṁ', 'Ñ', 'Ö', 'Þ', 'Ǫ', 'Ŕ', 'Š', 'Ţ', 'Û', 'Ṽ', 'Ŵ', 'Ẋ', 'Ý', 'Ž',
+];
+
+/// Rewrite the text of a pattern so that it can be read but plainly is
+/// not the source text, to find text which is not localized and layouts
+/// which can not take longer translations before there are any.
+///
+/// Letters are accented, vowels are doubled to make the text about a
+/// third longer, and the whole is wrapped in brackets so that text cut
+/// off at either end shows up. Arguments are kept as they are.
+///
+/// ```
+/// use message_format::icu;
+///
+/// assert_eq!(
+///     icu::pseudo_localize("Hello, {name}!").unwrap(),
+///     "[Ĥééļļöö, {name}!]"
+/// );
+/// ```
+///
+/// A pattern which can not be parsed fails with the same error as
+/// from [`parse`].
+///
+/// [`parse`]: fn.parse.html
+pub fn pseudo_localize(pattern: &str) -> Result<String, Error> {
+    parse(pattern)?;
+    let mut output = String::with_capacity(pattern.len() * 2);
+    output.push('[');
+    for piece in pieces(pattern)? {
+        match piece {
+            Piece::Text(text) => {
+                for c in text.chars() {
+                    let accented = match c {
+                        'a'..='z' => ACCENTED[c as usize - 'a' as usize],
+                        'A'..='Z' => ACCENTED[c as usize - 'A' as usize + 26],
+                        '\'' => {
+                            output.push_str("''");
+                            continue;
+                        }
+                        '{' | '}' => {
+                            output.push('\'');
+                            output.push(c);
+                            output.push('\'');
+                            continue;
+                        }
+                        _ => c,
+                    };
+                    output.push(accented);
+                    if "aeiouAEIOU".contains(c) {
+                        output.push(accented);
+                    }
+                }
+            }
+            Piece::Placeholder => output.push('#'),
+            Piece::Format(_, format) => {
+                output.push('{');
+                output.push_str(format);
+                output.push('}');
+            }
+        }
+    }
+    output.push(']');
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pseudo_localize;
+    use icu::parse;
+
+    #[test]
+    fn arguments_are_kept() {
+        let pseudo = pseudo_localize("{count, number} files in {dir}").unwrap();
+        assert_eq!(pseudo, "[{count, number} ƒííļééš ííñ {dir}]");
+        assert_eq!(
+            parse(&pseudo).unwrap().arguments(),
+            parse("{count, number} files in {dir}").unwrap().arguments()
+        );
+        assert_eq!(pseudo_localize("Zoë").unwrap(), "[Žööë]");
+        assert_eq!(
+            pseudo_localize("'{'a'}' isn''t it").unwrap(),
+            "['{'áá'}' ííšñ''ţ ííţ]"
+        );
+        assert!(pseudo_localize("Hi {name").is_err());
+    }
+}
@@ -0,0 +1,179 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use Error;
+
+/// The inflected forms of words, such as the grammatical cases of the
+/// names of cities, for an `inflect` argument.
+///
+/// A message can not know how to decline a name which is given to it
+/// as an argument, so the application supplies a table of the forms of
+/// the words it uses, through `Context::inflections`. A pattern then
+/// picks a form by the name of its case:
+///
+/// ```
+/// use std::sync::Arc;
+/// use message_format::{icu, Context, Inflections, Value};
+///
+/// let mut inflections = Inflections::new();
+/// inflections.insert("Praha", "locative", "Praze");
+/// let ctx = Context {
+///     language_tag: "cs".parse().unwrap(),
+///     inflections: Some(Arc::new(inflections)),
+///     ..Context::default()
+/// };
+/// let m = icu::parse("Bydlím v {city, inflect, locative}.").unwrap();
+/// assert_eq!(ctx.format(&m, Some(&[("city", Value::Str("Praha"))])), "Bydlím v Praze.");
+/// assert_eq!(ctx.format(&m, Some(&[("city", Value::Str("Brno"))])), "Bydlím v Brno.");
+/// ```
+///
+/// A word without the form that is asked for is written as it is
+/// given, which is usually its nominative or dictionary form.
+///
+/// This requires the `std` feature.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Inflections {
+    words: HashMap<String, HashMap<String, String>>,
+}
+
+impl Inflections {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Inflections::default()
+    }
+
+    /// Add the form of `word` in the given `case`, replacing any form
+    /// it already had in that case.
+    pub fn insert(&mut self, word: &str, case: &str, form: &str) {
+        self.words
+            .entry(word.to_string())
+            .or_default()
+            .insert(case.to_string(), form.to_string());
+    }
+
+    /// The form of `word` in the given `case`, if there is one.
+    pub fn get(&self, word: &str, case: &str) -> Option<&str> {
+        self.words
+            .get(word)
+            .and_then(|forms| forms.get(case))
+            .map(|form| &form[..])
+    }
+
+    /// The number of words with forms in the table.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Whether the table has no words.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Read a table with a line for each word. The word is followed by
+    /// a colon and its forms, separated by commas, each with the name
+    /// of its case and an equals sign in front:
+    ///
+    /// ```text
+    /// # The names of Czech cities.
+    /// Praha: genitive=Prahy, dative=Praze, locative=Praze
+    /// Brno: genitive=Brna, dative=Brnu, locative=Brně
+    /// ```
+    ///
+    /// Blank lines and lines starting with `#` are skipped. Words,
+    /// cases and forms can not hold the characters `:`, `,` or `=`. A
+    /// line which can not be read fails with `Error::Entry`, holding
+    /// an `Error::Parse` with the offset in the line.
+    ///
+    /// ```
+    /// use message_format::Inflections;
+    ///
+    /// let text = "Praha: genitive=Prahy, locative=Praze\n";
+    /// let inflections = Inflections::read(text.as_bytes()).unwrap();
+    /// assert_eq!(inflections.get("Praha", "locative"), Some("Praze"));
+    /// ```
+    pub fn read<R: BufRead>(reader: R) -> Result<Self, Error> {
+        let mut inflections = Inflections::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let text = line.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            inflections.read_line(&line, index + 1)?;
+        }
+        Ok(inflections)
+    }
+
+    fn read_line(&mut self, line: &str, number: usize) -> Result<(), Error> {
+        let colon = line.find(':');
+        let word = line[..colon.unwrap_or(line.len())].trim();
+        let entry_error = |position, message| Error::Entry {
+            key: word.to_string(),
+            line: number,
+            error: Box::new(Error::parse(position, message)),
+        };
+        let colon = colon.ok_or_else(|| entry_error(line.len(), "Expected ':' after the word"))?;
+        if word.is_empty() {
+            return Err(entry_error(0, "Expected a word"));
+        }
+        let mut position = colon + 1;
+        for form in line[colon + 1..].split(',') {
+            let (case, value) = match form.find('=') {
+                Some(equals) => (form[..equals].trim(), form[equals + 1..].trim()),
+                None => ("", ""),
+            };
+            if case.is_empty() || value.is_empty() {
+                let offset = form.len() - form.trim_start().len();
+                return Err(entry_error(position + offset, "Expected 'case=form'"));
+            }
+            self.insert(word, case, value);
+            position += form.len() + 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Inflections;
+    use Error;
+
+    #[test]
+    fn tables_are_read() {
+        let text = "# Cities\n\nPraha: genitive=Prahy, locative=Praze\n  Brno :locative = Brně\n";
+        let inflections = Inflections::read(text.as_bytes()).unwrap();
+        assert_eq!(inflections.len(), 2);
+        assert_eq!(inflections.get("Praha", "genitive"), Some("Prahy"));
+        assert_eq!(inflections.get("Brno", "locative"), Some("Brně"));
+        assert_eq!(inflections.get("Brno", "genitive"), None);
+        assert_eq!(inflections.get("Ostrava", "locative"), None);
+
+        for &(text, key, line, expected) in &[
+            ("Praha genitive=Prahy", "Praha genitive=Prahy", 1, 20),
+            ("\n: genitive=Prahy", "", 2, 0),
+            ("Praha: genitive=Prahy, locative", "Praha", 1, 23),
+            ("Praha: =Prahy", "Praha", 1, 7),
+        ] {
+            match Inflections::read(text.as_bytes()) {
+                Err(Error::Entry {
+                    key: ref k,
+                    line: l,
+                    ref error,
+                }) => {
+                    assert_eq!((&k[..], l), (key, line), "{}", text);
+                    match **error {
+                        Error::Parse { position, .. } => assert_eq!(position, expected, "{}", text),
+                        ref other => panic!("unexpected error: {:?}", other),
+                    }
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+}
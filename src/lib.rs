@@ -73,6 +73,37 @@
 //! message-format = "0.0.1"
 //! ```
 //!
+//! The parser and formatter also work without the standard library,
+//! using only `core` and `alloc`, by disabling the default `std`
+//! feature:
+//!
+//! ```toml
+//! [dependencies]
+//! message-format = { version = "0.0.1", default-features = false }
+//! ```
+//!
+//! Without `std`, a [`Context`] does not carry a language tag, and the
+//! [`convert` module] and [`Catalog`] are not available.
+//!
+//! With the `serde` feature, a [`Message`] can be deserialized from its
+//! ICU pattern and [`deserialize_messages`] reads a whole map of
+//! patterns, reporting the key of any pattern that fails to parse.
+//! [`Args::from_serialize`] collects the arguments for a message from
+//! the fields of any serializable struct.
+//!
+//! The `fast-hash` feature provides [`FastBuildHasher`], a faster hasher
+//! for maps of arguments or messages. The `small-strings` feature stores
+//! short literal text and variable names inline in a parsed message,
+//! saving a heap allocation for each. The `testing` feature provides
+//! the [`testing` module], which generates valid patterns for property
+//! tests, and the `testkit` feature provides the [`testkit` module] for
+//! snapshot testing the output of a catalog. The `extract` feature
+//! provides the [`extract` module], which collects the messages written
+//! with `message!` from Rust source into a catalog for translators.
+//! The `cli` feature builds the `message-format` command, which
+//! validates, converts, pseudo-localizes, diffs and counts the coverage
+//! of `.properties` catalogs, for use in CI and by translators.
+//!
 //! This library depends upon some macros being used, so at the top of your crate,
 //! you will want to make sure that macros from this crate are used:
 //!
@@ -91,6 +122,12 @@
 //! For now, this stores the locale that is being used, but in the future,
 //! it will be used for additional features.
 //!
+//! A context can also hold [`Metrics`], which are told about each
+//! message formatted with it, the errors it fails with and the hits of
+//! a [`FormatCache`], for watching localization in production. It can
+//! also hold a [`NumberBackend`], which writes the digits of numbers
+//! for an application which already formats numbers in its own way.
+//!
 //! ## ICU Formatted Messages
 //!
 //! The simplest way to create an ICU formatted [`Message`] from code is
@@ -104,10 +141,76 @@
 //!
 //! For details on the [ICU Message Format] syntax, see the [`icu` module].
 //!
+//! A program which parses the same patterns many times can parse them
+//! through a [`ParseCache`], such as the one shared by the whole program
+//! from `ParseCache::global`, which parses each pattern only once and
+//! shares the message.
+//!
+//! For a quick script, [`format`] parses a pattern and formats it for a
+//! locale in one call, keeping the patterns it used most recently
+//! parsed:
+//!
+//! ```
+//! # #[cfg(feature = "std")]
+//! # fn main() {
+//! use message_format::{format, Value};
+//!
+//! let args = [("host", Value::Str("example.com"))];
+//! let output = format("Connecting to {host}...", "en", &args).unwrap();
+//! assert_eq!(output, "Connecting to example.com...");
+//! # }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
+//! ```
+//!
+//! ## Catalogs
+//!
+//! A [`Catalog`] holds the messages of an application by key. It can be
+//! read from a Java `.properties` file of ICU patterns with
+//! [`Catalog::read_properties`], which reads from any `io::BufRead` a
+//! line at a time rather than loading the whole file first. Each
+//! message may also have [`Metadata`] for translators, such as a
+//! description of where it is used, or a [`Schema`] of the arguments
+//! that it takes, which [`Catalog::validate`] checks each translation
+//! against when it is loaded. A catalog from a source which is not
+//! trusted can be given [`Limits`] on the length of its output and on
+//! how many messages its references may format, or be sandboxed so
+//! that it only formats messages without references or parts of other
+//! kinds. Keys with the same message share one copy of it, as counted
+//! by [`Catalog::dedup_stats`].
+//!
+//! [`diff`] compares two versions of a message part by part, so that
+//! review tools can show translators what changed in a source message
+//! since they translated it.
+//!
+//! A `.properties` catalog can also be embedded in the program with
+//! `include_messages!` from the `message-format-macros` crate, which
+//! checks its patterns when the program is compiled.
+//!
+//! The [`properties` module] reads and writes the entries of a
+//! `.properties` file without parsing their patterns, for tools which
+//! rewrite them. The [`container` module] keeps them in a `.properties`
+//! file which records the version of its layout and the revision of
+//! each message, and migrates files written by older versions of an
+//! application.
+//!
+//! Keys are split into namespaces at dots, such as `settings.network`,
+//! and [`Catalog::namespace`] gives a view of the messages of one of
+//! them, so that each part of a large application can use short keys.
+//!
+//! Text in UTF-16, as exported by many Windows tools, can be decoded
+//! with the [`encoding` module], and [`icu::parse_bytes`] parses a
+//! pattern from bytes in any encoding that it detects.
+//!
 //! ## Fluent Formatted Messages
 //!
 //! Support for the [Fluent] localization format is under development.
 //!
+//! ## Converting Other Formats
+//!
+//! Format strings written for other languages and libraries can be
+//! converted into ICU patterns using the [`convert` module].
+//!
 //! ## Formatting a Message
 //!
 //! Messages need arguments or parameters. Since messages typically have named
@@ -135,6 +238,21 @@
 //! }
 //! ```
 //!
+//! `format_message!` returns whatever could be formatted, even if an
+//! argument is missing. [`Context::try_format`] instead returns an
+//! [`Error`] saying which argument was missing or had the wrong type.
+//! The same `Error` type is returned when parsing messages or reading a
+//! [`Catalog`], and its [`code`] tells the kinds of errors apart.
+//!
+//! Behavior which varies between calls, such as what to do about a
+//! missing argument or whether to escape values for HTML, is set with
+//! [`FormatOptions`] and the `format_message_with!` macro or
+//! [`Context::format_with`].
+//!
+//! Output which must fit in a limited space can be shortened with the
+//! [`truncate` module], which never splits a grapheme cluster and keeps
+//! the values of arguments whole.
+//!
 //! ## Future Directions
 //!
 //! In the future, we want to extend this library to support a number of
@@ -156,34 +274,120 @@
 //!
 //! [ICU Message Format]: icu/index.html
 //! [`icu` module]: icu/index.html
+//! [`convert` module]: convert/index.html
+//! [`encoding` module]: encoding/index.html
+//! [`extract` module]: extract/index.html
+//! [`container` module]: container/index.html
+//! [`properties` module]: properties/index.html
+//! [`testing` module]: testing/index.html
+//! [`testkit` module]: testkit/index.html
+//! [`truncate` module]: truncate/index.html
+//! [`icu::parse_bytes`]: icu/fn.parse_bytes.html
 //! [Fluent]: http://projectfluent.org/
 //! [`parse`]: icu/fn.parse.html
+//! [`Args::from_serialize`]: struct.Args.html#method.from_serialize
+//! [`Catalog`]: struct.Catalog.html
+//! [`Catalog::dedup_stats`]: struct.Catalog.html#method.dedup_stats
+//! [`Catalog::read_properties`]: struct.Catalog.html#method.read_properties
+//! [`Catalog::validate`]: struct.Catalog.html#method.validate
+//! [`Catalog::namespace`]: struct.Catalog.html#method.namespace
+//! [`code`]: enum.Error.html#method.code
+//! [`Context`]: struct.Context.html
+//! [`Context::format_with`]: struct.Context.html#method.format_with
+//! [`Context::try_format`]: struct.Context.html#method.try_format
+//! [`Error`]: enum.Error.html
+//! [`deserialize_messages`]: fn.deserialize_messages.html
+//! [`diff`]: fn.diff.html
+//! [`FastBuildHasher`]: type.FastBuildHasher.html
+//! [`format`]: fn.format.html
+//! [`FormatCache`]: struct.FormatCache.html
+//! [`FormatOptions`]: struct.FormatOptions.html
+//! [`Limits`]: struct.Limits.html
 //! [`Message`]: struct.Message.html
+//! [`Metadata`]: struct.Metadata.html
+//! [`Metrics`]: trait.Metrics.html
+//! [`NumberBackend`]: trait.NumberBackend.html
+//! [`ParseCache`]: struct.ParseCache.html
+//! [`Schema`]: struct.Schema.html
 //! [`Value`]: enum.Value.html
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![deny(trivial_numeric_casts, unsafe_code, unstable_features, unused_import_braces,
         unused_qualifications)]
 
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+#[cfg(feature = "std")]
 extern crate language_tags;
+#[cfg(feature = "extract")]
+extern crate proc_macro2;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
 #[macro_use]
-extern crate nom;
+extern crate serde_derive;
+#[cfg(feature = "extract")]
+extern crate syn;
 
 mod args;
+#[cfg(feature = "std")]
+mod cache;
 mod context;
+mod diff;
+mod duration;
+mod error;
+#[cfg(feature = "fast-hash")]
+mod hash;
+#[cfg(feature = "std")]
+mod inflection;
 mod message;
 mod message_part;
+#[cfg(feature = "std")]
+mod metrics;
+mod number;
+mod options;
 mod plural_category;
 mod plural_classifiers;
+mod prelude;
+mod schema;
+mod source_map;
+mod text;
 mod value;
 
-pub use self::args::{arg, Args};
+pub use self::args::{arg, Args, Arguments, WithDefaults};
+#[cfg(feature = "std")]
+pub use self::cache::{format, FormatCache, ParseCache};
+#[cfg(feature = "std")]
+pub use self::catalog::{Catalog, DedupStats, Limits, Metadata, Namespace};
 pub use self::context::Context;
+pub use self::diff::{diff, Change};
+pub use self::duration::DurationStyle;
+pub use self::error::Error;
+#[cfg(feature = "fast-hash")]
+pub use self::hash::{FastBuildHasher, FastHasher};
+#[cfg(feature = "std")]
+pub use self::inflection::Inflections;
+#[cfg(all(feature = "serde", feature = "std"))]
+pub use self::deserialize::deserialize_messages;
 pub use self::message::Message;
 pub use self::message_part::MessagePart;
+#[cfg(feature = "std")]
+pub use self::metrics::Metrics;
+pub use self::number::{
+    ByteUnits, CurrencyDisplay, NonFiniteNumbers, NumberBackend, NumberOptions,
+};
+pub use self::options::{Escape, FormatOptions, MissingArguments};
 pub use self::plural_category::PluralCategory;
 pub use self::plural_classifiers::*;
-pub use self::value::Value;
+pub use self::schema::{ArgumentType, Schema};
+#[cfg(feature = "serde")]
+pub use self::serialize::{SerializeError, SerializedArgs};
+pub use self::source_map::{MappedOutput, OutputSpan, SpanKind};
+pub use self::value::{Decimal, Value};
 
 #[macro_export]
 macro_rules! format_message {
@@ -196,6 +400,21 @@ macro_rules! format_message {
     });
 }
 
+/// Format a message with [`FormatOptions`], taking its arguments the same
+/// way as `format_message!`.
+///
+/// [`FormatOptions`]: struct.FormatOptions.html
+#[macro_export]
+macro_rules! format_message_with {
+    ($ctx:expr, $msg:expr, $options:expr) => {
+        $ctx.format_with($msg, None, $options)
+    };
+    ($ctx:expr, $msg:expr, $options:expr, $($rest:tt)*) => ({
+        use $crate::Value;
+        $ctx.format_with($msg, message_args!($($rest)*), $options)
+    });
+}
+
 #[macro_export]
 macro_rules! write_message {
     ($ctx:expr, $msg:expr, $stream:expr) => {
@@ -280,9 +499,39 @@ macro_rules! message_args {
     };
 }
 
+/// Parse the pattern of a message in the source locale, giving its id
+/// and, optionally, a description for translators.
+///
+/// The id and description are not used here, but are read along with
+/// the pattern by the [`extract` module], which collects the messages
+/// of an application into a catalog for translators. Each must be a
+/// string literal for that.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate message_format;
+/// # fn main() {
+/// let ctx = message_format::Context::default();
+/// let m = message!("greeting", "Hello, {name}!", "Shown on the home page.").unwrap();
+/// assert_eq!(format_message!(ctx, &m, name => "Ann"), "Hello, Ann!");
+/// # }
+/// ```
+///
+/// [`extract` module]: extract/index.html
+#[macro_export]
+macro_rules! message {
+    ($id:expr, $pattern:expr) => {
+        $crate::icu::parse($pattern)
+    };
+    ($id:expr, $pattern:expr, $description:expr) => {
+        $crate::icu::parse($pattern)
+    };
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{icu, Context};
+    use super::{arg, icu, Context, Error, FormatOptions, MissingArguments};
+    use prelude::*;
 
     #[test]
     fn format_without_args() {
@@ -350,7 +599,81 @@ mod tests {
         write_message!(ctx, &m, &mut stream, name => "John").unwrap();
         assert_eq!(stream, "John");
     }
+
+    #[test]
+    fn format_with_options() {
+        let ctx = Context::default();
+        let m = icu::parse("{a}, {b}").unwrap();
+        let options = FormatOptions {
+            missing_arguments: MissingArguments::Name,
+            ..FormatOptions::default()
+        };
+        let output = format_message_with!(ctx, &m, &options, a => 1).unwrap();
+        assert_eq!(output.text(), "1, {b}");
+        let output = format_message_with!(ctx, &m, &options).unwrap();
+        assert_eq!(output.text(), "{a}, {b}");
+    }
+
+    #[test]
+    fn strict_arguments_report_unused() {
+        let mut ctx = Context::default();
+        let m = icu::parse("{a}{b}").unwrap();
+        let args = arg("a", 1);
+        let args = args.arg("c", 3);
+        let args = args.arg("b", 2);
+        let args = args.arg("d", 4);
+        assert_eq!(ctx.try_format(&m, Some(&args)).unwrap(), "12");
+
+        ctx.strict_arguments = true;
+        match ctx.try_format(&m, Some(&args)) {
+            Err(Error::UnusedArguments { ref names }) => assert_eq!(names, &["c", "d"]),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let args = arg("a", 1);
+        let args = args.arg("b", 2);
+        assert_eq!(ctx.try_format(&m, Some(&args)).unwrap(), "12");
+    }
+
+    #[test]
+    fn strict_arguments_apply_to_format_with() {
+        let ctx = Context {
+            strict_arguments: true,
+            ..Context::default()
+        };
+        let m = icu::parse("{a}{b}").unwrap();
+        let options = FormatOptions::default();
+        match format_message_with!(ctx, &m, &options, a => 1, b => 2, c => 3) {
+            Err(Error::UnusedArguments { ref names }) => assert_eq!(names, &["c"]),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let options = FormatOptions {
+            missing_arguments: MissingArguments::Name,
+            ..FormatOptions::default()
+        };
+        let output = format_message_with!(ctx, &m, &options, a => 1).unwrap();
+        assert_eq!(output.text(), "1{b}");
+    }
 }
 
+#[cfg(feature = "std")]
+mod catalog;
+#[cfg(feature = "std")]
+pub mod container;
+#[cfg(feature = "std")]
+pub mod convert;
+pub mod encoding;
+#[cfg(feature = "serde")]
+mod deserialize;
+#[cfg(feature = "extract")]
+pub mod extract;
 pub mod fluent;
+#[cfg(feature = "std")]
+pub mod properties;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+pub mod truncate;
 pub mod icu;
@@ -6,7 +6,10 @@
 
 use std::fmt;
 
-use {Args, Context, MessagePart};
+use prelude::*;
+use schema::add_argument;
+use text::{self, Text};
+use {ArgumentType, Arguments, Context, Error, MappedOutput, MessagePart, SpanKind};
 
 /// A message that has been localized and can be formatted in a
 /// locale-aware manner.
@@ -18,28 +21,357 @@ use {Args, Context, MessagePart};
 /// [`icu::parse`]: icu/fn.parse.html
 #[derive(Debug)]
 pub struct Message {
-    parts: Vec<Box<MessagePart>>,
+    shape: Shape,
+}
+
+/// How a message is stored.
+///
+/// Most user interface strings are either plain text or a single
+/// argument surrounded by text, so the parser stores these directly
+/// rather than as a list of parts which must each be visited when
+/// formatting.
+#[derive(Debug)]
+enum Shape {
+    /// Only literal text.
+    Text(Text),
+    /// A single `{name}` argument with literal text either side of it.
+    Argument(Box<Argument>),
+    /// Any other message.
+    Parts(Box<[Box<dyn MessagePart>]>),
+}
+
+#[derive(Debug)]
+struct Argument {
+    prefix: Text,
+    name: Text,
+    suffix: Text,
 }
 
 impl Message {
     /// Construct a message from constituent parts.
     pub fn new(parts: Vec<Box<MessagePart>>) -> Self {
-        Message { parts: parts }
+        Message {
+            shape: Shape::Parts(parts.into_boxed_slice()),
+        }
+    }
+
+    /// Construct a message which is only literal text.
+    pub(crate) fn text(text: &str) -> Self {
+        Message {
+            shape: Shape::Text(Text::from(text)),
+        }
+    }
+
+    /// Construct a message which formats a single argument as it is,
+    /// between a `prefix` and a `suffix`.
+    pub(crate) fn argument(prefix: &str, name: &str, suffix: &str) -> Self {
+        Message {
+            shape: Shape::Argument(Box::new(Argument {
+                prefix: Text::from(prefix),
+                name: Text::from(name),
+                suffix: Text::from(suffix),
+            })),
+        }
+    }
+
+    /// The number of bytes used by this message, including all of the
+    /// heap allocations that it owns.
+    ///
+    /// This is meant for measuring how much memory a catalog of
+    /// messages takes up.
+    ///
+    /// ```
+    /// use message_format::icu;
+    ///
+    /// let message = icu::parse("Hello, {name}!").unwrap();
+    /// assert!(message.memory_footprint() > 0);
+    /// ```
+    pub fn memory_footprint(&self) -> usize {
+        size_of::<Self>() + self.heap_size()
+    }
+
+    /// The number of bytes allocated on the heap for this message, for
+    /// use by message parts which contain other messages.
+    pub(crate) fn heap_size(&self) -> usize {
+        match self.shape {
+            Shape::Text(ref text) => text::heap_size(text),
+            Shape::Argument(ref argument) => {
+                size_of::<Argument>()
+                    + text::heap_size(&argument.prefix)
+                    + text::heap_size(&argument.name)
+                    + text::heap_size(&argument.suffix)
+            }
+            Shape::Parts(ref parts) => {
+                size_of_val(&**parts)
+                    + parts
+                        .iter()
+                        .map(|part| part.memory_footprint())
+                        .sum::<usize>()
+            }
+        }
+    }
+
+    /// Write a message, recording which part produced each span of the
+    /// output, for `Context::format_mapped` and for message parts which
+    /// contain other messages.
+    pub(crate) fn write_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        use std::fmt::Write;
+
+        match self.shape {
+            Shape::Text(ref text) => output.part(0, |output| write_text(output, text)),
+            Shape::Argument(ref argument) => {
+                let value = args
+                    .and_then(|args| args.lookup(&argument.name))
+                    .ok_or(fmt::Error {})?;
+                let mut index = 0;
+                if !argument.prefix.is_empty() {
+                    output.part(index, |output| write_text(output, &argument.prefix))?;
+                    index += 1;
+                }
+                output.part(index, |output| {
+                    let start = output.len();
+                    write!(output, "{}", value)?;
+                    output.record(start, SpanKind::Argument(argument.name.to_string()));
+                    Ok(())
+                })?;
+                if !argument.suffix.is_empty() {
+                    output.part(index + 1, |output| write_text(output, &argument.suffix))?;
+                }
+                Ok(())
+            }
+            Shape::Parts(ref parts) => {
+                for (index, part) in parts.iter().enumerate() {
+                    output.part(index, |output| part.apply_format_mapped(ctx, output, args))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Check that `args` holds the arguments that this message needs,
+    /// for use by message parts which contain other messages.
+    pub(crate) fn check_arguments(
+        &self,
+        ctx: &Context,
+        args: Option<&dyn Arguments>,
+    ) -> Result<(), Error> {
+        match self.shape {
+            Shape::Text(_) => Ok(()),
+            Shape::Argument(ref argument) => {
+                match args.and_then(|args| args.lookup(&argument.name)) {
+                    Some(_) => Ok(()),
+                    None => Err(Error::missing_argument(&argument.name)),
+                }
+            }
+            Shape::Parts(ref parts) => parts
+                .iter()
+                .try_for_each(|part| part.check_arguments(ctx, args)),
+        }
+    }
+
+    /// The arguments that this message uses, in order of first use,
+    /// along with the type of value that it needs for each.
+    ///
+    /// Arguments used inside of every branch of a `plural` or `select`
+    /// are included, whichever branch would be chosen.
+    ///
+    /// ```
+    /// use message_format::{icu, ArgumentType};
+    ///
+    /// let message = icu::parse("{name} has {count}").unwrap();
+    /// assert_eq!(message.arguments(), vec![
+    ///     ("name".to_string(), ArgumentType::Any),
+    ///     ("count".to_string(), ArgumentType::Any),
+    /// ]);
+    /// ```
+    pub fn arguments(&self) -> Vec<(String, ArgumentType)> {
+        let mut arguments = vec![];
+        self.collect_arguments(&mut arguments);
+        arguments
+    }
+
+    /// The names of the arguments in `args` which this message does not
+    /// use in any of its branches, in the order they were given.
+    ///
+    /// ```
+    /// use message_format::{icu, Value};
+    ///
+    /// let message = icu::parse("Hello, {name}!").unwrap();
+    /// let args = [("name", Value::Str("Ann")), ("nmae", Value::Str("Ann"))];
+    /// assert_eq!(message.unused_arguments(&args), vec!["nmae"]);
+    /// ```
+    pub fn unused_arguments<'a>(&self, args: &'a dyn Arguments) -> Vec<&'a str> {
+        let used = self.arguments();
+        let mut names = args.names();
+        names.retain(|name| !used.iter().any(|argument| argument.0 == *name));
+        names.dedup();
+        names
+    }
+
+    /// Add the arguments that this message uses to `arguments`, for use
+    /// by message parts which contain other messages.
+    pub(crate) fn collect_arguments(&self, arguments: &mut Vec<(String, ArgumentType)>) {
+        match self.shape {
+            Shape::Text(_) => {}
+            Shape::Argument(ref argument) => {
+                add_argument(arguments, &argument.name, ArgumentType::Any)
+            }
+            Shape::Parts(ref parts) => {
+                for part in parts.iter() {
+                    part.collect_arguments(arguments);
+                }
+            }
+        }
+    }
+
+    /// An indented outline of how this message was parsed, with one
+    /// line for each part.
+    ///
+    /// ```
+    /// use message_format::icu;
+    ///
+    /// let message = icu::parse("Hello, {name}!").unwrap();
+    /// assert_eq!(message.dump(), "\
+    /// Message
+    ///   Text \"Hello, \"
+    ///   Argument name
+    ///   Text \"!\"
+    /// ");
+    /// ```
+    pub fn dump(&self) -> String {
+        let mut output = String::new();
+        let _ = self.dump_to(&mut output, 0);
+        output
+    }
+
+    /// Whether this message only uses the parts built into this crate
+    /// which write what follows from their arguments, with no
+    /// references to other messages and no parts of other kinds, in any
+    /// of its branches.
+    ///
+    /// Every message parsed from a pattern is pure unless it has a
+    /// reference. A sandboxed [`Catalog`] only formats pure messages.
+    ///
+    /// ```
+    /// use message_format::icu;
+    ///
+    /// assert!(icu::parse("{count, number} items").unwrap().is_pure());
+    /// assert!(!icu::parse("Welcome to {@app.name}").unwrap().is_pure());
+    /// ```
+    ///
+    /// [`Catalog`]: struct.Catalog.html
+    pub fn is_pure(&self) -> bool {
+        match self.shape {
+            Shape::Text(_) | Shape::Argument(_) => true,
+            Shape::Parts(ref parts) => parts.iter().all(|part| part.is_pure()),
+        }
+    }
+
+    /// A hash of what this message formats, for caches and translation
+    /// memories which need to recognize the same message written in a
+    /// different way.
+    ///
+    /// The hash is taken over the outline from [`dump`](#method.dump)
+    /// rather than the source, so the whitespace which the parser
+    /// ignores around format types and styles makes no difference.
+    /// Adjacent text is joined first, so a message built from several
+    /// pieces of text has the same fingerprint as one parsed from the
+    /// same text. It uses FNV-1a, so it is the same from one run or
+    /// platform to the next and can be stored.
+    ///
+    /// ```
+    /// use message_format::icu;
+    ///
+    /// let a = icu::parse("{count, number} items").unwrap();
+    /// let b = icu::parse("{count,  number } items").unwrap();
+    /// let c = icu::parse("{count, number} things").unwrap();
+    /// assert_eq!(a.fingerprint(), b.fingerprint());
+    /// assert_ne!(a.fingerprint(), c.fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        ::diff::fingerprint(self)
+    }
+
+    /// Write the outline of this message for `dump`, for use by
+    /// message parts which contain other messages.
+    pub(crate) fn dump_to(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(stream, "{:indent$}Message", "", indent = indent)?;
+        let indent = indent + 2;
+        match self.shape {
+            Shape::Text(ref text) => {
+                writeln!(stream, "{:indent$}Text {:?}", "", text, indent = indent)
+            }
+            Shape::Argument(ref argument) => {
+                if !argument.prefix.is_empty() {
+                    writeln!(
+                        stream,
+                        "{:indent$}Text {:?}",
+                        "",
+                        argument.prefix,
+                        indent = indent
+                    )?;
+                }
+                writeln!(
+                    stream,
+                    "{:indent$}Argument {}",
+                    "",
+                    &*argument.name,
+                    indent = indent
+                )?;
+                if !argument.suffix.is_empty() {
+                    writeln!(
+                        stream,
+                        "{:indent$}Text {:?}",
+                        "",
+                        argument.suffix,
+                        indent = indent
+                    )?;
+                }
+                Ok(())
+            }
+            Shape::Parts(ref parts) => parts.iter().try_for_each(|part| part.dump(stream, indent)),
+        }
     }
 
     /// Write a message to a stream.
     ///
     /// This shouldn't be called directly in the usual case.
     /// Use `Context::write` or `Context::format` instead.
-    pub fn write_message<'f>(
+    pub fn write_message(
         &self,
         ctx: &Context,
         stream: &mut fmt::Write,
-        args: Option<&Args<'f>>,
+        args: Option<&dyn Arguments>,
     ) -> fmt::Result {
-        for part in &self.parts {
-            try!(part.apply_format(ctx, stream, args));
+        match self.shape {
+            Shape::Text(ref text) => stream.write_str(text),
+            Shape::Argument(ref argument) => {
+                let value = args
+                    .and_then(|args| args.lookup(&argument.name))
+                    .ok_or(fmt::Error {})?;
+                stream.write_str(&argument.prefix)?;
+                write!(stream, "{}", value)?;
+                stream.write_str(&argument.suffix)
+            }
+            Shape::Parts(ref parts) => {
+                for part in parts.iter() {
+                    part.apply_format(ctx, stream, args)?;
+                }
+                Ok(())
+            }
         }
-        Ok(())
     }
 }
+
+/// Write literal text as a span of `SpanKind::Text`.
+fn write_text(output: &mut MappedOutput, text: &str) -> fmt::Result {
+    let start = output.len();
+    fmt::Write::write_str(output, text)?;
+    output.record(start, SpanKind::Text);
+    Ok(())
+}
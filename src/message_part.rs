@@ -6,16 +6,92 @@
 
 use std::fmt;
 
-use {Args, Context};
+use prelude::*;
+use {ArgumentType, Arguments, Context, Error, MappedOutput, SpanKind};
 
 /// Part of a message. May be something that requires formatting a
 /// value or just plain text.
-pub trait MessagePart: fmt::Debug {
+///
+/// Parts must be `Send` and `Sync`, so that parsed messages can be
+/// shared between threads, such as by a [`Catalog`].
+///
+/// [`Catalog`]: struct.Catalog.html
+pub trait MessagePart: fmt::Debug + Send + Sync {
     /// Format this message part.
-    fn apply_format<'f>(
+    fn apply_format(
         &self,
         ctx: &Context,
         stream: &mut fmt::Write,
-        args: Option<&Args<'f>>,
+        args: Option<&dyn Arguments>,
     ) -> fmt::Result;
+
+    /// Format this message part, recording which spans of the output
+    /// it produced.
+    ///
+    /// The default records all of the output as a single span of
+    /// `SpanKind::Other`, so parts should override this to say what
+    /// they are or to map the messages that they contain.
+    fn apply_format_mapped(
+        &self,
+        ctx: &Context,
+        output: &mut MappedOutput,
+        args: Option<&dyn Arguments>,
+    ) -> fmt::Result {
+        let start = output.len();
+        self.apply_format(ctx, output, args)?;
+        output.record(start, SpanKind::Other);
+        Ok(())
+    }
+
+    /// Check that `args` holds the arguments that this message part
+    /// needs, to explain why formatting it failed.
+    ///
+    /// The default finds no problems, so parts which use arguments
+    /// should override this.
+    fn check_arguments(&self, _ctx: &Context, _args: Option<&dyn Arguments>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Add the arguments that this message part uses, and the type of
+    /// value that it needs for each, to `arguments`, for
+    /// [`Message::arguments`].
+    ///
+    /// The default adds nothing, so parts which use arguments should
+    /// override this.
+    ///
+    /// [`Message::arguments`]: struct.Message.html#method.arguments
+    fn collect_arguments(&self, _arguments: &mut Vec<(String, ArgumentType)>) {}
+
+    /// Write an outline of this message part, starting each line with
+    /// `indent` spaces, for [`Message::dump`].
+    ///
+    /// The default writes the `Debug` form of the part on one line.
+    ///
+    /// [`Message::dump`]: struct.Message.html#method.dump
+    fn dump(&self, stream: &mut dyn fmt::Write, indent: usize) -> fmt::Result {
+        writeln!(stream, "{:indent$}{:?}", "", self, indent = indent)
+    }
+
+    /// The number of bytes used by this message part, including any
+    /// heap allocations that it owns.
+    ///
+    /// The default only counts the part itself, so parts which own
+    /// heap allocations should override this.
+    fn memory_footprint(&self) -> usize {
+        size_of_val(self)
+    }
+
+    /// Whether this message part only writes what follows from its
+    /// arguments and the context, without side effects or other
+    /// messages, for [`Message::is_pure`].
+    ///
+    /// The default is false, so that a sandboxed [`Catalog`] refuses
+    /// parts which it knows nothing about. Parts which are pure may
+    /// override this.
+    ///
+    /// [`Message::is_pure`]: struct.Message.html#method.is_pure
+    /// [`Catalog`]: struct.Catalog.html
+    fn is_pure(&self) -> bool {
+        false
+    }
 }
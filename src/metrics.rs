@@ -0,0 +1,166 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+/// Counters for how messages are being formatted, for watching the
+/// health of localization in production.
+///
+/// Set as the `metrics` of a [`Context`], these are called by each
+/// call which formats a message with that context, by
+/// [`Catalog::format`] and by [`FormatCache`]. Every method does
+/// nothing by default, so an implementation only needs the counters it
+/// reports. They are called while formatting, so they should be cheap,
+/// such as incrementing an atomic counter.
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use message_format::{icu, Context, Metrics};
+///
+/// #[derive(Debug, Default)]
+/// struct Counters {
+///     formatted: AtomicUsize,
+///     missing_arguments: AtomicUsize,
+/// }
+///
+/// impl Metrics for Counters {
+///     fn formatted(&self) {
+///         self.formatted.fetch_add(1, Ordering::Relaxed);
+///     }
+///
+///     fn failed(&self, code: &'static str) {
+///         if code == "missing-argument" {
+///             self.missing_arguments.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+///
+/// let counters = Arc::new(Counters::default());
+/// let ctx = Context {
+///     metrics: Some(counters.clone()),
+///     ..Context::default()
+/// };
+/// let message = icu::parse("Hello, {name}!").unwrap();
+/// assert!(ctx.try_format(&message, None).is_err());
+/// assert_eq!(counters.formatted.load(Ordering::Relaxed), 1);
+/// assert_eq!(counters.missing_arguments.load(Ordering::Relaxed), 1);
+/// ```
+///
+/// This requires the `std` feature.
+///
+/// [`Context`]: struct.Context.html
+/// [`Catalog::format`]: struct.Catalog.html#method.format
+/// [`FormatCache`]: struct.FormatCache.html
+pub trait Metrics: fmt::Debug + Send + Sync {
+    /// A message was formatted, whether or not it could be.
+    fn formatted(&self) {}
+
+    /// A message could not be formatted, with an error of the kind
+    /// `code`, as given by `Error::code`.
+    ///
+    /// A message formatted with `Context::format` or `Context::write`
+    /// is only looked into for the kind of error when it fails.
+    fn failed(&self, _code: &'static str) {}
+
+    /// A [`FormatCache`] already held the result of formatting.
+    ///
+    /// [`FormatCache`]: struct.FormatCache.html
+    fn cache_hit(&self) {}
+
+    /// A [`FormatCache`] did not hold the result of formatting, so the
+    /// message was formatted.
+    ///
+    /// [`FormatCache`]: struct.FormatCache.html
+    fn cache_miss(&self) {}
+
+    /// The missing argument `name` was replaced by text chosen by
+    /// `FormatOptions::missing_arguments`.
+    fn fallback(&self, _name: &str) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::Metrics;
+    use icu::parse;
+    use {Catalog, Context, FormatCache, FormatOptions, MissingArguments, Value};
+
+    #[derive(Debug, Default)]
+    struct Events(Mutex<Vec<String>>);
+
+    impl Events {
+        fn push(&self, event: &str) {
+            self.0.lock().unwrap().push(event.to_string());
+        }
+
+        fn take(&self) -> Vec<String> {
+            self.0.lock().unwrap().drain(..).collect()
+        }
+    }
+
+    impl Metrics for Events {
+        fn formatted(&self) {
+            self.push("formatted");
+        }
+
+        fn failed(&self, code: &'static str) {
+            self.push(code);
+        }
+
+        fn cache_hit(&self) {
+            self.push("hit");
+        }
+
+        fn cache_miss(&self) {
+            self.push("miss");
+        }
+
+        fn fallback(&self, name: &str) {
+            self.push(&format!("fallback {}", name));
+        }
+    }
+
+    #[test]
+    fn activity_is_counted() {
+        let events = Arc::new(Events::default());
+        let ctx = Context {
+            metrics: Some(events.clone()),
+            ..Context::default()
+        };
+        let message = parse("Hello, {name}!").unwrap();
+        assert_eq!(ctx.format(&message, None), "");
+        assert_eq!(events.take(), ["formatted", "missing-argument"]);
+
+        let options = FormatOptions {
+            missing_arguments: MissingArguments::Name,
+            ..FormatOptions::default()
+        };
+        ctx.format_with(&message, None, &options).unwrap();
+        assert_eq!(events.take(), ["fallback name", "formatted"]);
+
+        let mut cache = FormatCache::new(10);
+        let args = [("name", Value::Str("Ann"))];
+        cache.format(&ctx, "hello", &message, &args);
+        cache.format(&ctx, "hello", &message, &args);
+        assert_eq!(events.take(), ["miss", "formatted", "hit"]);
+
+        let mut catalog = Catalog::new();
+        catalog.insert("welcome", parse("Welcome to {@app}").unwrap());
+        assert!(catalog.format(&ctx, "welcome", None).is_err());
+        assert!(catalog.format(&ctx, "missing", None).is_err());
+        assert_eq!(
+            events.take(),
+            [
+                "formatted",
+                "missing-message",
+                "formatted",
+                "missing-message"
+            ]
+        );
+    }
+}
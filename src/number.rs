@@ -0,0 +1,1262 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[cfg(feature = "std")]
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::fmt;
+
+use prelude::*;
+use {english_ordinal_classifier, Context, Decimal, Error, PluralCategory};
+
+/// The characters that a language uses to write numbers.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Symbols<'a> {
+    /// The separator between the whole part of a number and its
+    /// fraction.
+    pub decimal: char,
+    /// The separator between groups of three digits.
+    pub group: char,
+    /// What is written for a value which is not a number.
+    pub nan: &'static str,
+    /// The backend which writes the digits instead, along with the
+    /// context that it writes them for.
+    pub backend: Option<(&'a dyn NumberBackend, &'a Context)>,
+}
+
+/// Another engine for writing numbers, such as the one which the rest
+/// of an application already uses, so that numbers look the same in
+/// messages as everywhere else.
+///
+/// Set as the `number_backend` of a [`Context`], it writes the digits of
+/// each `number`, `ordinal`, `bytes` and long `duration` argument
+/// formatted with that context, with the grouping and decimal separator
+/// that it chooses. This crate still writes the sign, the currency, the
+/// percent sign and the suffixes of ordinals and units around them, as
+/// well as infinity and values which are not a number.
+///
+/// ```
+/// use std::fmt;
+/// use std::sync::Arc;
+/// use message_format::{icu, Context, NumberBackend};
+///
+/// /// Groups digits as in India, such as `12,34,567`.
+/// #[derive(Debug)]
+/// struct Indian;
+///
+/// impl NumberBackend for Indian {
+///     fn write_digits(
+///         &self,
+///         _ctx: &Context,
+///         stream: &mut dyn fmt::Write,
+///         whole: &str,
+///         fraction: &str,
+///         grouping: bool,
+///     ) -> fmt::Result {
+///         for (index, digit) in whole.char_indices() {
+///             let left = whole.len() - index;
+///             if grouping && index > 0 && (left == 3 || left > 3 && left % 2 == 1) {
+///                 stream.write_char(',')?;
+///             }
+///             stream.write_char(digit)?;
+///         }
+///         if !fraction.is_empty() {
+///             write!(stream, ".{}", fraction)?;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// # #[cfg(feature = "std")]
+/// # fn main() {
+/// let ctx = Context {
+///     number_backend: Some(Arc::new(Indian)),
+///     ..Context::default()
+/// };
+/// let m = icu::parse("{n, number} people").unwrap();
+/// let args = [("n", 1234567.into())];
+/// assert_eq!(ctx.format(&m, Some(&args)), "12,34,567 people");
+/// # }
+/// # #[cfg(not(feature = "std"))]
+/// # fn main() {}
+/// ```
+///
+/// The `number_backend` of a `Context` requires the `std` feature.
+///
+/// [`Context`]: struct.Context.html
+pub trait NumberBackend: fmt::Debug + Send + Sync {
+    /// Write a number without its sign for the language of `ctx`, given
+    /// as the decimal digits of its whole part and of its fraction,
+    /// which is empty for a whole number. The digits of the whole part
+    /// should be grouped if `grouping` is set.
+    fn write_digits(
+        &self,
+        ctx: &Context,
+        stream: &mut dyn fmt::Write,
+        whole: &str,
+        fraction: &str,
+        grouping: bool,
+    ) -> fmt::Result;
+}
+
+/// The symbols of the languages which do not write numbers the way
+/// English does, from the CLDR.
+const SYMBOLS: &[(&str, char, char)] = &[
+    ("cs", ',', '\u{a0}'),
+    ("da", ',', '.'),
+    ("de", ',', '.'),
+    ("es", ',', '.'),
+    ("fi", ',', '\u{a0}'),
+    ("fr", ',', '\u{202f}'),
+    ("id", ',', '.'),
+    ("it", ',', '.'),
+    ("nb", ',', '\u{a0}'),
+    ("nl", ',', '.'),
+    ("pl", ',', '\u{a0}'),
+    ("pt", ',', '.'),
+    ("ru", ',', '\u{a0}'),
+    ("sv", ',', '\u{a0}'),
+    ("tr", ',', '.'),
+    ("uk", ',', '\u{a0}'),
+];
+
+/// What the languages which do not write `NaN` write for a value which
+/// is not a number, from the CLDR.
+const NAN: &[(&str, &str)] = &[("fi", "epäluku"), ("ru", "не\u{a0}число")];
+
+/// The symbols of English, which are used for the languages which are
+/// not in `SYMBOLS`.
+pub(crate) const ENGLISH: Symbols<'static> = Symbols {
+    decimal: '.',
+    group: ',',
+    nan: "NaN",
+    backend: None,
+};
+
+/// The symbols for the language of `ctx`, looked up in the tables of
+/// the languages which do not write numbers the way English does.
+fn language_symbols(ctx: &Context) -> Symbols<'static> {
+    let symbols = SYMBOLS
+        .iter()
+        .find(|symbols| ctx.is_language(symbols.0))
+        .map_or(ENGLISH, |symbols| Symbols {
+            decimal: symbols.1,
+            group: symbols.2,
+            ..ENGLISH
+        });
+    NAN.iter()
+        .find(|nan| ctx.is_language(nan.0))
+        .map_or(symbols, |nan| Symbols {
+            nan: nan.1,
+            ..symbols
+        })
+}
+
+/// The number of languages whose symbols each thread keeps, so that
+/// language tags from requests can not fill its memory.
+#[cfg(feature = "std")]
+const CACHED_LANGUAGES: usize = 32;
+
+#[cfg(feature = "std")]
+thread_local! {
+    /// The symbols of each language which numbers have been written
+    /// for on this thread, by primary language subtag, so that they
+    /// are only looked up once and threads which format at the same
+    /// time do not wait for each other.
+    static CACHED_SYMBOLS: RefCell<Vec<(String, Symbols<'static>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The symbols for the language of `ctx`, along with its number
+/// backend, if any.
+pub(crate) fn symbols(ctx: &Context) -> Symbols<'_> {
+    #[cfg(feature = "std")]
+    {
+        let language = ctx
+            .language_tag
+            .language
+            .as_ref()
+            .map_or("", |language| &language[..]);
+        let symbols = CACHED_SYMBOLS.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            match cache
+                .iter()
+                .find(|cached| cached.0.eq_ignore_ascii_case(language))
+            {
+                Some(cached) => cached.1,
+                None => {
+                    let symbols = language_symbols(ctx);
+                    if cache.len() < CACHED_LANGUAGES {
+                        cache.push((language.to_ascii_lowercase(), symbols));
+                    }
+                    symbols
+                }
+            }
+        });
+        match ctx.number_backend {
+            Some(ref backend) => Symbols {
+                backend: Some((&**backend, ctx)),
+                ..symbols
+            },
+            None => symbols,
+        }
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        language_symbols(ctx)
+    }
+}
+
+/// The value of a `number` argument.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Number {
+    Integer(i128),
+    /// A whole number which is too large for an `i128`.
+    Unsigned(u128),
+    Float(f64),
+    Decimal(Decimal),
+}
+
+impl From<u128> for Number {
+    fn from(value: u128) -> Number {
+        match i128::try_from(value) {
+            Ok(value) => Number::Integer(value),
+            Err(_) => Number::Unsigned(value),
+        }
+    }
+}
+
+impl Number {
+    pub(crate) fn is_finite(self) -> bool {
+        match self {
+            Number::Integer(_) | Number::Unsigned(_) | Number::Decimal(_) => true,
+            Number::Float(f) => f.is_finite(),
+        }
+    }
+
+    fn is_negative(self) -> bool {
+        match self {
+            Number::Integer(n) => n < 0,
+            Number::Unsigned(_) => false,
+            Number::Float(f) => f < 0.0,
+            Number::Decimal(d) => d.mantissa < 0,
+        }
+    }
+
+    /// This number multiplied by `scale`, if the result fits.
+    fn scaled(self, scale: i64) -> Option<Number> {
+        match self {
+            Number::Integer(n) => n.checked_mul(i128::from(scale)).map(Number::Integer),
+            Number::Unsigned(n) => u128::try_from(scale)
+                .ok()
+                .and_then(|scale| n.checked_mul(scale))
+                .map(Number::Unsigned),
+            // Infinity and NaN stay as they are, but a finite float
+            // must not overflow.
+            Number::Float(f) => Some(f * scale as f64)
+                .filter(|scaled| scaled.is_finite() || !f.is_finite())
+                .map(Number::Float),
+            Number::Decimal(d) => d
+                .mantissa
+                .checked_mul(i128::from(scale))
+                .map(|mantissa| Number::Decimal(Decimal::new(mantissa, d.scale))),
+        }
+    }
+
+    /// This number rounded half to even to at most `maximum` digits
+    /// after the decimal separator, without the trailing zeros past
+    /// `minimum` digits and with zeros to make up `minimum` digits, if
+    /// the result fits.
+    fn rounded(self, minimum: u32, maximum: Option<u32>) -> Option<Number> {
+        let decimal = match self {
+            Number::Integer(_) | Number::Unsigned(_) if minimum == 0 => return Some(self),
+            Number::Integer(n) => Decimal::new(n, 0),
+            Number::Unsigned(n) => Decimal::new(i128::try_from(n).ok()?, 0),
+            Number::Float(f) if !f.is_finite() => return Some(self),
+            Number::Float(f) => {
+                let (whole, fraction) = float_digits(f);
+                let sign = if f < 0.0 { "-" } else { "" };
+                // A float too large for a decimal has no digits after
+                // its separator to round.
+                match format!("{}{}.{}", sign, whole, fraction).parse() {
+                    Ok(decimal) => decimal,
+                    Err(_) => return Some(self),
+                }
+            }
+            Number::Decimal(d) => d,
+        };
+        let mut digits = maximum.map_or(decimal.scale, |maximum| decimal.scale.min(maximum));
+        let mut mantissa = if digits < decimal.scale {
+            let divisor = 10i128.checked_pow(decimal.scale - digits)?;
+            let quotient = decimal.mantissa / divisor;
+            let remainder = (decimal.mantissa % divisor).abs();
+            let half = divisor / 2;
+            if remainder > half || (remainder == half && quotient % 2 != 0) {
+                quotient + decimal.mantissa.signum()
+            } else {
+                quotient
+            }
+        } else {
+            decimal.mantissa
+        };
+        // Without a maximum, a decimal keeps the digits that it has.
+        if maximum.is_some() {
+            while digits > minimum && mantissa % 10 == 0 {
+                mantissa /= 10;
+                digits -= 1;
+            }
+        }
+        if digits < minimum {
+            mantissa = mantissa.checked_mul(10i128.checked_pow(minimum - digits)?)?;
+            digits = minimum;
+        }
+        Some(Number::Decimal(Decimal::new(mantissa, digits)))
+    }
+
+    /// Write this number, separating the digits of its whole part into
+    /// groups of three if `grouping` is set.
+    pub(crate) fn write(
+        self,
+        stream: &mut dyn fmt::Write,
+        symbols: Symbols,
+        grouping: bool,
+    ) -> fmt::Result {
+        if self.is_negative() {
+            stream.write_char('-')?;
+        }
+        self.write_unsigned(stream, symbols, grouping)
+    }
+
+    /// Write this number without its sign.
+    fn write_unsigned(
+        self,
+        stream: &mut dyn fmt::Write,
+        symbols: Symbols,
+        grouping: bool,
+    ) -> fmt::Result {
+        match self {
+            Number::Integer(n) => {
+                write_parts(stream, &n.unsigned_abs().to_string(), "", symbols, grouping)
+            }
+            Number::Unsigned(n) => write_parts(stream, &n.to_string(), "", symbols, grouping),
+            Number::Decimal(d) => {
+                let digits = d.mantissa.unsigned_abs().to_string();
+                let scale = d.scale as usize;
+                if digits.len() > scale {
+                    let (whole, fraction) = digits.split_at(digits.len() - scale);
+                    write_parts(stream, whole, fraction, symbols, grouping)
+                } else {
+                    let fraction = format!("{:0>width$}", digits, width = scale);
+                    write_parts(stream, "0", &fraction, symbols, grouping)
+                }
+            }
+            Number::Float(f) if f.is_nan() => stream.write_str(symbols.nan),
+            Number::Float(f) if f.is_infinite() => stream.write_char('∞'),
+            Number::Float(f) => {
+                let (whole, fraction) = float_digits(f);
+                write_parts(stream, &whole, &fraction, symbols, grouping)
+            }
+        }
+    }
+}
+
+/// The digits of the whole part and the fraction of a finite `value`,
+/// without its sign.
+///
+/// The value is rounded to the 15 significant digits which an `f64`
+/// always holds and trailing zeros are dropped, so that the error of
+/// floating point arithmetic is not shown, as with `0.1 + 0.2`, whose
+/// shortest exact form is `0.30000000000000004`.
+fn float_digits(value: f64) -> (String, String) {
+    let scientific = format!("{:.*e}", f64::DIGITS as usize - 1, value);
+    let mut parts = scientific.split('e');
+    let mantissa = parts.next().unwrap_or("");
+    let exponent: i64 = parts.next().and_then(|e| e.parse().ok()).unwrap_or(0);
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let digits = digits.trim_end_matches('0');
+    if digits.is_empty() {
+        return ("0".to_string(), String::new());
+    }
+    // The number of digits before the decimal separator.
+    let whole = exponent + 1;
+    if whole <= 0 {
+        let zeros = "0".repeat(whole.unsigned_abs() as usize);
+        ("0".to_string(), zeros + digits)
+    } else if whole as usize >= digits.len() {
+        let zeros = "0".repeat(whole as usize - digits.len());
+        (digits.to_string() + &zeros, String::new())
+    } else {
+        let (whole, fraction) = digits.split_at(whole as usize);
+        (whole.to_string(), fraction.to_string())
+    }
+}
+
+/// Write `value`, separating its digits into groups of three if
+/// `grouping` is set.
+pub(crate) fn write_integer(
+    stream: &mut dyn fmt::Write,
+    value: i64,
+    symbols: Symbols,
+    grouping: bool,
+) -> fmt::Result {
+    if value < 0 {
+        stream.write_char('-')?;
+    }
+    write_digits(stream, value.unsigned_abs(), symbols, grouping)
+}
+
+/// Write the digits of `value` without a sign.
+pub(crate) fn write_digits(
+    stream: &mut dyn fmt::Write,
+    value: u64,
+    symbols: Symbols,
+    grouping: bool,
+) -> fmt::Result {
+    write_parts(stream, &value.to_string(), "", symbols, grouping)
+}
+
+/// Write the digits of the whole part of a number and of its fraction,
+/// which is empty for a whole number, separating the digits of the
+/// whole part into groups of three if `grouping` is set, or else with
+/// the number backend of `symbols`.
+fn write_parts(
+    stream: &mut dyn fmt::Write,
+    whole: &str,
+    fraction: &str,
+    symbols: Symbols,
+    grouping: bool,
+) -> fmt::Result {
+    if let Some((backend, ctx)) = symbols.backend {
+        return backend.write_digits(ctx, stream, whole, fraction, grouping);
+    }
+    for (index, digit) in whole.char_indices() {
+        if grouping && index > 0 && (whole.len() - index).is_multiple_of(3) {
+            stream.write_char(symbols.group)?;
+        }
+        stream.write_char(digit)?;
+    }
+    if !fraction.is_empty() {
+        stream.write_char(symbols.decimal)?;
+        stream.write_str(fraction)?;
+    }
+    Ok(())
+}
+
+/// Write `value` as an ordinal number, such as "1st" in English or
+/// "1." in German.
+///
+/// Languages whose ordinals depend on the gender of what is counted
+/// use the masculine form. Languages without suffix data here use the
+/// English suffixes.
+pub(crate) fn write_ordinal(stream: &mut dyn fmt::Write, ctx: &Context, value: i64) -> fmt::Result {
+    write_integer(stream, value, symbols(ctx), true)?;
+    let suffix = if ["cs", "da", "de", "fi", "nb", "pl", "tr"]
+        .iter()
+        .any(|language| ctx.is_language(language))
+    {
+        "."
+    } else if ctx.is_language("fr") {
+        if value == 1 {
+            "er"
+        } else {
+            "e"
+        }
+    } else if ctx.is_language("nl") {
+        "e"
+    } else if ctx.is_language("es") {
+        ".º"
+    } else if ctx.is_language("it") || ctx.is_language("pt") {
+        "º"
+    } else if ctx.is_language("sv") {
+        match value.unsigned_abs() % 100 {
+            11 | 12 => ":e",
+            n if n % 10 == 1 || n % 10 == 2 => ":a",
+            _ => ":e",
+        }
+    } else {
+        match english_ordinal_classifier(value) {
+            PluralCategory::One => "st",
+            PluralCategory::Two => "nd",
+            PluralCategory::Few => "rd",
+            _ => "th",
+        }
+    };
+    stream.write_str(suffix)
+}
+
+/// How to write a `number` argument which is infinite or not a number.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NonFiniteNumbers {
+    /// Write `∞` or `-∞`, or the symbol of the language for a value
+    /// which is not a number, such as `NaN` in English.
+    Symbols,
+    /// Fail, as if the argument were not a number. A `plural` still
+    /// chooses its `other` branch.
+    Fail,
+}
+
+/// How to show the currency of an amount.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CurrencyDisplay {
+    /// A symbol such as `$`, or the code if there is none.
+    Symbol,
+    /// The ISO 4217 code, such as `USD`.
+    Code,
+    /// The name of the currency in the plural form for the amount,
+    /// such as "US dollars".
+    Name,
+}
+
+/// How a `number` argument is formatted.
+///
+/// These are usually given by an ICU number skeleton in a pattern,
+/// such as `{price, number, ::currency/EUR unit-width-full-name}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumberOptions {
+    /// Whether to separate groups of digits, as in `1,234`.
+    pub grouping: bool,
+    /// The ISO 4217 code of the currency that the number is an amount
+    /// of, in whole units.
+    pub currency: Option<String>,
+    /// How to show the currency.
+    pub currency_display: CurrencyDisplay,
+    /// Whether the number is a percentage, written with the percent
+    /// sign of the language, such as `50%` in English or `50 %` in
+    /// German.
+    pub percent: bool,
+    /// What to multiply the number by before it is written. A ratio,
+    /// such as 1 for 100 %, needs a scale of 100, while a percentage
+    /// which is already scaled needs a scale of 1.
+    pub scale: i64,
+    /// The fewest digits to write after the decimal separator, which
+    /// are made up with zeros, such as 2 for `1,234.50`.
+    pub minimum_fraction_digits: u32,
+    /// The most digits to write after the decimal separator, to which
+    /// the number is rounded half to even and past the minimum of which
+    /// trailing zeros are left out. `None` writes the digits that the
+    /// number has.
+    pub maximum_fraction_digits: Option<u32>,
+}
+
+impl Default for NumberOptions {
+    fn default() -> Self {
+        NumberOptions {
+            grouping: true,
+            currency: None,
+            currency_display: CurrencyDisplay::Symbol,
+            percent: false,
+            scale: 1,
+            minimum_fraction_digits: 0,
+            maximum_fraction_digits: None,
+        }
+    }
+}
+
+impl NumberOptions {
+    /// Read the options from an ICU number skeleton, without the
+    /// leading `::`.
+    ///
+    /// The tokens `currency/XXX`, `unit-width-short`,
+    /// `unit-width-iso-code`, `unit-width-full-name`, `group-off`,
+    /// `percent` and `scale/N` for a whole number `N` are understood,
+    /// along with `precision-integer`, and `.00` or `.0##` for the fewest
+    /// and most digits after the separator. As in ICU, `percent` alone is for a value
+    /// which is already scaled, and `percent scale/100` is for a ratio.
+    /// Error positions are offsets in `skeleton`.
+    ///
+    /// ```
+    /// use message_format::{CurrencyDisplay, NumberOptions};
+    ///
+    /// let options = NumberOptions::from_skeleton("currency/USD unit-width-full-name").unwrap();
+    /// assert_eq!(options.currency.as_ref().unwrap(), "USD");
+    /// assert_eq!(options.currency_display, CurrencyDisplay::Name);
+    /// ```
+    pub fn from_skeleton(skeleton: &str) -> Result<Self, Error> {
+        let mut options = NumberOptions::default();
+        let mut rest = skeleton;
+        loop {
+            let rest_trimmed = rest.trim_start();
+            if rest_trimmed.is_empty() {
+                return Ok(options);
+            }
+            let position = skeleton.len() - rest_trimmed.len();
+            let end = rest_trimmed
+                .find(char::is_whitespace)
+                .unwrap_or(rest_trimmed.len());
+            let token = &rest_trimmed[..end];
+            rest = &rest_trimmed[end..];
+            if let Some(code) = token.strip_prefix("currency/") {
+                if code.len() != 3 || !code.bytes().all(|b| b.is_ascii_uppercase()) {
+                    return Err(Error::parse(
+                        position + "currency/".len(),
+                        "Expected a three letter currency code",
+                    ));
+                }
+                options.currency = Some(code.to_string());
+                continue;
+            }
+            if let Some(scale) = token.strip_prefix("scale/") {
+                options.scale = scale.parse().map_err(|_| {
+                    Error::parse(position + "scale/".len(), "Expected a whole number scale")
+                })?;
+                continue;
+            }
+            if let Some(digits) = token.strip_prefix('.') {
+                let zeros = digits.len() - digits.trim_start_matches('0').len();
+                let hashes = digits[zeros..].len() - digits[zeros..].trim_start_matches('#').len();
+                if zeros + hashes == 0 || zeros + hashes != digits.len() {
+                    return Err(Error::parse(
+                        position + 1 + zeros + hashes,
+                        "Expected a number of zeros and then of '#'",
+                    ));
+                }
+                options.minimum_fraction_digits = zeros as u32;
+                options.maximum_fraction_digits = Some((zeros + hashes) as u32);
+                continue;
+            }
+            match token {
+                "precision-integer" => {
+                    options.minimum_fraction_digits = 0;
+                    options.maximum_fraction_digits = Some(0);
+                }
+                "unit-width-short" => options.currency_display = CurrencyDisplay::Symbol,
+                "unit-width-iso-code" => options.currency_display = CurrencyDisplay::Code,
+                "unit-width-full-name" => options.currency_display = CurrencyDisplay::Name,
+                "group-off" => options.grouping = false,
+                "percent" => options.percent = true,
+                _ => return Err(Error::parse(position, "Unsupported number skeleton token")),
+            }
+        }
+    }
+}
+
+/// Symbols for currencies, which are the same in every language here.
+const CURRENCY_SYMBOLS: &[(&str, &str)] = &[("EUR", "€"), ("GBP", "£"), ("JPY", "¥"), ("USD", "$")];
+
+/// The singular and plural names of currencies, from the CLDR.
+const CURRENCY_NAMES: &[(&str, &str, &str, &str)] = &[
+    ("de", "EUR", "Euro", "Euro"),
+    ("de", "GBP", "Britisches Pfund", "Britische Pfund"),
+    ("de", "JPY", "Japanischer Yen", "Japanische Yen"),
+    ("de", "RUB", "Russischer Rubel", "Russische Rubel"),
+    ("de", "USD", "US-Dollar", "US-Dollar"),
+    ("en", "EUR", "euro", "euros"),
+    ("en", "GBP", "British pound", "British pounds"),
+    ("en", "JPY", "Japanese yen", "Japanese yen"),
+    ("en", "RUB", "Russian ruble", "Russian rubles"),
+    ("en", "USD", "US dollar", "US dollars"),
+    ("fr", "EUR", "euro", "euros"),
+    ("fr", "GBP", "livre sterling", "livres sterling"),
+    ("fr", "JPY", "yen japonais", "yens japonais"),
+    ("fr", "RUB", "rouble russe", "roubles russes"),
+    (
+        "fr",
+        "USD",
+        "dollar des États-Unis",
+        "dollars des États-Unis",
+    ),
+];
+
+/// The name of `amount` units of `currency`, using the plural rules of
+/// the language of `ctx`.
+fn currency_name(ctx: &Context, currency: &str, amount: Number) -> Option<&'static str> {
+    let language = ["de", "fr"]
+        .iter()
+        .find(|language| ctx.is_language(language))
+        .map_or("en", |language| *language);
+    let one = match (language, amount) {
+        ("fr", Number::Integer(n)) => n.unsigned_abs() <= 1,
+        ("fr", Number::Float(f)) => f.abs() < 2.0,
+        (_, Number::Integer(n)) => n.unsigned_abs() == 1,
+        (_, Number::Float(f)) => f.abs() == 1.0,
+        (_, Number::Unsigned(_)) => false,
+        // A decimal has the plural form of its digits, as in "1.00 US
+        // dollars" in English, but of its whole part in French.
+        ("fr", Number::Decimal(d)) => {
+            d.mantissa.unsigned_abs() < 10u128.checked_pow(d.scale).map_or(u128::MAX, |p| p * 2)
+        }
+        (_, Number::Decimal(d)) => d.scale == 0 && d.mantissa.unsigned_abs() == 1,
+    };
+    CURRENCY_NAMES
+        .iter()
+        .find(|names| names.0 == language && names.1 == currency)
+        .map(|names| if one { names.2 } else { names.3 })
+}
+
+/// The languages whose percent sign is not written right after the
+/// number, with whether it comes first and what separates it from the
+/// number, from the CLDR.
+const PERCENT_PATTERNS: &[(&str, bool, &str)] = &[
+    ("cs", false, "\u{a0}"),
+    ("da", false, "\u{a0}"),
+    ("de", false, "\u{a0}"),
+    ("es", false, "\u{a0}"),
+    ("fi", false, "\u{a0}"),
+    ("fr", false, "\u{202f}"),
+    ("nb", false, "\u{a0}"),
+    ("ru", false, "\u{a0}"),
+    ("sv", false, "\u{a0}"),
+    ("tr", true, ""),
+];
+
+/// Write `value` as a percentage, in the language of `ctx`.
+fn write_percent(
+    stream: &mut dyn fmt::Write,
+    ctx: &Context,
+    value: Number,
+    grouping: bool,
+) -> fmt::Result {
+    let symbols = symbols(ctx);
+    let (first, separator) = PERCENT_PATTERNS
+        .iter()
+        .find(|pattern| ctx.is_language(pattern.0))
+        .map_or((false, ""), |pattern| (pattern.1, pattern.2));
+    if first {
+        if value.is_negative() {
+            stream.write_char('-')?;
+        }
+        write!(stream, "%{}", separator)?;
+        value.write_unsigned(stream, symbols, grouping)
+    } else {
+        value.write(stream, symbols, grouping)?;
+        write!(stream, "{}%", separator)
+    }
+}
+
+/// Write `value` as `options` say, in the language of `ctx`.
+pub(crate) fn write_number(
+    stream: &mut dyn fmt::Write,
+    ctx: &Context,
+    value: Number,
+    options: &NumberOptions,
+) -> fmt::Result {
+    let mut value = value.scaled(options.scale).ok_or(fmt::Error {})?;
+    if options.minimum_fraction_digits > 0 || options.maximum_fraction_digits.is_some() {
+        value = value
+            .rounded(
+                options.minimum_fraction_digits,
+                options.maximum_fraction_digits,
+            )
+            .ok_or(fmt::Error {})?;
+    }
+    if options.percent {
+        return write_percent(stream, ctx, value, options.grouping);
+    }
+    let symbols = symbols(ctx);
+    let currency = match options.currency {
+        Some(ref currency) => &currency[..],
+        None => return value.write(stream, symbols, options.grouping),
+    };
+    let unit = match options.currency_display {
+        CurrencyDisplay::Name => currency_name(ctx, currency, value),
+        CurrencyDisplay::Symbol => CURRENCY_SYMBOLS
+            .iter()
+            .find(|symbol| symbol.0 == currency)
+            .map(|symbol| symbol.1),
+        CurrencyDisplay::Code => None,
+    };
+    let unit = unit.unwrap_or(currency);
+    // The languages which write numbers as English does also put the
+    // symbol or code of a currency first.
+    let unit_first = symbols.decimal == '.';
+    if options.currency_display == CurrencyDisplay::Name {
+        value.write(stream, symbols, options.grouping)?;
+        write!(stream, " {}", unit)
+    } else if unit_first {
+        if value.is_negative() {
+            stream.write_char('-')?;
+        }
+        stream.write_str(unit)?;
+        if unit.bytes().all(|b| b.is_ascii_alphabetic()) {
+            stream.write_char('\u{a0}')?;
+        }
+        value.write_unsigned(stream, symbols, options.grouping)
+    } else {
+        value.write(stream, symbols, options.grouping)?;
+        write!(stream, "\u{a0}{}", unit)
+    }
+}
+
+/// Which multiples of a byte to use for a `bytes` argument.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ByteUnits {
+    /// Powers of 1000, as in `kB` and `MB`.
+    Decimal,
+    /// Powers of 1024, as in `KiB` and `MiB`.
+    Binary,
+}
+
+/// The decimal and binary byte units, from bytes to exabytes.
+const BYTE_UNITS: [[&str; 7]; 2] = [
+    ["B", "kB", "MB", "GB", "TB", "PB", "EB"],
+    ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"],
+];
+
+/// The byte units of French, which counts in octets.
+const OCTET_UNITS: [[&str; 7]; 2] = [
+    ["o", "ko", "Mo", "Go", "To", "Po", "Eo"],
+    ["o", "Kio", "Mio", "Gio", "Tio", "Pio", "Eio"],
+];
+
+/// Write a size of `value` bytes in the largest unit in which it is at
+/// least one, rounded to a tenth, such as "1.5 MB".
+pub(crate) fn write_bytes(
+    stream: &mut dyn fmt::Write,
+    ctx: &Context,
+    value: i64,
+    units: ByteUnits,
+) -> fmt::Result {
+    let (names, base) = match units {
+        ByteUnits::Decimal => (0, 1000),
+        ByteUnits::Binary => (1, 1024),
+    };
+    let names = if ctx.is_language("fr") {
+        &OCTET_UNITS[names]
+    } else {
+        &BYTE_UNITS[names]
+    };
+    let bytes = u128::from(value.unsigned_abs());
+    let mut unit = 0;
+    let mut size = 1;
+    // Move up a unit while the size, once rounded, would be at least
+    // one of the next unit.
+    while unit + 1 < names.len() && (bytes * 10 + size / 2) / size >= base * 10 {
+        unit += 1;
+        size *= base;
+    }
+    let tenths = (bytes * 10 + size / 2) / size;
+    if value < 0 {
+        stream.write_char('-')?;
+    }
+    let fraction = if unit > 0 && tenths % 10 != 0 {
+        (tenths % 10).to_string()
+    } else {
+        String::new()
+    };
+    write_parts(
+        stream,
+        &(tenths / 10).to_string(),
+        &fraction,
+        symbols(ctx),
+        true,
+    )?;
+    write!(stream, " {}", names[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        symbols, write_bytes, write_integer, write_number, ByteUnits, CurrencyDisplay, Number,
+        NumberOptions,
+    };
+    use prelude::*;
+    use {Context, Decimal, Error};
+
+    #[cfg(feature = "std")]
+    fn number(ctx: &Context, value: i64, options: &NumberOptions) -> String {
+        let mut output = String::new();
+        write_number(
+            &mut output,
+            ctx,
+            Number::Integer(i128::from(value)),
+            options,
+        )
+        .unwrap();
+        output
+    }
+
+    #[test]
+    fn integers_are_grouped() {
+        let ctx = Context::default();
+        for &(value, expected) in &[
+            (0, "0"),
+            (999, "999"),
+            (1000, "1,000"),
+            (-1234567, "-1,234,567"),
+            (i64::MIN, "-9,223,372,036,854,775,808"),
+        ] {
+            let mut output = String::new();
+            write_integer(&mut output, value, symbols(&ctx), true).unwrap();
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn symbols_are_cached_for_each_thread() {
+        use super::{CACHED_LANGUAGES, CACHED_SYMBOLS};
+        use std::thread;
+
+        let ctx = Context::for_language;
+        for language in &["de", "DE-at", "de-CH"] {
+            assert_eq!(symbols(&ctx(language)).decimal, ',');
+        }
+        assert_eq!(symbols(&ctx("en")).decimal, '.');
+        let german = CACHED_SYMBOLS.with(|cache| {
+            let cache = cache.borrow();
+            cache.iter().filter(|cached| cached.0 == "de").count()
+        });
+        assert_eq!(german, 1);
+
+        thread::spawn(move || {
+            assert!(CACHED_SYMBOLS.with(|cache| cache.borrow().is_empty()));
+            for index in 0..CACHED_LANGUAGES + 8 {
+                let language = format!(
+                    "q{}{}",
+                    (b'a' + index as u8 / 26) as char,
+                    (b'a' + index as u8 % 26) as char
+                );
+                assert_eq!(symbols(&ctx(&language)).group, ',');
+            }
+            assert_eq!(symbols(&ctx("fi")).nan, "epäluku");
+            assert_eq!(
+                CACHED_SYMBOLS.with(|cache| cache.borrow().len()),
+                CACHED_LANGUAGES
+            );
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn floats_are_rounded() {
+        let ctx = Context::for_language;
+        let float = |ctx: &Context, value, options: &NumberOptions| {
+            let mut output = String::new();
+            write_number(&mut output, ctx, Number::Float(value), options).unwrap();
+            output
+        };
+        let options = NumberOptions::default();
+        for &(value, expected) in &[
+            (0.1 + 0.2, "0.3"),
+            (1.0, "1"),
+            (-0.0, "0"),
+            (-2.5, "-2.5"),
+            (1e-7, "0.0000001"),
+            (1234567.125, "1,234,567.125"),
+            (1e20, "100,000,000,000,000,000,000"),
+            (123_456_789.123_456_79, "123,456,789.123457"),
+        ] {
+            assert_eq!(float(&ctx("en"), value, &options), expected);
+        }
+        assert_eq!(float(&ctx("de"), 1234.5, &options), "1.234,5");
+        assert_eq!(float(&ctx("fi"), f64::NAN, &options), "epäluku");
+
+        let percent = NumberOptions {
+            percent: true,
+            scale: 100,
+            ..NumberOptions::default()
+        };
+        assert_eq!(float(&ctx("en"), 0.125, &percent), "12.5%");
+        assert_eq!(float(&ctx("en"), f64::INFINITY, &percent), "∞%");
+        let mut output = String::new();
+        assert!(write_number(&mut output, &ctx("en"), Number::Float(1e307), &percent).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn decimals_keep_their_digits() {
+        let ctx = Context::for_language;
+        let decimal = |ctx: &Context, value: &str, options: &NumberOptions| {
+            let mut output = String::new();
+            let value = Number::Decimal(value.parse().unwrap());
+            write_number(&mut output, ctx, value, options).unwrap();
+            output
+        };
+        let mut options = NumberOptions::default();
+        for &(value, expected) in &[
+            ("0", "0"),
+            ("1234567.50", "1,234,567.50"),
+            ("-0.005", "-0.005"),
+            ("+.5", "0.5"),
+            ("12.", "12"),
+        ] {
+            assert_eq!(decimal(&ctx("en"), value, &options), expected);
+        }
+        assert_eq!(decimal(&ctx("de"), "1234.50", &options), "1.234,50");
+
+        options.currency = Some("USD".to_string());
+        options.currency_display = CurrencyDisplay::Name;
+        assert_eq!(decimal(&ctx("en"), "1", &options), "1 US dollar");
+        assert_eq!(decimal(&ctx("en"), "1.00", &options), "1.00 US dollars");
+        assert_eq!(
+            decimal(&ctx("fr"), "1.50", &options),
+            "1,50 dollar des États-Unis"
+        );
+
+        for &bad in &["", ".", "-", "1.2.3", "--1", "1e5", "1,000"] {
+            assert!(bad.parse::<Decimal>().is_err(), "{:?}", bad);
+        }
+        assert!("9".repeat(40).parse::<Decimal>().is_err());
+    }
+
+    #[test]
+    fn fraction_digits_are_fixed() {
+        let ctx = Context::default();
+        let options = NumberOptions {
+            minimum_fraction_digits: 2,
+            maximum_fraction_digits: Some(2),
+            ..NumberOptions::default()
+        };
+        let write = |value, options: &NumberOptions| {
+            let mut output = String::new();
+            write_number(&mut output, &ctx, value, options).unwrap();
+            output
+        };
+        for &(value, expected) in &[
+            (Number::Integer(1234), "1,234.00"),
+            (Number::Float(0.1 + 0.2), "0.30"),
+            (Number::Float(2.675), "2.68"),
+            (Number::Float(-0.001), "0.00"),
+            (Number::Float(f64::INFINITY), "∞"),
+            (Number::Decimal(Decimal::new(-12_345, 3)), "-12.34"),
+            (Number::Decimal(Decimal::new(12_355, 3)), "12.36"),
+            (Number::Decimal(Decimal::new(5, 0)), "5.00"),
+        ] {
+            assert_eq!(write(value, &options), expected);
+        }
+        let whole = NumberOptions {
+            maximum_fraction_digits: Some(0),
+            ..NumberOptions::default()
+        };
+        assert_eq!(write(Number::Float(2.5), &whole), "2");
+        assert_eq!(write(Number::Float(3.5), &whole), "4");
+        assert_eq!(write(Number::Unsigned(u128::MAX), &whole).len(), 51);
+        let mut output = String::new();
+        assert!(write_number(&mut output, &ctx, Number::Unsigned(u128::MAX), &options).is_err());
+
+        let up_to_two = NumberOptions {
+            minimum_fraction_digits: 1,
+            maximum_fraction_digits: Some(2),
+            ..NumberOptions::default()
+        };
+        for &(value, expected) in &[
+            (Number::Integer(3), "3.0"),
+            (Number::Float(1.5), "1.5"),
+            (Number::Float(1.256), "1.26"),
+            (Number::Decimal(Decimal::new(1_500, 3)), "1.5"),
+            (Number::Decimal(Decimal::new(1_999, 3)), "2.0"),
+        ] {
+            assert_eq!(write(value, &up_to_two), expected);
+        }
+        let at_least_two = NumberOptions {
+            minimum_fraction_digits: 2,
+            ..NumberOptions::default()
+        };
+        assert_eq!(write(Number::Float(1.2345), &at_least_two), "1.2345");
+        assert_eq!(write(Number::Float(1.5), &at_least_two), "1.50");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn backends_write_the_digits() {
+        use std::fmt;
+        use std::sync::Arc;
+
+        use super::NumberBackend;
+        use icu::parse;
+        use Value;
+
+        #[derive(Debug)]
+        struct Brackets;
+
+        impl NumberBackend for Brackets {
+            fn write_digits(
+                &self,
+                ctx: &Context,
+                stream: &mut dyn fmt::Write,
+                whole: &str,
+                fraction: &str,
+                grouping: bool,
+            ) -> fmt::Result {
+                let language = ctx.language_tag.language.as_ref().unwrap();
+                write!(stream, "[{} {}|{}|{}]", language, whole, fraction, grouping)
+            }
+        }
+
+        let ctx = Context {
+            number_backend: Some(Arc::new(Brackets)),
+            ..Context::for_language("de")
+        };
+        let m =
+            parse("{a, number, ::currency/EUR} {b, number, ::group-off} {c, ordinal} {d, bytes}")
+                .unwrap();
+        let args = [
+            ("a", Value::Decimal("-12.50".parse().unwrap())),
+            ("b", Value::Number(1000)),
+            ("c", Value::Number(3)),
+            ("d", Value::Number(1500)),
+        ];
+        assert_eq!(
+            ctx.format(&m, Some(&args)),
+            "-[de 12|50|true]\u{a0}€ [de 1000||false] [de 3||true]. [de 1|5|true] kB"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn ordinals_work() {
+        use super::write_ordinal;
+
+        let ordinal = |language: &str, value| {
+            let ctx = Context::for_language(language);
+            let mut output = String::new();
+            write_ordinal(&mut output, &ctx, value).unwrap();
+            output
+        };
+        let english: Vec<_> = [1, 2, 3, 4, 11, 12, 13, 21, 102, 1011]
+            .iter()
+            .map(|&value| ordinal("en-GB", value))
+            .collect();
+        assert_eq!(
+            english,
+            ["1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "102nd", "1,011th"]
+        );
+        assert_eq!(ordinal("de", 3), "3.");
+        assert_eq!(ordinal("fr", 1), "1er");
+        assert_eq!(ordinal("fr", 2), "2e");
+        assert_eq!(ordinal("sv", 21), "21:a");
+        assert_eq!(ordinal("sv", 12), "12:e");
+        assert_eq!(ordinal("es", 5), "5.º");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn currencies_work() {
+        let en = Context::default();
+        let de = Context::for_language("de-DE");
+        let fr = Context::for_language("fr");
+        let mut options = NumberOptions {
+            currency: Some("USD".to_string()),
+            currency_display: CurrencyDisplay::Name,
+            ..NumberOptions::default()
+        };
+        assert_eq!(number(&en, 1, &options), "1 US dollar");
+        assert_eq!(number(&en, 2, &options), "2 US dollars");
+        assert_eq!(number(&de, 2, &options), "2 US-Dollar");
+        assert_eq!(number(&fr, 0, &options), "0 dollar des États-Unis");
+        assert_eq!(
+            number(&fr, 1500, &options),
+            "1\u{202f}500 dollars des États-Unis"
+        );
+
+        options.currency = Some("RUB".to_string());
+        assert_eq!(number(&de, 1, &options), "1 Russischer Rubel");
+        assert_eq!(number(&de, 3, &options), "3 Russische Rubel");
+
+        options.currency_display = CurrencyDisplay::Symbol;
+        assert_eq!(number(&en, -5, &options), "-RUB\u{a0}5");
+        options.currency = Some("EUR".to_string());
+        assert_eq!(number(&en, 1234, &options), "€1,234");
+        assert_eq!(number(&de, 1234, &options), "1.234\u{a0}€");
+
+        options.currency_display = CurrencyDisplay::Code;
+        assert_eq!(number(&en, 5, &options), "EUR\u{a0}5");
+        options.currency = Some("XTS".to_string());
+        options.currency_display = CurrencyDisplay::Name;
+        assert_eq!(number(&en, 5, &options), "5 XTS");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn percentages_work() {
+        let ctx = Context::for_language;
+        let mut options = NumberOptions {
+            percent: true,
+            ..NumberOptions::default()
+        };
+        assert_eq!(number(&ctx("en"), 50, &options), "50%");
+        assert_eq!(number(&ctx("de"), 1250, &options), "1.250\u{a0}%");
+        assert_eq!(number(&ctx("fr"), 50, &options), "50\u{202f}%");
+        assert_eq!(number(&ctx("tr"), -50, &options), "-%50");
+
+        options.scale = 100;
+        assert_eq!(number(&ctx("en"), 2, &options), "200%");
+        let mut output = String::new();
+        assert!(write_number(
+            &mut output,
+            &ctx("en"),
+            Number::Integer(i128::MAX),
+            &options
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn byte_sizes_work() {
+        let bytes = |ctx: &Context, value, units| {
+            let mut output = String::new();
+            write_bytes(&mut output, ctx, value, units).unwrap();
+            output
+        };
+        let ctx = Context::default();
+        for &(value, decimal, binary) in &[
+            (0, "0 B", "0 B"),
+            (999, "999 B", "999 B"),
+            (1000, "1 kB", "1,000 B"),
+            (1536, "1.5 kB", "1.5 KiB"),
+            (999_949, "999.9 kB", "976.5 KiB"),
+            (999_950, "1 MB", "976.5 KiB"),
+            (-1_500_000, "-1.5 MB", "-1.4 MiB"),
+            (i64::MAX, "9.2 EB", "8 EiB"),
+        ] {
+            assert_eq!(bytes(&ctx, value, ByteUnits::Decimal), decimal);
+            assert_eq!(bytes(&ctx, value, ByteUnits::Binary), binary);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn byte_sizes_are_localized() {
+        let ctx = Context::for_language("fr-CA");
+        let mut output = String::new();
+        write_bytes(&mut output, &ctx, 1_500_000, ByteUnits::Decimal).unwrap();
+        assert_eq!(output, "1,5 Mo");
+    }
+
+    #[test]
+    fn skeletons_are_read() {
+        assert_eq!(
+            NumberOptions::from_skeleton("").unwrap(),
+            NumberOptions::default()
+        );
+        let options =
+            NumberOptions::from_skeleton(" group-off  currency/EUR unit-width-iso-code").unwrap();
+        assert!(!options.grouping);
+        assert_eq!(options.currency.as_ref().unwrap(), "EUR");
+        assert_eq!(options.currency_display, CurrencyDisplay::Code);
+
+        let options = NumberOptions::from_skeleton("percent scale/100 .00").unwrap();
+        assert!(options.percent);
+        assert_eq!(options.scale, 100);
+        assert_eq!(options.minimum_fraction_digits, 2);
+        assert_eq!(options.maximum_fraction_digits, Some(2));
+
+        let options = NumberOptions::from_skeleton(".0##").unwrap();
+        assert_eq!(options.minimum_fraction_digits, 1);
+        assert_eq!(options.maximum_fraction_digits, Some(3));
+
+        let options = NumberOptions::from_skeleton("precision-integer").unwrap();
+        assert_eq!(options.minimum_fraction_digits, 0);
+        assert_eq!(options.maximum_fraction_digits, Some(0));
+
+        for &(skeleton, expected) in &[
+            ("permille", 0),
+            ("percent scale/0.5", 14),
+            ("group-off scientific", 10),
+            ("currency/usd", 9),
+            ("group-off .#0", 12),
+            ("group-off .", 11),
+        ] {
+            match NumberOptions::from_skeleton(skeleton) {
+                Err(Error::Parse { position, .. }) => {
+                    assert_eq!(position, expected, "{}", skeleton)
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[cfg(feature = "std")]
+use language_tags::LanguageTag;
+
+use prelude::*;
+use {Context, MappedOutput, OutputSpan, SpanKind};
+
+/// What to do when a message uses an argument which was not given.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MissingArguments {
+    /// Fail with `Error::MissingArgument`.
+    Fail,
+    /// Write the name of the argument in braces, such as `{name}`, so
+    /// that the problem can be seen in the output.
+    Name,
+    /// Write nothing in place of the argument.
+    Empty,
+}
+
+/// How to escape the values of arguments.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Escape {
+    /// Write values as they are.
+    None,
+    /// Escape `&`, `<`, `>`, `"` and `'` so that values can be put in
+    /// HTML text or attributes. The text of the message itself is left
+    /// as it is, since it may hold markup, but everything else is
+    /// escaped, such as the `#` of a `plural` and the output of custom
+    /// message parts. A message which is referred to with `{@id}` is
+    /// escaped as a whole, markup included, since the values of its
+    /// arguments can not be told apart from its text.
+    Html,
+}
+
+/// Options for formatting a single message with [`Context::format_with`].
+///
+/// ```
+/// use message_format::{icu, Context, Escape, FormatOptions, MissingArguments, Value};
+///
+/// let ctx = Context::default();
+/// let m = icu::parse("<b>{name}</b> wrote to {to}").unwrap();
+/// let options = FormatOptions {
+///     missing_arguments: MissingArguments::Name,
+///     escape: Escape::Html,
+///     ..FormatOptions::default()
+/// };
+/// let args = [("name", Value::Str("Ann & Bob"))];
+/// let output = ctx.format_with(&m, Some(&args), &options).unwrap();
+/// assert_eq!(output.text(), "<b>Ann &amp; Bob</b> wrote to {to}");
+/// ```
+///
+/// [`Context::format_with`]: struct.Context.html#method.format_with
+#[derive(Clone, Debug)]
+pub struct FormatOptions {
+    /// The language to format for instead of that of the `Context`.
+    ///
+    /// This requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub language_tag: Option<LanguageTag>,
+    /// What to do when an argument is missing. A `plural` or `select`
+    /// can not do without its argument, so it always fails.
+    pub missing_arguments: MissingArguments,
+    /// Whether to surround the value of each argument, and everything
+    /// else which is not the text of the message, with Unicode
+    /// directional isolates, so that a name written right to left does
+    /// not reorder the text around it.
+    pub isolate_arguments: bool,
+    /// How to escape the values of arguments.
+    pub escape: Escape,
+    /// Whether to keep the spans recording which part of the message
+    /// produced each part of the output.
+    pub trace: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            #[cfg(feature = "std")]
+            language_tag: None,
+            missing_arguments: MissingArguments::Fail,
+            isolate_arguments: false,
+            escape: Escape::None,
+            trace: false,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// The context to format with, in the language of these options.
+    pub(crate) fn context(&self, ctx: &Context) -> Context {
+        #[cfg_attr(not(feature = "std"), allow(unused_mut))]
+        let mut ctx = ctx.clone();
+        #[cfg(feature = "std")]
+        {
+            if let Some(ref language_tag) = self.language_tag {
+                ctx.language_tag = language_tag.clone();
+            }
+        }
+        ctx
+    }
+
+    /// The text to use for the missing argument `name`, if any.
+    pub(crate) fn fallback(&self, name: &str) -> Option<String> {
+        match self.missing_arguments {
+            MissingArguments::Fail => None,
+            MissingArguments::Name => Some(format!("{{{}}}", name)),
+            MissingArguments::Empty => Some(String::new()),
+        }
+    }
+
+    /// Apply the options which change the values of arguments, and of
+    /// every other span which is not text, to formatted output, keeping
+    /// its spans only if `trace` is set.
+    pub(crate) fn finish(&self, output: MappedOutput) -> MappedOutput {
+        if !self.isolate_arguments && self.escape == Escape::None {
+            return if self.trace {
+                output
+            } else {
+                MappedOutput::from_parts(output.text().to_string(), vec![])
+            };
+        }
+        let source = output.text();
+        let mut text = String::with_capacity(source.len());
+        let mut spans = vec![];
+        let mut copied = 0;
+        for span in output.spans() {
+            text.push_str(&source[copied..span.start]);
+            let start = text.len();
+            let value = &source[span.start..span.end];
+            match span.kind {
+                SpanKind::Text => text.push_str(value),
+                _ => {
+                    if self.isolate_arguments {
+                        text.push('\u{2068}');
+                    }
+                    self.escape(value, &mut text);
+                    if self.isolate_arguments {
+                        text.push('\u{2069}');
+                    }
+                }
+            }
+            if self.trace {
+                spans.push(OutputSpan {
+                    start,
+                    end: text.len(),
+                    path: span.path.clone(),
+                    kind: span.kind.clone(),
+                });
+            }
+            copied = span.end;
+        }
+        text.push_str(&source[copied..]);
+        MappedOutput::from_parts(text, spans)
+    }
+
+    fn escape(&self, value: &str, output: &mut String) {
+        match self.escape {
+            Escape::None => output.push_str(value),
+            Escape::Html => {
+                for c in value.chars() {
+                    match c {
+                        '&' => output.push_str("&amp;"),
+                        '<' => output.push_str("&lt;"),
+                        '>' => output.push_str("&gt;"),
+                        '"' => output.push_str("&quot;"),
+                        '\'' => output.push_str("&#39;"),
+                        _ => output.push(c),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Escape, FormatOptions, MissingArguments};
+    use prelude::*;
+    use {icu, Context, SpanKind, Value};
+
+    #[test]
+    fn missing_arguments_follow_the_policy() {
+        let ctx = Context::default();
+        let m = icu::parse("Hi {name}!").unwrap();
+        let mut options = FormatOptions::default();
+        assert_eq!(
+            ctx.format_with(&m, None, &options).unwrap_err().code(),
+            "missing-argument"
+        );
+        options.missing_arguments = MissingArguments::Name;
+        assert_eq!(
+            ctx.format_with(&m, None, &options).unwrap().text(),
+            "Hi {name}!"
+        );
+        options.missing_arguments = MissingArguments::Empty;
+        assert_eq!(ctx.format_with(&m, None, &options).unwrap().text(), "Hi !");
+        let args = [("name", Value::Str("Ann"))];
+        assert_eq!(
+            ctx.format_with(&m, Some(&args), &options).unwrap().text(),
+            "Hi Ann!"
+        );
+    }
+
+    #[test]
+    fn arguments_are_isolated_and_escaped() {
+        let ctx = Context::default();
+        let m = icu::parse("{a} & {b}").unwrap();
+        let args = [("a", Value::Str("<i>")), ("b", Value::Str("אבג"))];
+        let options = FormatOptions {
+            isolate_arguments: true,
+            escape: Escape::Html,
+            trace: true,
+            ..FormatOptions::default()
+        };
+        let output = ctx.format_with(&m, Some(&args), &options).unwrap();
+        assert_eq!(
+            output.text(),
+            "\u{2068}&lt;i&gt;\u{2069} & \u{2068}אבג\u{2069}"
+        );
+        let span = output.span_at(output.text().len() - 1).unwrap();
+        assert_eq!(&output.text()[span.start..span.end], "\u{2068}אבג\u{2069}");
+        assert_eq!(span.kind, SpanKind::Argument("b".to_string()));
+    }
+
+    #[test]
+    fn everything_but_text_is_escaped() {
+        use std::fmt;
+        use Arguments;
+
+        struct Markup;
+
+        impl Arguments for Markup {
+            fn lookup(&self, name: &str) -> Option<Value<'_>> {
+                match name {
+                    "count" => Some(Value::Number(2)),
+                    _ => None,
+                }
+            }
+
+            fn write_reference(
+                &self,
+                _: &Context,
+                id: &str,
+                stream: &mut dyn fmt::Write,
+            ) -> Option<fmt::Result> {
+                Some(write!(stream, "<b>{}</b>", id))
+            }
+        }
+
+        let ctx = Context::default();
+        let m = icu::parse("<p>{count, plural, other {# by {@app}}}</p>").unwrap();
+        let options = FormatOptions {
+            isolate_arguments: true,
+            escape: Escape::Html,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            ctx.format_with(&m, Some(&Markup), &options).unwrap().text(),
+            "<p>\u{2068}2\u{2069} by \u{2068}&lt;b&gt;app&lt;/b&gt;\u{2069}</p>"
+        );
+    }
+
+    #[test]
+    fn spans_are_only_kept_when_tracing() {
+        let ctx = Context::default();
+        let m = icu::parse("Hi {name}").unwrap();
+        let args = [("name", Value::Str("Ann"))];
+        let mut options = FormatOptions::default();
+        assert!(ctx
+            .format_with(&m, Some(&args), &options)
+            .unwrap()
+            .spans()
+            .is_empty());
+        options.trace = true;
+        assert_eq!(
+            ctx.format_with(&m, Some(&args), &options)
+                .unwrap()
+                .spans()
+                .len(),
+            2
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn language_can_be_overridden() {
+        let ctx = Context::default();
+        let options = FormatOptions {
+            language_tag: Some("zh".parse().unwrap()),
+            ..FormatOptions::default()
+        };
+        assert_eq!(options.context(&ctx).language_tag.to_string(), "zh");
+    }
+}
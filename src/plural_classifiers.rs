@@ -4,7 +4,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use super::PluralCategory;
+use super::{Context, Decimal, PluralCategory};
 
 /// English cardinal plural classifier.
 ///
@@ -24,3 +24,107 @@ pub fn english_cardinal_classifier(value: i64) -> PluralCategory {
         _ => PluralCategory::Other,
     }
 }
+
+/// English ordinal plural classifier, as used by `selectordinal`.
+///
+/// Numbers ending in 1, 2 or 3 are mapped to `PluralCategory::One`,
+/// `PluralCategory::Two` and `PluralCategory::Few`, as in "1st", "2nd"
+/// and "3rd", except for those ending in 11, 12 or 13. All other
+/// numbers are mapped to `PluralCategory::Other`.
+///
+/// ```
+/// use message_format::{english_ordinal_classifier, PluralCategory};
+///
+/// assert_eq!(english_ordinal_classifier(22), PluralCategory::Two);
+/// assert_eq!(english_ordinal_classifier(13), PluralCategory::Other);
+/// assert_eq!(english_ordinal_classifier(103), PluralCategory::Few);
+/// ```
+pub fn english_ordinal_classifier(value: i64) -> PluralCategory {
+    let value = value.unsigned_abs();
+    match (value % 10, value % 100) {
+        (1, n) if n != 11 => PluralCategory::One,
+        (2, n) if n != 12 => PluralCategory::Two,
+        (3, n) if n != 13 => PluralCategory::Few,
+        _ => PluralCategory::Other,
+    }
+}
+
+/// The operands of a number which the [CLDR plural rules] look at, for
+/// its absolute value, as a `plural` finds them for its argument.
+///
+/// ```
+/// use message_format::{Decimal, PluralOperands};
+///
+/// let operands = PluralOperands::decimal(Decimal::new(-1250, 3)).unwrap();
+/// assert_eq!(operands, PluralOperands { i: 1, v: 3, f: 250, t: 25 });
+/// ```
+///
+/// [CLDR plural rules]: https://unicode.org/reports/tr35/tr35-numbers.html#Operands
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PluralOperands {
+    /// The integer digits, `i`.
+    pub i: u128,
+    /// The number of fraction digits with trailing zeros, `v`.
+    pub v: u32,
+    /// The fraction digits with trailing zeros, `f`.
+    pub f: u128,
+    /// The fraction digits without trailing zeros, `t`.
+    pub t: u128,
+}
+
+impl PluralOperands {
+    /// The operands of a whole number.
+    pub fn integer(value: i64) -> Self {
+        PluralOperands {
+            i: u128::from(value.unsigned_abs()),
+            v: 0,
+            f: 0,
+            t: 0,
+        }
+    }
+
+    /// The operands of a decimal, or `None` if it has more fraction
+    /// digits than a `u128` holds.
+    pub fn decimal(decimal: Decimal) -> Option<Self> {
+        let divisor = 10u128.checked_pow(decimal.scale)?;
+        let digits = decimal.mantissa.unsigned_abs();
+        let f = digits % divisor;
+        let mut t = f;
+        while t != 0 && t.is_multiple_of(10) {
+            t /= 10;
+        }
+        Some(PluralOperands {
+            i: digits / divisor,
+            v: decimal.scale,
+            f,
+            t,
+        })
+    }
+}
+
+/// The cardinal plural category of a number with the given `operands`
+/// in the language of `ctx`.
+///
+/// French, Czech and Slovak have their own rules, and other languages
+/// use those of English, in which only a whole 1 is `One`.
+pub(crate) fn cardinal_category(ctx: &Context, operands: PluralOperands) -> PluralCategory {
+    let PluralOperands { i, v, .. } = operands;
+    if ctx.is_language("fr") {
+        match i {
+            0 | 1 => PluralCategory::One,
+            _ if v == 0 && i.is_multiple_of(1_000_000) => PluralCategory::Many,
+            _ => PluralCategory::Other,
+        }
+    } else if ctx.is_language("cs") || ctx.is_language("sk") {
+        match i {
+            _ if v != 0 => PluralCategory::Many,
+            1 => PluralCategory::One,
+            2..=4 => PluralCategory::Few,
+            _ => PluralCategory::Other,
+        }
+    } else if i == 1 && v == 0 {
+        PluralCategory::One
+    } else {
+        PluralCategory::Other
+    }
+}
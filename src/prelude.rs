@@ -0,0 +1,22 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The allocating types which are in the standard prelude but must be
+//! imported from `alloc` when building without `std`.
+
+#[cfg(not(feature = "std"))]
+pub use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+pub use alloc::string::{String, ToString};
+#[cfg(not(feature = "std"))]
+pub use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+pub use std::boxed::Box;
+#[cfg(feature = "std")]
+pub use std::string::{String, ToString};
+#[cfg(feature = "std")]
+pub use std::vec::Vec;
@@ -0,0 +1,304 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reading and writing the entries of Java `.properties` files.
+//!
+//! [`Catalog::read_properties`] parses the pattern of each entry as
+//! it reads it. Tools which work on the text of the patterns, such as
+//! converting them from another syntax, can use [`read`] and [`write`]
+//! instead, which keep each pattern as it is written:
+//!
+//! ```
+//! use message_format::properties;
+//!
+//! let source = "# The title of the window.\ntitle = Settings\n";
+//! let entries = properties::read(source.as_bytes())
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(entries[0].key, "title");
+//! assert_eq!(entries[0].pattern, "Settings");
+//!
+//! let mut output = vec![];
+//! properties::write(&entries, &mut output).unwrap();
+//! assert_eq!(output, b"# The title of the window.\ntitle=Settings\n");
+//! ```
+//!
+//! This requires the `std` feature.
+//!
+//! [`Catalog::read_properties`]: ../struct.Catalog.html#method.read_properties
+//! [`read`]: fn.read.html
+//! [`write`]: fn.write.html
+
+use std::io::{self, BufRead};
+
+use Error;
+
+/// An entry of a `.properties` file, with its escapes replaced.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// The key of the entry.
+    pub key: String,
+    /// The pattern of the entry.
+    pub pattern: String,
+    /// The comment lines directly before the entry, if there are any.
+    pub description: Option<String>,
+    /// The line on which the entry starts, counting from 1.
+    pub line: usize,
+}
+
+/// Read the entries of a `.properties` file a line at a time, in the
+/// way described for [`Catalog::read_properties`].
+///
+/// An escape which can not be read fails with `Error::Entry`, after
+/// which there are no more entries.
+///
+/// [`Catalog::read_properties`]: ../struct.Catalog.html#method.read_properties
+pub fn read<R: BufRead>(reader: R) -> Entries<R> {
+    Entries {
+        reader,
+        line: String::new(),
+        line_number: 0,
+        done: false,
+    }
+}
+
+/// An iterator over the entries of a `.properties` file, from [`read`].
+///
+/// [`read`]: fn.read.html
+#[derive(Debug)]
+pub struct Entries<R> {
+    reader: R,
+    line: String,
+    line_number: usize,
+    done: bool,
+}
+
+impl<R: BufRead> Entries<R> {
+    fn read_entry(&mut self) -> Result<Option<Entry>, Error> {
+        let mut entry = String::new();
+        let mut comment = String::new();
+        let mut entry_line = 0;
+        loop {
+            let line = &mut self.line;
+            line.clear();
+            if self.reader.read_line(line)? == 0 {
+                break;
+            }
+            self.line_number += 1;
+            if self.line_number == 1 && line.starts_with('\u{feff}') {
+                line.drain(..'\u{feff}'.len_utf8());
+            }
+            let text = line.trim_end_matches(&['\n', '\r'][..]);
+            let continued = !entry.is_empty();
+            let text = if continued {
+                text.trim_start()
+            } else {
+                let text = text.trim_start();
+                if text.is_empty() {
+                    comment.clear();
+                    continue;
+                }
+                if text.starts_with('#') || text.starts_with('!') {
+                    if !comment.is_empty() {
+                        comment.push('\n');
+                    }
+                    comment.push_str(text[1..].trim());
+                    continue;
+                }
+                entry_line = self.line_number;
+                text
+            };
+            let backslashes = text.len() - text.trim_end_matches('\\').len();
+            if backslashes % 2 == 1 {
+                entry.push_str(&text[..text.len() - 1]);
+                // Keep the entry non-empty so the next line continues it.
+                if entry.is_empty() {
+                    entry.push(' ');
+                }
+                continue;
+            }
+            entry.push_str(text);
+            return split_entry(&entry, entry_line, comment).map(Some);
+        }
+        if entry.is_empty() {
+            Ok(None)
+        } else {
+            split_entry(&entry, entry_line, comment).map(Some)
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Entries<R> {
+    type Item = Result<Entry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let entry = self.read_entry();
+        self.done = !matches!(entry, Ok(Some(_)));
+        entry.transpose()
+    }
+}
+
+/// Split an entry, with its continuation lines joined, into its key
+/// and pattern.
+fn split_entry(entry: &str, line: usize, comment: String) -> Result<Entry, Error> {
+    let entry = entry.trim_start();
+    let mut key_end = entry.len();
+    let mut escaped = false;
+    for (index, c) in entry.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '=' || c == ':' || c.is_whitespace() {
+            key_end = index;
+            break;
+        }
+    }
+    let entry_error = |key: &str, error| Error::Entry {
+        key: key.to_string(),
+        line,
+        error: Box::new(error),
+    };
+    let key = unescape(&entry[..key_end]).map_err(|e| entry_error(&entry[..key_end], e))?;
+    let mut pattern = entry[key_end..].trim_start();
+    if pattern.starts_with('=') || pattern.starts_with(':') {
+        pattern = pattern[1..].trim_start();
+    }
+    let pattern = unescape(pattern).map_err(|e| entry_error(&key, e))?;
+    Ok(Entry {
+        key,
+        pattern,
+        description: if comment.is_empty() {
+            None
+        } else {
+            Some(comment)
+        },
+        line,
+    })
+}
+
+fn unescape(text: &str) -> Result<String, Error> {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.char_indices();
+    while let Some((position, c)) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next().map(|(_, c)| c) {
+            Some('t') => output.push('\t'),
+            Some('n') => output.push('\n'),
+            Some('r') => output.push('\r'),
+            Some('f') => output.push('\u{c}'),
+            Some('u') => {
+                let digits: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                let c = u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .filter(|_| digits.len() == 4 && digits.chars().all(|c| c.is_ascii_hexdigit()))
+                    .and_then(::std::char::from_u32)
+                    .ok_or_else(|| Error::parse(position, "Expected a \\uXXXX escape"))?;
+                output.push(c);
+            }
+            Some(c) => output.push(c),
+            None => {}
+        }
+    }
+    Ok(output)
+}
+
+/// Write entries as a `.properties` file, with each description in a
+/// comment before its entry.
+///
+/// Entries are written in the order in which they are given. The file
+/// is UTF-8, and only the characters which the format needs escaped
+/// are escaped.
+pub fn write<W: io::Write>(entries: &[Entry], mut writer: W) -> Result<(), Error> {
+    for entry in entries {
+        if let Some(ref description) = entry.description {
+            for line in description.lines() {
+                writeln!(writer, "# {}", line.trim())?;
+            }
+        }
+        writeln!(
+            writer,
+            "{}={}",
+            escape(&entry.key, true),
+            escape(&entry.pattern, false)
+        )?;
+    }
+    Ok(())
+}
+
+/// Escape `text` for a `.properties` file, as a key if `key` is set
+/// or else as a pattern.
+fn escape(text: &str, key: bool) -> String {
+    let mut output = String::with_capacity(text.len());
+    for (index, c) in text.char_indices() {
+        match c {
+            '\\' => output.push_str("\\\\"),
+            '\t' => output.push_str("\\t"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\u{c}' => output.push_str("\\f"),
+            '=' | ':' | ' ' if key => {
+                output.push('\\');
+                output.push(c);
+            }
+            '#' | '!' if key && index == 0 => {
+                output.push('\\');
+                output.push(c);
+            }
+            ' ' if index == 0 => output.push_str("\\ "),
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read, write, Entry};
+    use Error;
+
+    #[test]
+    fn entries_are_written_back() {
+        let entries = [
+            Entry {
+                key: "#a b=c".to_string(),
+                pattern: " x\\y:\n".to_string(),
+                description: Some("Two\nlines".to_string()),
+                line: 3,
+            },
+            Entry {
+                key: "plain".to_string(),
+                pattern: String::new(),
+                description: None,
+                line: 4,
+            },
+        ];
+        let mut output = vec![];
+        write(&entries, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output.clone()).unwrap(),
+            "# Two\n# lines\n\\#a\\ b\\=c=\\ x\\\\y:\\n\nplain=\n"
+        );
+        let read = read(&output[..]).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(read, entries);
+    }
+
+    #[test]
+    fn reading_stops_at_an_error() {
+        let mut entries = read("a=\\u00\nb=c\n".as_bytes());
+        match entries.next() {
+            Some(Err(Error::Entry { ref key, line, .. })) => assert_eq!((&key[..], line), ("a", 1)),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(entries.next().is_none());
+    }
+}
@@ -0,0 +1,195 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use prelude::*;
+use {Error, Message};
+
+/// The type of value that a message needs for an argument.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ArgumentType {
+    /// Any value, which is formatted as it is.
+    Any,
+    /// A `Value::Number`, such as for a `plural`.
+    Number,
+    /// A `Value::Str`, such as for a `select`.
+    Str,
+    /// A `Value::Duration`, or a number of seconds, such as for a
+    /// `duration`.
+    Duration,
+}
+
+impl ArgumentType {
+    /// A description of the values of this type, as used in
+    /// `Error::UnsupportedType`.
+    pub fn description(self) -> &'static str {
+        match self {
+            ArgumentType::Any => "any value",
+            ArgumentType::Number => "a number",
+            ArgumentType::Str => "a string",
+            ArgumentType::Duration => "a duration",
+        }
+    }
+
+    /// Whether values of this type can be used where `needed` is.
+    fn satisfies(self, needed: ArgumentType) -> bool {
+        needed == ArgumentType::Any
+            || self == needed
+            || (self == ArgumentType::Number && needed == ArgumentType::Duration)
+    }
+}
+
+/// Add an argument to a list of the arguments of a message, keeping a
+/// more specific type over `ArgumentType::Any`.
+pub(crate) fn add_argument(
+    arguments: &mut Vec<(String, ArgumentType)>,
+    name: &str,
+    ty: ArgumentType,
+) {
+    match arguments.iter_mut().find(|argument| argument.0 == name) {
+        Some(argument) => {
+            if argument.1 == ArgumentType::Any {
+                argument.1 = ty;
+            }
+        }
+        None => arguments.push((name.to_string(), ty)),
+    }
+}
+
+/// The arguments that a message is expected to be formatted with, and
+/// their types.
+///
+/// A schema is usually written once, for the messages of the source
+/// language, and each translation is checked against it when it is
+/// loaded rather than failing when it is formatted.
+///
+/// ```
+/// use message_format::{icu, ArgumentType, Schema};
+///
+/// let schema = Schema::new().argument("name", ArgumentType::Str);
+/// assert!(schema.check(&icu::parse("Hallo, {name}!").unwrap()).is_ok());
+///
+/// let error = schema.check(&icu::parse("Hallo, {nmae}!").unwrap()).unwrap_err();
+/// assert_eq!(error.code(), "undeclared-argument");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Schema {
+    arguments: Vec<(String, ArgumentType)>,
+}
+
+impl Schema {
+    /// Create a schema without any arguments.
+    pub fn new() -> Self {
+        Schema::default()
+    }
+
+    /// Declare an argument, replacing any with the same name.
+    pub fn argument(mut self, name: &str, ty: ArgumentType) -> Self {
+        match self
+            .arguments
+            .iter_mut()
+            .find(|argument| argument.0 == name)
+        {
+            Some(argument) => argument.1 = ty,
+            None => self.arguments.push((name.to_string(), ty)),
+        }
+        self
+    }
+
+    /// The type declared for the argument with the given `name`.
+    pub fn get(&self, name: &str) -> Option<ArgumentType> {
+        self.arguments
+            .iter()
+            .find(|argument| argument.0 == name)
+            .map(|argument| argument.1)
+    }
+
+    /// Check that `message` only uses declared arguments, and that it
+    /// can use values of their declared types.
+    ///
+    /// A message need not use every declared argument.
+    pub fn check(&self, message: &Message) -> Result<(), Error> {
+        for (name, needed) in message.arguments() {
+            match self.get(&name) {
+                None => return Err(Error::UndeclaredArgument { name }),
+                Some(declared) if !declared.satisfies(needed) => {
+                    return Err(Error::unsupported_type(&name, needed.description()));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArgumentType, Schema};
+    use icu::ast::{PlaceholderFormat, PlainText, PluralFormat, SelectFormat, SimpleFormat};
+    use prelude::*;
+    use {icu, Error, Message};
+
+    fn plural() -> Message {
+        let mut select = SelectFormat::new(
+            "gender",
+            Message::new(vec![Box::new(PlainText::new("their"))]),
+        );
+        select.map(
+            "female",
+            Message::new(vec![Box::new(SimpleFormat::new("name"))]),
+        );
+        let mut plural = PluralFormat::new(
+            "count",
+            Message::new(vec![Box::new(PlaceholderFormat::new()), Box::new(select)]),
+        );
+        plural.one(Message::new(vec![Box::new(SimpleFormat::new("count"))]));
+        Message::new(vec![Box::new(plural)])
+    }
+
+    #[test]
+    fn arguments_are_collected() {
+        assert_eq!(icu::parse("Hello").unwrap().arguments(), vec![]);
+        assert_eq!(
+            icu::parse("{a} and {b}, {a}").unwrap().arguments(),
+            vec![
+                ("a".to_string(), ArgumentType::Any),
+                ("b".to_string(), ArgumentType::Any),
+            ]
+        );
+        assert_eq!(
+            plural().arguments(),
+            vec![
+                ("count".to_string(), ArgumentType::Number),
+                ("gender".to_string(), ArgumentType::Str),
+                ("name".to_string(), ArgumentType::Any),
+            ]
+        );
+    }
+
+    #[test]
+    fn schemas_are_checked() {
+        let schema = Schema::new()
+            .argument("count", ArgumentType::Number)
+            .argument("gender", ArgumentType::Str)
+            .argument("name", ArgumentType::Number)
+            .argument("name", ArgumentType::Str);
+        assert_eq!(schema.get("name"), Some(ArgumentType::Str));
+        assert!(schema.check(&plural()).is_ok());
+        assert!(schema.check(&icu::parse("Nothing").unwrap()).is_ok());
+
+        let schema = schema.argument("count", ArgumentType::Str);
+        match schema.check(&plural()) {
+            Err(Error::UnsupportedType { ref name, expected }) => {
+                assert_eq!(name, "count");
+                assert_eq!(expected, "a number");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match Schema::new().check(&plural()) {
+            Err(Error::UndeclaredArgument { ref name }) => assert_eq!(name, "count"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
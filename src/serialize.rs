@@ -0,0 +1,669 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use serde::ser::{self, Impossible, Serialize, Serializer};
+
+use prelude::*;
+use {Args, Arguments, Value};
+
+/// Arguments collected from the fields of a serializable value by
+/// [`Args::from_serialize`].
+///
+/// This requires the `serde` feature.
+///
+/// [`Args::from_serialize`]: struct.Args.html#method.from_serialize
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SerializedArgs {
+    args: Vec<(String, OwnedValue)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum OwnedValue {
+    Number(i64),
+    Str(String),
+    I128(i128),
+    U128(u128),
+    Float(f64),
+}
+
+impl Arguments for SerializedArgs {
+    fn lookup(&self, name: &str) -> Option<Value<'_>> {
+        self.args
+            .iter()
+            .find(|arg| arg.0 == name)
+            .map(|arg| match arg.1 {
+                OwnedValue::Number(n) => Value::Number(n),
+                OwnedValue::Str(ref s) => Value::Str(s),
+                OwnedValue::I128(n) => Value::I128(n),
+                OwnedValue::U128(n) => Value::U128(n),
+                OwnedValue::Float(f) => Value::Float(f),
+            })
+    }
+}
+
+/// An error resulting from [`Args::from_serialize`].
+///
+/// This requires the `serde` feature.
+///
+/// [`Args::from_serialize`]: struct.Args.html#method.from_serialize
+#[derive(Clone, Debug, PartialEq)]
+pub struct SerializeError {
+    message: String,
+}
+
+impl SerializeError {
+    fn new(message: &str) -> Self {
+        SerializeError {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.message.fmt(f)
+    }
+}
+
+impl ser::StdError for SerializeError {}
+
+impl ser::Error for SerializeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerializeError {
+            message: format!("{}", msg),
+        }
+    }
+}
+
+impl<'a> Args<'a> {
+    /// Collect arguments from the fields of a struct or the entries of
+    /// a map.
+    ///
+    /// Integers and integral floats become numbers, so they can select
+    /// a `plural` branch. Strings, characters, booleans, other floats and
+    /// unit enum variants become strings. Fields of nested structs and
+    /// maps are named with a dotted path such as `user.name`, while
+    /// fields which are `None` are left out. Sequences and other enum
+    /// variants can not be used as arguments.
+    ///
+    /// Any serializable map works, so arguments which arrive as JSON can
+    /// be passed as a `serde_json::Value` object or `serde_json::Map`.
+    /// JSON booleans become the strings `true` and `false`, for use with
+    /// `select`.
+    ///
+    /// This requires the `serde` feature.
+    ///
+    /// ```
+    /// # extern crate message_format;
+    /// # #[macro_use] extern crate serde_derive;
+    /// # fn main() {
+    /// use message_format::{icu, Args, Context};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Inbox {
+    ///     owner: &'static str,
+    ///     unread: u32,
+    /// }
+    ///
+    /// let ctx = Context::default();
+    /// let message = icu::parse("{owner} has {unread} new messages").unwrap();
+    /// let args = Args::from_serialize(&Inbox { owner: "Ann", unread: 3 }).unwrap();
+    /// assert_eq!(ctx.format(&message, Some(&args)), "Ann has 3 new messages");
+    /// # }
+    /// ```
+    pub fn from_serialize<T: Serialize + ?Sized>(
+        value: &T,
+    ) -> Result<SerializedArgs, SerializeError> {
+        let mut args = SerializedArgs::default();
+        value.serialize(FieldSerializer {
+            args: &mut args.args,
+            name: String::new(),
+        })?;
+        Ok(args)
+    }
+}
+
+/// Serializes a value into the argument called `name`, or into
+/// arguments prefixed by `name` for structs and maps. The top level
+/// value has an empty name.
+struct FieldSerializer<'a> {
+    args: &'a mut Vec<(String, OwnedValue)>,
+    name: String,
+}
+
+impl<'a> FieldSerializer<'a> {
+    fn push(self, value: OwnedValue) -> Result<(), SerializeError> {
+        if self.name.is_empty() {
+            return Err(SerializeError::new("Expected a struct or map of arguments"));
+        }
+        let name = self.name;
+        self.args.retain(|arg| arg.0 != name);
+        self.args.push((name, value));
+        Ok(())
+    }
+
+    fn nested(self) -> NestedSerializer<'a> {
+        NestedSerializer {
+            args: self.args,
+            prefix: self.name,
+            key: None,
+        }
+    }
+}
+
+impl<'a> Serializer for FieldSerializer<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+    type SerializeSeq = Impossible<(), SerializeError>;
+    type SerializeTuple = Impossible<(), SerializeError>;
+    type SerializeTupleStruct = Impossible<(), SerializeError>;
+    type SerializeTupleVariant = Impossible<(), SerializeError>;
+    type SerializeMap = NestedSerializer<'a>;
+    type SerializeStruct = NestedSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), SerializeError>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), SerializeError> {
+        self.push(OwnedValue::Str(v.to_string()))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), SerializeError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), SerializeError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), SerializeError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), SerializeError> {
+        self.push(OwnedValue::Number(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), SerializeError> {
+        self.push(OwnedValue::I128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), SerializeError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), SerializeError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), SerializeError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), SerializeError> {
+        if v > i64::MAX as u64 {
+            return self.serialize_u128(u128::from(v));
+        }
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), SerializeError> {
+        self.push(OwnedValue::U128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), SerializeError> {
+        // As with `Value::from`, take the shortest decimal form, so that
+        // `0.1f32` is not written with the digits of its error.
+        self.serialize_f64(v.to_string().parse().unwrap_or_else(|_| f64::from(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), SerializeError> {
+        // A whole number stays an integer, so that it can choose the
+        // branch of a `plural`. `i64::MAX as f64` is 2^63, which is
+        // already too large.
+        if v as i64 as f64 == v && v < i64::MAX as f64 {
+            self.serialize_i64(v as i64)
+        } else {
+            self.push(OwnedValue::Float(v))
+        }
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), SerializeError> {
+        self.push(OwnedValue::Str(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), SerializeError> {
+        self.push(OwnedValue::Str(v.to_string()))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), SerializeError> {
+        Err(SerializeError::new("Bytes can not be used as an argument"))
+    }
+
+    fn serialize_none(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SerializeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), SerializeError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), SerializeError> {
+        Err(SerializeError::new(
+            "Enum variants with data can not be used as arguments",
+        ))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerializeError> {
+        Err(SerializeError::new(
+            "Sequences can not be used as arguments",
+        ))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerializeError> {
+        Err(SerializeError::new("Tuples can not be used as arguments"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SerializeError> {
+        Err(SerializeError::new(
+            "Tuple structs can not be used as arguments",
+        ))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerializeError> {
+        Err(SerializeError::new(
+            "Enum variants with data can not be used as arguments",
+        ))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerializeError> {
+        Ok(self.nested())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SerializeError> {
+        Ok(self.nested())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerializeError> {
+        Err(SerializeError::new(
+            "Enum variants with data can not be used as arguments",
+        ))
+    }
+}
+
+/// Serializes the fields of a struct or the entries of a map into
+/// arguments named with `prefix`.
+struct NestedSerializer<'a> {
+    args: &'a mut Vec<(String, OwnedValue)>,
+    prefix: String,
+    key: Option<String>,
+}
+
+impl<'a> NestedSerializer<'a> {
+    fn field(&mut self, key: &str) -> FieldSerializer<'_> {
+        let name = if self.prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.prefix, key)
+        };
+        FieldSerializer {
+            args: self.args,
+            name,
+        }
+    }
+}
+
+impl<'a> ser::SerializeStruct for NestedSerializer<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerializeError> {
+        value.serialize(self.field(key))
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeMap for NestedSerializer<'a> {
+    type Ok = ();
+    type Error = SerializeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerializeError> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializeError> {
+        let key = match self.key.take() {
+            Some(key) => key,
+            None => return Err(SerializeError::new("Expected a map key")),
+        };
+        value.serialize(self.field(&key))
+    }
+
+    fn end(self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+/// Serializes a map key into an argument name.
+struct KeySerializer;
+
+const KEY_ERROR: &str = "Map keys must be strings, characters or integers";
+
+impl Serializer for KeySerializer {
+    type Ok = String;
+    type Error = SerializeError;
+    type SerializeSeq = Impossible<String, SerializeError>;
+    type SerializeTuple = Impossible<String, SerializeError>;
+    type SerializeTupleStruct = Impossible<String, SerializeError>;
+    type SerializeTupleVariant = Impossible<String, SerializeError>;
+    type SerializeMap = Impossible<String, SerializeError>;
+    type SerializeStruct = Impossible<String, SerializeError>;
+    type SerializeStructVariant = Impossible<String, SerializeError>;
+
+    fn serialize_bool(self, _v: bool) -> Result<String, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, SerializeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_none(self) -> Result<String, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, SerializeError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, SerializeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerializeError> {
+        Err(SerializeError::new(KEY_ERROR))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use icu;
+    use {Args, Arguments, Context, Value};
+
+    #[derive(Serialize)]
+    #[allow(dead_code)]
+    enum Gender {
+        Female,
+        Male,
+    }
+
+    #[derive(Serialize)]
+    struct User {
+        name: String,
+        gender: Gender,
+    }
+
+    #[derive(Serialize)]
+    struct Notification {
+        user: User,
+        count: u64,
+        ratio: f64,
+        total: f64,
+        nickname: Option<String>,
+    }
+
+    #[test]
+    fn structs_are_flattened() {
+        let notification = Notification {
+            user: User {
+                name: "Ann".to_string(),
+                gender: Gender::Female,
+            },
+            count: 2,
+            ratio: 0.5,
+            total: 3.0,
+            nickname: None,
+        };
+        let args = Args::from_serialize(&notification).unwrap();
+        assert_eq!(args.lookup("user.name"), Some(Value::Str("Ann")));
+        assert_eq!(args.lookup("user.gender"), Some(Value::Str("Female")));
+        assert_eq!(args.lookup("count"), Some(Value::Number(2)));
+        assert_eq!(args.lookup("ratio"), Some(Value::Float(0.5)));
+        assert_eq!(args.lookup("total"), Some(Value::Number(3)));
+        assert_eq!(args.lookup("nickname"), None);
+
+        let ctx = Context::default();
+        let message = icu::parse("{user.name}: {count}").unwrap();
+        assert_eq!(ctx.format(&message, Some(&args)), "Ann: 2");
+        let message = icu::parse("{ratio, number, percent}").unwrap();
+        assert_eq!(ctx.format(&message, Some(&args)), "50%");
+    }
+
+    #[test]
+    fn maps_work() {
+        let mut map = BTreeMap::new();
+        map.insert("name", "Ann");
+        let args = Args::from_serialize(&map).unwrap();
+        assert_eq!(args.lookup("name"), Some(Value::Str("Ann")));
+    }
+
+    #[test]
+    fn unsupported_values_fail() {
+        assert!(Args::from_serialize(&3).is_err());
+        assert!(Args::from_serialize(&vec![1, 2]).is_err());
+        let mut map = BTreeMap::new();
+        map.insert("items", vec![1, 2]);
+        assert!(Args::from_serialize(&map).is_err());
+    }
+
+    #[test]
+    fn large_floats_are_not_saturated() {
+        let mut map = BTreeMap::new();
+        map.insert("big", 9_223_372_036_854_775_808.0);
+        let args = Args::from_serialize(&map).unwrap();
+        assert_eq!(
+            args.lookup("big"),
+            Some(Value::Float(9_223_372_036_854_775_808.0))
+        );
+
+        let mut map = BTreeMap::new();
+        map.insert("small", 0.1f32);
+        let args = Args::from_serialize(&map).unwrap();
+        assert_eq!(args.lookup("small"), Some(Value::Float(0.1)));
+    }
+
+    #[test]
+    fn wide_integers_keep_their_digits() {
+        let mut map = BTreeMap::new();
+        map.insert("big", u64::MAX);
+        let args = Args::from_serialize(&map).unwrap();
+        assert_eq!(args.lookup("big"), Some(Value::U128(u128::from(u64::MAX))));
+    }
+}
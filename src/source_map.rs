@@ -0,0 +1,143 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use prelude::*;
+
+/// What produced a span of formatted output.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpanKind {
+    /// Literal text from the message.
+    Text,
+    /// The value of the argument with this name.
+    Argument(String),
+    /// The `#` placeholder inside of a `plural`.
+    Placeholder,
+    /// Another message of a catalog, with this id, which the message
+    /// refers to.
+    Reference(String),
+    /// A custom [`MessagePart`] which does not say what it is.
+    ///
+    /// [`MessagePart`]: trait.MessagePart.html
+    Other,
+}
+
+/// A span of formatted output and the part of the message which
+/// produced it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputSpan {
+    /// The byte offset in the output at which the span starts.
+    pub start: usize,
+    /// The byte offset in the output at which the span ends.
+    pub end: usize,
+    /// The index of the part which produced the span, following the
+    /// parts the same way as [`Message::dump`]. A part inside of the
+    /// chosen branch of a `plural` or `select` has the index of the
+    /// `plural` or `select` followed by its own index in the branch.
+    ///
+    /// [`Message::dump`]: struct.Message.html#method.dump
+    pub path: Vec<usize>,
+    /// What produced the span.
+    pub kind: SpanKind,
+}
+
+/// Formatted output along with the spans of it which each part of the
+/// message produced, from [`Context::format_mapped`].
+///
+/// ```
+/// use message_format::{icu, Context, SpanKind, Value};
+///
+/// let ctx = Context::default();
+/// let message = icu::parse("Hello, {name}!").unwrap();
+/// let output = ctx.format_mapped(&message, Some(&[("name", Value::Str("Ann"))]));
+/// assert_eq!(output.text(), "Hello, Ann!");
+///
+/// let span = output.span_at(8).unwrap();
+/// assert_eq!(&output.text()[span.start..span.end], "Ann");
+/// assert_eq!(span.kind, SpanKind::Argument("name".to_string()));
+/// ```
+///
+/// [`Context::format_mapped`]: struct.Context.html#method.format_mapped
+#[derive(Clone, Debug, Default)]
+pub struct MappedOutput {
+    text: String,
+    spans: Vec<OutputSpan>,
+    path: Vec<usize>,
+}
+
+impl MappedOutput {
+    pub(crate) fn from_parts(text: String, spans: Vec<OutputSpan>) -> Self {
+        MappedOutput {
+            text,
+            spans,
+            path: vec![],
+        }
+    }
+
+    /// The formatted text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The spans of the output in order. Spans do not overlap, but
+    /// formatting which failed part of the way through may leave some
+    /// of the output without a span.
+    pub fn spans(&self) -> &[OutputSpan] {
+        &self.spans
+    }
+
+    /// The span containing the byte at `offset`, such as the one under
+    /// a cursor.
+    pub fn span_at(&self, offset: usize) -> Option<&OutputSpan> {
+        self.spans
+            .iter()
+            .find(|span| span.start <= offset && offset < span.end)
+    }
+
+    /// The length of the output so far, which is where the next span
+    /// starts.
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    /// Whether nothing has been written yet.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Record that the output from `start` up to now was produced by
+    /// the current part. Empty spans are not recorded.
+    pub fn record(&mut self, start: usize, kind: SpanKind) {
+        if start < self.text.len() {
+            self.spans.push(OutputSpan {
+                start,
+                end: self.text.len(),
+                path: self.path.clone(),
+                kind,
+            });
+        }
+    }
+
+    /// Run `f` for the part with the given index, within the current
+    /// part.
+    pub(crate) fn part<F>(&mut self, index: usize, f: F) -> fmt::Result
+    where
+        F: FnOnce(&mut Self) -> fmt::Result,
+    {
+        self.path.push(index);
+        let result = f(self);
+        self.path.pop();
+        result
+    }
+}
+
+impl fmt::Write for MappedOutput {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.text.push_str(s);
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Generating Patterns for Tests
+//!
+//! This provides a [`PatternGenerator`] which produces valid ICU
+//! patterns from a seed, along with the names of the arguments that
+//! they use, for property testing the parser and formatter. The same
+//! seed always produces the same patterns, so a failure can be
+//! reproduced from the seed alone.
+//!
+//! This requires the `testing` feature.
+//!
+//! [`PatternGenerator`]: struct.PatternGenerator.html
+
+use prelude::*;
+
+/// The characters used for literal text. This includes spaces,
+/// punctuation and characters outside of ASCII, but never braces.
+const TEXT: &[char] = &[
+    'a', 'b', 'c', 'x', 'y', 'z', 'A', 'Z', '0', '9', ' ', ' ', '.', ',', '!', '?', '-', '#',
+    'é', 'ß', 'Ω', '日', '😀',
+];
+
+/// The characters used in argument names.
+const NAME: &[char] = &['a', 'b', 'c', 'n', 'x', '_', '0', '1'];
+
+/// A pattern produced by a [`PatternGenerator`].
+///
+/// [`PatternGenerator`]: struct.PatternGenerator.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratedPattern {
+    /// The text of the pattern.
+    pub pattern: String,
+    /// The names of the arguments used by the pattern, in order of
+    /// first use.
+    pub arguments: Vec<String>,
+}
+
+/// Produces valid ICU patterns from a seed.
+///
+/// ```
+/// use message_format::icu;
+/// use message_format::testing::PatternGenerator;
+///
+/// let mut generator = PatternGenerator::new(42);
+/// for _ in 0..100 {
+///     let generated = generator.next_pattern();
+///     assert!(icu::parse(&generated.pattern).is_ok());
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PatternGenerator {
+    state: u64,
+}
+
+impl PatternGenerator {
+    /// Create a generator from a `seed`.
+    pub fn new(seed: u64) -> Self {
+        PatternGenerator {
+            // A zero state would only ever produce zeroes.
+            state: seed ^ 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    /// A pseudo-random number from xorshift64*.
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number less than `bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    fn push_from(&mut self, output: &mut String, chars: &[char], len: usize) {
+        for _ in 0..len {
+            output.push(chars[self.below(chars.len())]);
+        }
+    }
+
+    /// Produce the next pattern.
+    pub fn next_pattern(&mut self) -> GeneratedPattern {
+        let mut pattern = String::new();
+        let mut arguments: Vec<String> = vec![];
+        let pieces = 1 + self.below(6);
+        for _ in 0..pieces {
+            if self.below(2) == 0 {
+                let len = 1 + self.below(12);
+                self.push_from(&mut pattern, TEXT, len);
+                continue;
+            }
+            let mut name = String::new();
+            let len = self.below(6);
+            self.push_from(&mut name, &NAME[..5], 1);
+            self.push_from(&mut name, NAME, len);
+            pattern.push('{');
+            pattern.push_str(&name);
+            match self.below(4) {
+                0 => pattern.push_str(", plural"),
+                1 => pattern.push_str(", select"),
+                _ => {}
+            }
+            pattern.push('}');
+            if !arguments.contains(&name) {
+                arguments.push(name);
+            }
+        }
+        GeneratedPattern { pattern, arguments }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PatternGenerator;
+    use icu;
+    use {Context, Value};
+
+    #[test]
+    fn patterns_are_deterministic() {
+        let mut a = PatternGenerator::new(7);
+        let mut b = PatternGenerator::new(7);
+        let mut c = PatternGenerator::new(8);
+        let patterns: Vec<_> = (0..20).map(|_| a.next_pattern()).collect();
+        assert_eq!(patterns, (0..20).map(|_| b.next_pattern()).collect::<Vec<_>>());
+        assert_ne!(patterns, (0..20).map(|_| c.next_pattern()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn patterns_format_with_their_arguments() {
+        let ctx = Context::default();
+        let mut generator = PatternGenerator::new(0);
+        for _ in 0..1000 {
+            let generated = generator.next_pattern();
+            let message = icu::parse(&generated.pattern).unwrap();
+            let args: Vec<_> = generated
+                .arguments
+                .iter()
+                .map(|name| (&name[..], Value::Str("value")))
+                .collect();
+            let output = ctx.try_format(&message, Some(&args)).unwrap();
+            assert!(!output.contains('{'), "{}", generated.pattern);
+            if !generated.arguments.is_empty() {
+                assert!(ctx.try_format(&message, None).is_err());
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Snapshot Testing
+//!
+//! A [`Snapshot`] formats every message in a [`Catalog`] with a set of
+//! fixture arguments and renders the results as a report. The report
+//! is the same from run to run, so it can be compared against a copy
+//! kept alongside an application's tests to catch unintended changes
+//! to its localized output, one report per locale.
+//!
+//! This requires the `testkit` feature.
+//!
+//! [`Snapshot`]: struct.Snapshot.html
+//! [`Catalog`]: ../struct.Catalog.html
+
+use std::fmt::Write;
+
+use {Catalog, Context, Value};
+
+/// Fixture arguments for the messages of a catalog.
+///
+/// Each message is formatted once for each fixture given for its key,
+/// or once without arguments if it has none.
+///
+/// ```
+/// use message_format::testkit::Snapshot;
+/// use message_format::{Catalog, Context, Value};
+///
+/// let source = "greeting = Hello, {name}!\ntitle = Welcome\n";
+/// let catalog = Catalog::read_properties(source.as_bytes()).unwrap();
+///
+/// let mut snapshot = Snapshot::new();
+/// snapshot.fixture("greeting", &[("name", Value::Str("Ann"))]);
+/// snapshot.fixture("greeting", &[]);
+///
+/// assert_eq!(snapshot.report(&Context::default(), &catalog), "\
+/// greeting(name: \"Ann\") = \"Hello, Ann!\"
+/// greeting() ! missing-argument: missing argument `name`
+/// title() = \"Welcome\"
+/// ");
+/// ```
+#[derive(Debug, Default)]
+pub struct Snapshot<'a> {
+    fixtures: Vec<(&'a str, &'a [(&'a str, Value<'a>)])>,
+}
+
+impl<'a> Snapshot<'a> {
+    /// Create a `Snapshot` without any fixtures.
+    pub fn new() -> Self {
+        Snapshot::default()
+    }
+
+    /// Add a set of arguments with which to format the message `key`.
+    pub fn fixture(&mut self, key: &'a str, args: &'a [(&'a str, Value<'a>)]) {
+        self.fixtures.push((key, args));
+    }
+
+    /// Format the messages of `catalog` and render the results, one
+    /// line per result, sorted by key and then in the order that the
+    /// fixtures were added.
+    ///
+    /// A line holds the key and the arguments, followed by `=` and the
+    /// output or by `!` and the [`code`] and description of the error.
+    ///
+    /// [`code`]: ../enum.Error.html#method.code
+    pub fn report(&self, ctx: &Context, catalog: &Catalog) -> String {
+        let mut keys: Vec<_> = catalog.iter().map(|entry| entry.0).collect();
+        keys.sort_unstable();
+
+        let mut report = String::new();
+        for key in keys {
+            let message = catalog.get(key).expect("key is in the catalog");
+            let fixtures: Vec<_> = self
+                .fixtures
+                .iter()
+                .filter(|fixture| fixture.0 == key)
+                .map(|fixture| fixture.1)
+                .collect();
+            let fixtures = if fixtures.is_empty() {
+                vec![&[][..]]
+            } else {
+                fixtures
+            };
+            for args in fixtures {
+                report.push_str(key);
+                report.push('(');
+                for (index, arg) in args.iter().enumerate() {
+                    if index > 0 {
+                        report.push_str(", ");
+                    }
+                    let _ = match arg.1 {
+                        Value::Number(n) => write!(report, "{}: {}", arg.0, n),
+                        Value::Str(s) => write!(report, "{}: {:?}", arg.0, s),
+                        Value::Duration(d) => write!(report, "{}: {:?}", arg.0, d),
+                        Value::Float(f) => write!(report, "{}: {:?}", arg.0, f),
+                        Value::I128(n) => write!(report, "{}: {}", arg.0, n),
+                        Value::U128(n) => write!(report, "{}: {}", arg.0, n),
+                        Value::Decimal(d) => write!(report, "{}: {}", arg.0, Value::Decimal(d)),
+                    };
+                }
+                report.push(')');
+                let _ = match ctx.try_format(message, Some(&args)) {
+                    Ok(output) => writeln!(report, " = {:?}", output),
+                    Err(error) => writeln!(report, " ! {}: {}", error.code(), error),
+                };
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Snapshot;
+    use {Catalog, Context, Value};
+
+    #[test]
+    fn reports_are_sorted() {
+        let source = "b = {n} items\na = Line one\\nline two\nc = {n}\n";
+        let catalog = Catalog::read_properties(source.as_bytes()).unwrap();
+
+        let one = [("n", Value::Number(1))];
+        let many = [("n", Value::Number(12)), ("unused", Value::Str("x"))];
+        let mut snapshot = Snapshot::new();
+        snapshot.fixture("b", &many);
+        snapshot.fixture("b", &one);
+        snapshot.fixture("missing", &one);
+
+        let report = snapshot.report(&Context::default(), &catalog);
+        assert_eq!(
+            report,
+            "a() = \"Line one\\nline two\"\n\
+             b(n: 12, unused: \"x\") = \"12 items\"\n\
+             b(n: 1) = \"1 items\"\n\
+             c() ! missing-argument: missing argument `n`\n"
+        );
+        assert_eq!(report, snapshot.report(&Context::default(), &catalog));
+    }
+}
@@ -0,0 +1,126 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The string type used for the literal text and variable names in a
+//! parsed message.
+//!
+//! With the `small-strings` feature, short strings are stored inline
+//! rather than in their own heap allocation. Most of the literal
+//! segments and variable names in user interface strings are short, so
+//! this avoids most of the allocations made when parsing a catalog.
+
+#[cfg(feature = "small-strings")]
+use std::fmt;
+#[cfg(feature = "small-strings")]
+use std::ops::Deref;
+#[cfg(feature = "small-strings")]
+use std::str;
+
+use prelude::*;
+
+/// The string type used in a parsed message. The text of a message is
+/// never changed once parsed, so this does not keep a capacity.
+#[cfg(not(feature = "small-strings"))]
+pub type Text = Box<str>;
+
+/// The longest string that is stored inline. This keeps a `Text` the
+/// same size as a `String`.
+#[cfg(feature = "small-strings")]
+const INLINE_CAPACITY: usize = 22;
+
+/// The string type used in a parsed message, which stores strings of
+/// up to `INLINE_CAPACITY` bytes inline.
+#[cfg(feature = "small-strings")]
+#[derive(Clone, PartialEq, Eq)]
+pub enum Text {
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_CAPACITY],
+    },
+    Heap(Box<str>),
+}
+
+/// The number of bytes allocated on the heap for a `Text`.
+#[cfg(not(feature = "small-strings"))]
+pub fn heap_size(text: &Text) -> usize {
+    text.len()
+}
+
+/// The number of bytes allocated on the heap for a `Text`.
+#[cfg(feature = "small-strings")]
+pub fn heap_size(text: &Text) -> usize {
+    match *text {
+        Text::Inline { .. } => 0,
+        Text::Heap(ref text) => text.len(),
+    }
+}
+
+#[cfg(feature = "small-strings")]
+impl Text {
+    pub fn as_str(&self) -> &str {
+        match *self {
+            Text::Inline { len, ref bytes } => {
+                str::from_utf8(&bytes[..len as usize]).expect("inline text is valid UTF-8")
+            }
+            Text::Heap(ref text) => text,
+        }
+    }
+}
+
+#[cfg(feature = "small-strings")]
+impl<'a> From<&'a str> for Text {
+    fn from(text: &'a str) -> Self {
+        if text.len() <= INLINE_CAPACITY {
+            let mut bytes = [0; INLINE_CAPACITY];
+            bytes[..text.len()].copy_from_slice(text.as_bytes());
+            Text::Inline {
+                len: text.len() as u8,
+                bytes,
+            }
+        } else {
+            Text::Heap(text.into())
+        }
+    }
+}
+
+#[cfg(feature = "small-strings")]
+impl Deref for Text {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(feature = "small-strings")]
+impl fmt::Debug for Text {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+#[cfg(all(test, feature = "small-strings"))]
+mod tests {
+    use super::Text;
+
+    #[test]
+    fn short_text_is_inline() {
+        assert_eq!(size_of::<Text>(), size_of::<String>());
+
+        let text = Text::from("Hello, ");
+        assert!(matches!(text, Text::Inline { .. }));
+        assert_eq!(text.as_str(), "Hello, ");
+        assert_eq!(format!("{:?}", text), "\"Hello, \"");
+
+        let long = "A segment of text which is much too long";
+        let text = Text::from(long);
+        assert!(matches!(text, Text::Heap(_)));
+        assert_eq!(&*text, long);
+
+        assert_eq!(Text::from("").as_str(), "");
+        assert_eq!(Text::from("Grüße").as_str(), "Grüße");
+    }
+}
@@ -0,0 +1,222 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Truncating Formatted Output
+//!
+//! User interfaces often have room for a limited number of characters.
+//! [`truncate`] shortens text to a number of user-perceived characters,
+//! or grapheme clusters, without splitting an accented letter, a flag
+//! or an emoji sequence, and ends it with an ellipsis. [`truncate_mapped`]
+//! does the same for the output of [`Context::format_mapped`], and
+//! prefers to drop an argument value entirely rather than cut it part
+//! of the way through.
+//!
+//! Grapheme clusters are found with a simplified form of the Unicode
+//! rules: a character is joined by any combining marks, variation
+//! selectors, emoji modifiers and tags which follow it, by a character
+//! after a zero width joiner, and by a second regional indicator. This
+//! covers text in most scripts, but does not, for example, keep
+//! Hangul syllables written as separate jamo together.
+//!
+//! [`truncate`]: fn.truncate.html
+//! [`truncate_mapped`]: fn.truncate_mapped.html
+//! [`Context::format_mapped`]: ../struct.Context.html#method.format_mapped
+
+#[cfg(not(feature = "std"))]
+use alloc::borrow::Cow;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+
+use prelude::*;
+use {Context, MappedOutput, SpanKind};
+
+/// Whether `c` extends the grapheme cluster before it.
+fn is_extend(c: char) -> bool {
+    match c as u32 {
+        // Combining marks.
+        0x0300..=0x036f | 0x0483..=0x0489 | 0x0591..=0x05bd | 0x0610..=0x061a | 0x064b..=0x065f => {
+            true
+        }
+        0x0900..=0x0903 | 0x093a..=0x094f | 0x0e31 | 0x0e34..=0x0e3a | 0x0e47..=0x0e4e => true,
+        0x1ab0..=0x1aff | 0x1dc0..=0x1dff | 0x20d0..=0x20ff | 0xfe20..=0xfe2f => true,
+        // Variation selectors.
+        0xfe00..=0xfe0f | 0xe0100..=0xe01ef => true,
+        // Emoji modifiers and tags.
+        0x1f3fb..=0x1f3ff | 0xe0020..=0xe007f => true,
+        // Zero width joiner.
+        0x200d => true,
+        _ => false,
+    }
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+/// The byte offsets at which the grapheme clusters of `text` end.
+fn cluster_ends(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut chars = text.char_indices().peekable();
+    ::std::iter::from_fn(move || {
+        let (_, first) = chars.next()?;
+        let mut previous = first;
+        let mut indicators = if is_regional_indicator(first) { 1 } else { 0 };
+        while let Some(&(_, c)) = chars.peek() {
+            let joined = is_extend(c)
+                || previous == '\u{200d}'
+                || (previous == '\r' && c == '\n')
+                || (indicators == 1 && is_regional_indicator(c));
+            if !joined {
+                break;
+            }
+            if is_regional_indicator(c) {
+                indicators += 1;
+            }
+            previous = c;
+            chars.next();
+        }
+        Some(chars.peek().map_or(text.len(), |&(index, _)| index))
+    })
+}
+
+/// The number of grapheme clusters in `text`.
+///
+/// ```
+/// use message_format::truncate::grapheme_count;
+///
+/// assert_eq!(grapheme_count("cafe\u{301}"), 4);
+/// assert_eq!(grapheme_count("🇩🇪👍🏽"), 2);
+/// ```
+pub fn grapheme_count(text: &str) -> usize {
+    cluster_ends(text).count()
+}
+
+/// The ellipsis to use when truncating text for the language of `ctx`.
+///
+/// Chinese uses two ellipsis characters. Other languages use one.
+pub fn ellipsis(ctx: &Context) -> &'static str {
+    if ctx.is_language("zh") {
+        "……"
+    } else {
+        "…"
+    }
+}
+
+/// The byte offset at which to cut `text` so that the cut text and the
+/// ellipsis have at most `max` grapheme clusters, or `None` if `text`
+/// already fits.
+fn cut_point(text: &str, max: usize, ellipsis: &str) -> Option<usize> {
+    if grapheme_count(text) <= max {
+        return None;
+    }
+    let room = max.saturating_sub(grapheme_count(ellipsis));
+    Some(if room == 0 {
+        0
+    } else {
+        cluster_ends(text).nth(room - 1).unwrap_or(text.len())
+    })
+}
+
+/// Shorten `text` to at most `max` grapheme clusters, including the
+/// `ellipsis` which replaces the text that was cut off.
+///
+/// ```
+/// use message_format::truncate::truncate;
+///
+/// assert_eq!(truncate("Hello, world", 8, "…"), "Hello, …");
+/// assert_eq!(truncate("Hello", 8, "…"), "Hello");
+/// assert_eq!(truncate("Ame\u{301}lie", 4, "…"), "Ame\u{301}…");
+/// ```
+pub fn truncate<'a>(text: &'a str, max: usize, ellipsis: &str) -> Cow<'a, str> {
+    match cut_point(text, max, ellipsis) {
+        None => Cow::Borrowed(text),
+        Some(cut) => Cow::Owned(format!("{}{}", &text[..cut], ellipsis)),
+    }
+}
+
+/// Shorten formatted output to at most `max` grapheme clusters,
+/// including the `ellipsis`.
+///
+/// If the cut would fall part of the way through the value of an
+/// argument, the whole value is dropped instead, unless the output
+/// starts with that value.
+///
+/// ```
+/// use message_format::truncate::truncate_mapped;
+/// use message_format::{icu, Context, Value};
+///
+/// let ctx = Context::default();
+/// let message = icu::parse("Sent to {name}").unwrap();
+/// let output = ctx.format_mapped(&message, Some(&[("name", Value::Str("Alexandra"))]));
+/// assert_eq!(truncate_mapped(&output, 12, "…"), "Sent to …");
+/// ```
+pub fn truncate_mapped(output: &MappedOutput, max: usize, ellipsis: &str) -> String {
+    let text = output.text();
+    let cut = match cut_point(text, max, ellipsis) {
+        None => return text.to_string(),
+        Some(cut) => cut,
+    };
+    let cut = match output.span_at(cut) {
+        Some(span) if span.start > 0 && span.start < cut => match span.kind {
+            SpanKind::Argument(_) => span.start,
+            _ => cut,
+        },
+        _ => cut,
+    };
+    format!("{}{}", &text[..cut], ellipsis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icu;
+    use Value;
+
+    #[test]
+    fn clusters_work() {
+        assert_eq!(grapheme_count(""), 0);
+        assert_eq!(grapheme_count("abc"), 3);
+        assert_eq!(grapheme_count("\r\n"), 1);
+        assert_eq!(grapheme_count("👨\u{200d}👩\u{200d}👧"), 1);
+        assert_eq!(grapheme_count("🇫🇷🇩🇪🇯"), 3);
+        assert_eq!(grapheme_count("❤\u{fe0f}!"), 2);
+    }
+
+    #[test]
+    fn truncate_works() {
+        assert_eq!(truncate("🇫🇷🇩🇪🇯🇵", 2, "…"), "🇫🇷…");
+        assert_eq!(truncate("abcdef", 3, "..."), "...");
+        assert_eq!(truncate("abcdef", 0, "…"), "…");
+        assert_eq!(truncate("abc", 3, "…"), "abc");
+    }
+
+    #[test]
+    fn arguments_are_kept_whole() {
+        let ctx = Context::default();
+        let message = icu::parse("{name} wrote: {text}").unwrap();
+        let args = [
+            ("name", Value::Str("Ann")),
+            ("text", Value::Str("a long reply")),
+        ];
+        let output = ctx.format_mapped(&message, Some(&args));
+
+        assert_eq!(
+            truncate_mapped(&output, 100, "…"),
+            "Ann wrote: a long reply"
+        );
+        assert_eq!(truncate_mapped(&output, 15, "…"), "Ann wrote: …");
+        assert_eq!(truncate_mapped(&output, 8, "…"), "Ann wro…");
+        // The output starts with an argument, so it is cut.
+        assert_eq!(truncate_mapped(&output, 2, "…"), "A…");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn ellipsis_depends_on_language() {
+        let ctx = Context::for_language("zh-Hans");
+        assert_eq!(ellipsis(&ctx), "……");
+        assert_eq!(ellipsis(&Context::default()), "…");
+    }
+}
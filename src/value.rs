@@ -4,7 +4,21 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#[cfg(not(feature = "std"))]
+use alloc::borrow::Cow;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::str::FromStr;
+use std::time::Duration;
+
+use duration;
+use number::{self, Number};
+use prelude::*;
+use Error;
 
 /// A wrapper around a value, used with [`Args`] so that a [`MessagePart`]
 /// can access the original value when necessary.
@@ -14,12 +28,120 @@ use std::fmt;
 ///
 /// [`Args`]: struct.Args.html
 /// [`MessagePart`]: trait.MessagePart.html
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug)]
 pub enum Value<'a> {
     /// Wrap an `i64`.
     Number(i64),
     /// Wrap an `&str`.
     Str(&'a str),
+    /// Wrap a `Duration`, such as the length of a song.
+    Duration(Duration),
+    /// Wrap an `f64`, such as a measurement.
+    ///
+    /// It is written with at most the 15 significant digits which an
+    /// `f64` always holds, so `0.1 + 0.2` is written as `0.3` rather
+    /// than `0.30000000000000004`. A `number` argument writes it with
+    /// the decimal separator and digit grouping of the language.
+    Float(f64),
+    /// Wrap an `i128`, such as an identifier, which is written with all
+    /// of its digits.
+    I128(i128),
+    /// Wrap a `u128`, which is written with all of its digits.
+    U128(u128),
+    /// Wrap a [`Decimal`], such as an amount of money, which is written
+    /// with exactly its digits.
+    ///
+    /// [`Decimal`]: struct.Decimal.html
+    Decimal(Decimal),
+}
+
+impl<'a> Value<'a> {
+    /// The number held by this value. With `coerce`, a string which
+    /// holds a whole number is also taken as that number.
+    ///
+    /// A float which is a whole number is also taken as that number,
+    /// as is a wider integer which fits in an `i64`.
+    pub(crate) fn number(self, coerce: bool) -> Option<i64> {
+        match self {
+            Value::Number(n) => Some(n),
+            Value::Float(f) if f == f as i64 as f64 && f < i64::MAX as f64 => Some(f as i64),
+            Value::I128(n) => i64::try_from(n).ok(),
+            Value::U128(n) => i64::try_from(n).ok(),
+            Value::Decimal(d) if d.scale == 0 => i64::try_from(d.mantissa).ok(),
+            Value::Str(s) if coerce => s.trim().parse().ok(),
+            Value::Str(_) | Value::Duration(_) | Value::Float(_) | Value::Decimal(_) => None,
+        }
+    }
+
+    /// Whether this value is a number which `number` does not give,
+    /// since it is too large for an `i64` or is not finite, so that a
+    /// `plural` chooses its `other` branch for it.
+    pub(crate) fn is_unbounded(self) -> bool {
+        match self {
+            Value::Float(f) => !f.is_finite(),
+            Value::I128(_) | Value::U128(_) => self.number(false).is_none(),
+            Value::Number(_) | Value::Str(_) | Value::Duration(_) | Value::Decimal(_) => false,
+        }
+    }
+
+    /// The number held by this value, for a `number` argument, which
+    /// may have a fraction or not be finite. With `coerce`, a string
+    /// which holds a finite number is also taken as that number.
+    pub(crate) fn decimal(self, coerce: bool) -> Option<Number> {
+        match self {
+            Value::Number(n) => Some(Number::Integer(i128::from(n))),
+            Value::Float(f) => Some(Number::Float(f)),
+            Value::I128(n) => Some(Number::Integer(n)),
+            Value::U128(n) => Some(Number::from(n)),
+            Value::Decimal(d) => Some(Number::Decimal(d)),
+            Value::Str(s) if coerce => {
+                let s = s.trim();
+                if let Ok(n) = s.parse::<i128>() {
+                    Some(Number::Integer(n))
+                } else if let Ok(n) = s.parse::<u128>() {
+                    Some(Number::Unsigned(n))
+                } else {
+                    s.parse()
+                        .ok()
+                        .filter(|f: &f64| f.is_finite())
+                        .map(Number::Float)
+                }
+            }
+            Value::Str(_) | Value::Duration(_) => None,
+        }
+    }
+
+    /// The string held by this value. With `coerce`, a number is also
+    /// taken as its decimal digits.
+    pub(crate) fn string(self, coerce: bool) -> Option<Cow<'a, str>> {
+        match self {
+            Value::Str(s) => Some(Cow::Borrowed(s)),
+            Value::Number(n) if coerce => Some(Cow::Owned(n.to_string())),
+            Value::Float(_) | Value::I128(_) | Value::U128(_) | Value::Decimal(_) if coerce => {
+                Some(Cow::Owned(self.to_string()))
+            }
+            Value::Number(_) | Value::Duration(_) | Value::Float(_) => None,
+            Value::I128(_) | Value::U128(_) | Value::Decimal(_) => None,
+        }
+    }
+
+    /// The duration held by this value. A number which is not negative
+    /// is taken as a number of seconds, and with `coerce`, so is a
+    /// string which holds one.
+    pub(crate) fn duration(self, coerce: bool) -> Option<Duration> {
+        match self {
+            Value::Duration(duration) => Some(duration),
+            Value::Number(n) if n >= 0 => Some(Duration::from_secs(n as u64)),
+            Value::Float(f) => Duration::try_from_secs_f64(f).ok(),
+            Value::I128(n) => u64::try_from(n).ok().map(Duration::from_secs),
+            Value::U128(n) => u64::try_from(n).ok().map(Duration::from_secs),
+            Value::Decimal(_) => self
+                .number(false)
+                .and_then(|n| Value::Number(n).duration(false)),
+            Value::Str(s) if coerce => s.trim().parse().ok().map(Duration::from_secs),
+            Value::Number(_) | Value::Str(_) => None,
+        }
+    }
 }
 
 impl<'a> From<i32> for Value<'a> {
@@ -41,14 +163,53 @@ impl<'a> From<i64> for Value<'a> {
 }
 
 impl<'a> From<u64> for Value<'a> {
+    /// Wrap a value larger than `i64::MAX` as a `Value::U128`, so that
+    /// it keeps its digits.
     fn from(value: u64) -> Value<'a> {
-        Value::Number(value as i64)
+        match i64::try_from(value) {
+            Ok(value) => Value::Number(value),
+            Err(_) => Value::U128(u128::from(value)),
+        }
+    }
+}
+
+impl<'a> From<i128> for Value<'a> {
+    fn from(value: i128) -> Value<'a> {
+        Value::I128(value)
+    }
+}
+
+impl<'a> From<u128> for Value<'a> {
+    fn from(value: u128) -> Value<'a> {
+        Value::U128(value)
     }
 }
 
 impl<'a> From<usize> for Value<'a> {
     fn from(value: usize) -> Value<'a> {
-        Value::Number(value as i64)
+        Value::from(value as u64)
+    }
+}
+
+impl<'a> From<f32> for Value<'a> {
+    /// Wrap the `f64` nearest to the shortest decimal form of `value`,
+    /// so that `0.1f32` is written as `0.1` rather than with the digits
+    /// of its error.
+    fn from(value: f32) -> Value<'a> {
+        let shortest = value.to_string().parse().ok();
+        Value::Float(shortest.unwrap_or_else(|| f64::from(value)))
+    }
+}
+
+impl<'a> From<f64> for Value<'a> {
+    fn from(value: f64) -> Value<'a> {
+        Value::Float(value)
+    }
+}
+
+impl<'a> From<Decimal> for Value<'a> {
+    fn from(value: Decimal) -> Value<'a> {
+        Value::Decimal(value)
     }
 }
 
@@ -58,12 +219,131 @@ impl<'a> From<&'a str> for Value<'a> {
     }
 }
 
+impl<'a> From<Duration> for Value<'a> {
+    fn from(value: Duration) -> Value<'a> {
+        Value::Duration(value)
+    }
+}
+
 impl<'a> fmt::Display for Value<'a> {
     /// Forward `fmt::Display` to the underlying value.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Value::Number(i) => i.fmt(f),
             Value::Str(s) => s.fmt(f),
+            Value::Duration(d) => duration::write_clock(f, d, false),
+            Value::Float(x) => Number::Float(x).write(f, number::ENGLISH, false),
+            Value::I128(n) => n.fmt(f),
+            Value::U128(n) => n.fmt(f),
+            Value::Decimal(d) => Number::Decimal(d).write(f, number::ENGLISH, false),
+        }
+    }
+}
+
+impl<'a> PartialEq for Value<'a> {
+    fn eq(&self, other: &Value<'a>) -> bool {
+        match (*self, *other) {
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Duration(a), Value::Duration(b)) => a == b,
+            // Floats are compared by their bits, so that every value is
+            // equal to itself, as `Eq` requires, even NaN.
+            (Value::Float(a), Value::Float(b)) => a.to_bits() == b.to_bits(),
+            (Value::I128(a), Value::I128(b)) => a == b,
+            (Value::U128(a), Value::U128(b)) => a == b,
+            (Value::Decimal(a), Value::Decimal(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Eq for Value<'a> {}
+
+impl<'a> Hash for Value<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match *self {
+            Value::Number(n) => n.hash(state),
+            Value::Str(s) => s.hash(state),
+            Value::Duration(d) => d.hash(state),
+            Value::Float(x) => x.to_bits().hash(state),
+            Value::I128(n) => n.hash(state),
+            Value::U128(n) => n.hash(state),
+            Value::Decimal(d) => d.hash(state),
+        }
+    }
+}
+
+/// An exact decimal number, such as an amount of money, which keeps the
+/// number of digits after its separator.
+///
+/// The value is `mantissa` divided by 10 to the power of `scale`, so
+/// `12.50` has a mantissa of 1250 and a scale of 2, and is written with
+/// both of its digits after the separator. Decimals with the same value
+/// but a different scale, such as `1.5` and `1.50`, are not equal.
+///
+/// This is the form which the types of decimal crates can be turned
+/// into without losing digits, such as `rust_decimal::Decimal` by its
+/// `mantissa` and `scale`.
+///
+/// ```
+/// use message_format::{icu, Context, Decimal, Value};
+///
+/// let ctx = Context::default();
+/// let m = icu::parse("Total: {total, number}").unwrap();
+/// let total: Decimal = "1234.50".parse().unwrap();
+/// assert_eq!(total, Decimal::new(123450, 2));
+/// let args = [("total", Value::from(total))];
+/// assert_eq!(ctx.format(&m, Some(&args)), "Total: 1,234.50");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Decimal {
+    /// The digits of the number, with its sign.
+    pub mantissa: i128,
+    /// How many of the digits are after the decimal separator.
+    pub scale: u32,
+}
+
+impl Decimal {
+    /// Create a decimal number of `mantissa` divided by 10 to the power
+    /// of `scale`.
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        Decimal { mantissa, scale }
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = Error;
+
+    /// Read a decimal number such as `-12.50`, written with `.` as its
+    /// separator and no grouping, keeping all of its digits.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let offset = s.len() - unsigned.len();
+        let separator = unsigned.find('.').unwrap_or(unsigned.len());
+        if unsigned.len() <= 1 && separator == 0 {
+            return Err(Error::parse(offset, "Expected a digit"));
+        }
+        let mut mantissa: i128 = 0;
+        for (index, byte) in unsigned.bytes().enumerate() {
+            if index == separator {
+                continue;
+            }
+            if !byte.is_ascii_digit() {
+                return Err(Error::parse(offset + index, "Expected a digit"));
+            }
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add(i128::from(byte - b'0')))
+                .ok_or_else(|| Error::parse(offset + index, "Too many digits"))?;
         }
+        let scale = unsigned.len().saturating_sub(separator + 1) as u32;
+        Ok(Decimal::new(
+            if negative { -mantissa } else { mantissa },
+            scale,
+        ))
     }
 }